@@ -1,3 +1,35 @@
+use std::fs;
+use std::path::Path;
+
+// The Python sources bundled as the `src` resource
+const PYTHON_SRC_DIR: &str = "../src";
+
+include!("src/content_hash.rs");
+
+// Embed the hash of every bundled .py file for `verify_install`.
+fn write_resource_checksums() {
+    println!("cargo:rerun-if-changed={}", PYTHON_SRC_DIR);
+    let mut files: Vec<(String, String)> = fs::read_dir(PYTHON_SRC_DIR)
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .filter(|entry| entry.path().extension().is_some_and(|extension| extension == "py"))
+                .filter_map(|entry| {
+                    let contents = fs::read(entry.path()).ok()?;
+                    Some((entry.file_name().to_string_lossy().to_string(), content_hash(&contents)))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+
+    let entries: String = files.iter().map(|(name, hash)| format!("    ({:?}, {:?}),\n", name, hash)).collect();
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    let source = format!("pub const RESOURCE_CHECKSUMS: &[(&str, &str)] = &[\n{}];\n", entries);
+    fs::write(Path::new(&out_dir).join("resource_checksums.rs"), source).unwrap();
+}
+
 fn main() {
+    write_resource_checksums();
     tauri_build::build()
-}
\ No newline at end of file
+}
@@ -0,0 +1,86 @@
+use std::io;
+
+// Cores the runner may be pinned to, as seen by this app
+pub fn core_count() -> usize {
+    std::thread::available_parallelism().map_or(1, |count| count.get())
+}
+
+// Check a list of core indices against the machine.
+pub fn validate(cores: &[usize]) -> Result<(), String> {
+    if cores.is_empty() {
+        return Err("At least one core is required".to_string());
+    }
+    let count = core_count();
+    match cores.iter().find(|&&core| core >= count) {
+        Some(core) => Err(format!("Core {} does not exist, this machine has cores 0-{}", core, count - 1)),
+        None => Ok(()),
+    }
+}
+
+// Restrict the process `pid` to `cores`. Every existing thread is moved;
+// threads it starts afterwards inherit the affinity from their creator.
+#[cfg(target_os = "linux")]
+pub fn set(pid: u32, cores: &[usize]) -> io::Result<()> {
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    for &core in cores {
+        unsafe { libc::CPU_SET(core, &mut set) };
+    }
+
+    let threads: Vec<libc::pid_t> = match std::fs::read_dir(format!("/proc/{}/task", pid)) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+            .collect(),
+        Err(_) => vec![pid as libc::pid_t],
+    };
+    for tid in threads {
+        if unsafe { libc::sched_setaffinity(tid, std::mem::size_of::<libc::cpu_set_t>(), &set) } != 0 {
+            let error = io::Error::last_os_error();
+            // The thread may have exited since it was listed
+            if error.raw_os_error() != Some(libc::ESRCH) {
+                return Err(error);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+pub fn set(pid: u32, cores: &[usize]) -> io::Result<()> {
+    use windows_sys::Win32::Foundation::CloseHandle;
+    use windows_sys::Win32::System::Threading::{
+        OpenProcess, SetProcessAffinityMask, PROCESS_QUERY_LIMITED_INFORMATION, PROCESS_SET_INFORMATION,
+    };
+
+    // A mask only covers the first processor group
+    let bits = usize::BITS as usize;
+    if let Some(core) = cores.iter().find(|&&core| core >= bits) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Core {} is beyond the {} cores an affinity mask can address", core, bits),
+        ));
+    }
+    let mask = cores.iter().fold(0usize, |mask, &core| mask | (1 << core));
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_SET_INFORMATION | PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if handle == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let result = if SetProcessAffinityMask(handle, mask) == 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        };
+        CloseHandle(handle);
+        result
+    }
+}
+
+// macOS only takes affinity hints, which the scheduler is free to ignore
+#[cfg(not(any(target_os = "linux", windows)))]
+pub fn set(_pid: u32, _cores: &[usize]) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "CPU affinity is not supported on this platform",
+    ))
+}
@@ -0,0 +1,105 @@
+use std::fs;
+
+use crate::paths;
+
+// Set on the Python process when its arguments were moved into an args file
+pub const ARGS_FILE_ENV_VAR: &str = "ORIPHIM_ARGS_FILE";
+
+// CreateProcess caps the whole command line at 32767 UTF-16 units.
+#[cfg(windows)]
+fn command_line_limit() -> usize {
+    32767
+}
+
+// execve caps the arguments plus the environment at ARG_MAX bytes.
+#[cfg(unix)]
+fn command_line_limit() -> usize {
+    match unsafe { libc::sysconf(libc::_SC_ARG_MAX) } {
+        limit if limit > 0 => limit as usize,
+        _ => 128 * 1024,
+    }
+}
+
+// Linux also rejects any single argument longer than 32 pages.
+#[cfg(target_os = "linux")]
+const MAX_ARG_LEN: usize = 128 * 1024;
+#[cfg(not(target_os = "linux"))]
+const MAX_ARG_LEN: usize = usize::MAX;
+
+// How much of the limit the command takes: each argument quoted and space
+// separated on Windows; on Unix each string with its NUL and pointer, plus the
+// environment the process inherits.
+#[cfg(windows)]
+fn command_line_size(program: &str, args: &[String], _env_size: usize) -> usize {
+    std::iter::once(program)
+        .chain(args.iter().map(String::as_str))
+        .map(|arg| arg.encode_utf16().count() + 3 + arg.matches(['"', '\\']).count())
+        .sum()
+}
+
+#[cfg(unix)]
+fn command_line_size(program: &str, args: &[String], env_size: usize) -> usize {
+    let pointer = std::mem::size_of::<usize>();
+    std::iter::once(program)
+        .chain(args.iter().map(String::as_str))
+        .map(|arg| arg.len() + 1 + pointer)
+        .sum::<usize>()
+        + env_size
+}
+
+fn fits(program: &str, args: &[String], env_size: usize) -> bool {
+    args.iter().all(|arg| arg.len() < MAX_ARG_LEN) && command_line_size(program, args, env_size) <= command_line_limit()
+}
+
+// Bytes the environment takes up in the new process: the inherited variables
+// plus `extra`, as `NAME=value\0` and a pointer each.
+pub fn env_size<'a>(extra: impl Iterator<Item = (&'a String, &'a String)>) -> usize {
+    let pointer = std::mem::size_of::<usize>();
+    let inherited = std::env::vars_os().map(|(name, value)| name.len() + value.len() + 2 + pointer);
+    let extra = extra.map(|(name, value)| name.len() + value.len() + 2 + pointer);
+    inherited.chain(extra).sum()
+}
+
+// Keep the command within the platform's limit. `args` ends with the
+// `script_args.len()` script arguments; when the whole command is too long
+// they're written to an args file, one per line, and replaced by `@<file>`.
+// Returns the arguments to launch with and the args file, if one was written.
+pub fn fit(
+    program: &str,
+    args: Vec<String>,
+    script_args: usize,
+    env_size: usize,
+) -> Result<(Vec<String>, Option<String>), String> {
+    if fits(program, &args, env_size) {
+        return Ok((args, None));
+    }
+    let too_long = "The command line is too long for this platform";
+    let split = args.len() - script_args;
+    if script_args == 0 {
+        return Err(format!("{} and there are no script_args to move into an args file", too_long));
+    }
+    if args[split..].iter().any(|arg| arg.contains(['\n', '\r'])) {
+        return Err(format!("{} and script_args contains line breaks, so it can't be written to an args file", too_long));
+    }
+    let path = paths::args_file().ok_or("Could not determine the args file location")?;
+    let path_str = path.to_string_lossy().to_string();
+    let mut fitted = args[..split].to_vec();
+    fitted.push(format!("@{}", path_str));
+    if !fits(program, &fitted, env_size + ARGS_FILE_ENV_VAR.len() + path_str.len() + 2) {
+        return Err(format!("{} even with script_args moved into an args file", too_long));
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let mut contents = args[split..].join("\n");
+    contents.push('\n');
+    fs::write(&path, contents).map_err(|e| format!("Failed to write args file {}: {}", path.display(), e))?;
+    Ok((fitted, Some(path_str)))
+}
+
+// Remove the args file left by an earlier start, so the script never reads stale arguments.
+pub fn remove() {
+    if let Some(path) = paths::args_file() {
+        let _ = fs::remove_file(path);
+    }
+}
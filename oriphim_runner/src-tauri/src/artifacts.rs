@@ -0,0 +1,161 @@
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use log::info;
+
+use crate::paths;
+
+// Diagnostic files that pile up under ~/.oriphim (or the workspace's .oriphim).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactKind {
+    // `snapshot_log` copies in logs/snapshots
+    LogSnapshot,
+    // runner.1.log, runner.2.log, ... left by rotation
+    RotatedLog,
+    // One directory per crash from `crash_dump_command`
+    CrashDump,
+    // config.toml.bak and config.toml.v<N>.bak next to config.toml
+    ConfigBackup,
+    // backups/reset-* from `reset_to_defaults`
+    ResetBackup,
+}
+
+const KINDS: [ArtifactKind; 5] = [
+    ArtifactKind::LogSnapshot,
+    ArtifactKind::RotatedLog,
+    ArtifactKind::CrashDump,
+    ArtifactKind::ConfigBackup,
+    ArtifactKind::ResetBackup,
+];
+
+impl ArtifactKind {
+    // Prefix of the artifact's name
+    fn id(self) -> &'static str {
+        match self {
+            ArtifactKind::LogSnapshot => "log_snapshot",
+            ArtifactKind::RotatedLog => "rotated_log",
+            ArtifactKind::CrashDump => "crash_dump",
+            ArtifactKind::ConfigBackup => "config_backup",
+            ArtifactKind::ResetBackup => "reset_backup",
+        }
+    }
+
+    fn dir(self) -> Option<PathBuf> {
+        match self {
+            ArtifactKind::LogSnapshot => paths::logs_dir().map(|dir| dir.join("snapshots")),
+            ArtifactKind::RotatedLog => paths::logs_dir(),
+            ArtifactKind::CrashDump => paths::crashes_dir(),
+            ArtifactKind::ConfigBackup => paths::config_file().and_then(|file| file.parent().map(Path::to_path_buf)),
+            ArtifactKind::ResetBackup => paths::oriphim_dir().map(|dir| dir.join("backups")),
+        }
+    }
+
+    // Whether an entry of the kind's directory belongs to it. Everything else
+    // there, such as the active runner.log, is never listed or deleted.
+    fn matches(self, name: &str, is_dir: bool) -> bool {
+        match self {
+            ArtifactKind::LogSnapshot => !is_dir && name.ends_with(".log"),
+            ArtifactKind::RotatedLog => {
+                !is_dir
+                    && name
+                        .strip_prefix("runner.")
+                        .and_then(|rest| rest.strip_suffix(".log"))
+                        .is_some_and(|index| !index.is_empty() && index.bytes().all(|b| b.is_ascii_digit()))
+            }
+            ArtifactKind::CrashDump => is_dir,
+            ArtifactKind::ConfigBackup => !is_dir && name.starts_with("config.toml") && name.ends_with(".bak"),
+            ArtifactKind::ResetBackup => is_dir && name.starts_with("reset-"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Artifact {
+    // `<kind>/<file name>`, as `delete_artifact` takes it
+    pub name: String,
+    pub kind: ArtifactKind,
+    pub path: String,
+    // Everything inside, for a directory
+    pub size_bytes: u64,
+    pub modified: Option<DateTime<Local>>,
+    pub is_dir: bool,
+}
+
+fn size_of(path: &Path) -> u64 {
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(_) => return 0,
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+    fs::read_dir(path)
+        .map(|entries| entries.flatten().map(|entry| size_of(&entry.path())).sum())
+        .unwrap_or(0)
+}
+
+fn list_kind(kind: ArtifactKind, artifacts: &mut Vec<Artifact>) {
+    let entries = match kind.dir().map(fs::read_dir) {
+        Some(Ok(entries)) => entries,
+        _ => return,
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let metadata = match entry.metadata() {
+            Ok(metadata) => metadata,
+            Err(_) => continue,
+        };
+        if !kind.matches(&name, metadata.is_dir()) {
+            continue;
+        }
+        let path = entry.path();
+        artifacts.push(Artifact {
+            name: format!("{}/{}", kind.id(), name),
+            kind,
+            path: path.to_string_lossy().to_string(),
+            size_bytes: size_of(&path),
+            modified: metadata.modified().ok().map(DateTime::<Local>::from),
+            is_dir: metadata.is_dir(),
+        });
+    }
+}
+
+// Every saved diagnostic file or directory, newest first.
+#[tauri::command]
+pub async fn list_artifacts() -> Result<Vec<Artifact>, String> {
+    let mut artifacts = Vec::new();
+    for kind in KINDS {
+        list_kind(kind, &mut artifacts);
+    }
+    artifacts.sort_by_key(|artifact| std::cmp::Reverse(artifact.modified));
+    Ok(artifacts)
+}
+
+// Delete an artifact by the name `list_artifacts` gave it. The name must be
+// a kind and a single file name, and the entry must be one that kind lists, so
+// nothing outside the artifact directories can be reached.
+#[tauri::command]
+pub async fn delete_artifact(name: String) -> Result<String, String> {
+    let (kind_id, file_name) = name.split_once('/').ok_or_else(|| format!("Invalid artifact name '{}'", name))?;
+    let kind = KINDS
+        .into_iter()
+        .find(|kind| kind.id() == kind_id)
+        .ok_or_else(|| format!("Unknown artifact kind '{}'", kind_id))?;
+    let mut components = Path::new(file_name).components();
+    if !matches!((components.next(), components.next()), (Some(Component::Normal(part)), None) if part == file_name) {
+        return Err(format!("Invalid artifact name '{}'", name));
+    }
+
+    let path = kind.dir().ok_or("Could not find home directory")?.join(file_name);
+    // Not following symlinks: a link is removed itself, never what it points to
+    let metadata = fs::symlink_metadata(&path).map_err(|_| format!("No artifact named '{}'", name))?;
+    if !kind.matches(file_name, metadata.is_dir()) {
+        return Err(format!("'{}' is not a {} artifact", name, kind.id()));
+    }
+    let removed = if metadata.is_dir() { fs::remove_dir_all(&path) } else { fs::remove_file(&path) };
+    removed.map_err(|e| format!("Failed to delete {}: {}", path.display(), e))?;
+    info!("Deleted artifact {}", path.display());
+    Ok(format!("Deleted {}", name))
+}
@@ -0,0 +1,130 @@
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+use log::{info, warn};
+
+use crate::interpreters;
+use crate::paths;
+use crate::RunnerState;
+
+const DEFAULT_RUNS: u32 = 10;
+const MAX_RUNS: u32 = 100;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InterpreterBenchmark {
+    pub path: String,
+    pub version: Option<String>,
+    // "configured" for the interpreter the runner uses, otherwise where
+    // discovery found it
+    pub source: &'static str,
+    // Timed runs after the first
+    pub runs: u32,
+    // The first run, before this benchmark brought the interpreter and its
+    // site-packages into the OS file cache (unless something else already had)
+    pub cold_ms: Option<f64>,
+    pub min_ms: Option<f64>,
+    pub median_ms: Option<f64>,
+    pub max_ms: Option<f64>,
+    // Why the interpreter couldn't be timed
+    pub error: Option<String>,
+}
+
+// Time one `<python> -c pass`, the cost of starting the interpreter and
+// running site.py with nothing else to do.
+fn time_startup(python: &Path) -> Result<Duration, String> {
+    let started = Instant::now();
+    let status = Command::new(python)
+        .args(["-c", "pass"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_err(|e| format!("Failed to run {}: {}", python.display(), e))?;
+    if !status.success() {
+        return Err(format!("{} -c pass exited with {}", python.display(), status));
+    }
+    Ok(started.elapsed())
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+fn benchmark(python: &Path, version: Option<String>, source: &'static str, runs: u32) -> InterpreterBenchmark {
+    let mut result = InterpreterBenchmark {
+        path: python.to_string_lossy().to_string(),
+        version,
+        source,
+        runs: 0,
+        cold_ms: None,
+        min_ms: None,
+        median_ms: None,
+        max_ms: None,
+        error: None,
+    };
+    let timings = time_startup(python).and_then(|cold| {
+        result.cold_ms = Some(millis(cold));
+        (0..runs).map(|_| time_startup(python)).collect::<Result<Vec<_>, _>>()
+    });
+    match timings {
+        Ok(mut warm) => {
+            warm.sort_unstable();
+            result.runs = warm.len() as u32;
+            result.min_ms = warm.first().copied().map(millis);
+            result.median_ms = warm.get(warm.len() / 2).copied().map(millis);
+            result.max_ms = warm.last().copied().map(millis);
+        }
+        Err(e) => result.error = Some(e),
+    }
+    result
+}
+
+// The configured interpreter first, then every other one discovery finds.
+fn benchmark_all(configured: Option<PathBuf>, runs: u32) -> Vec<InterpreterBenchmark> {
+    let mut seen = HashSet::new();
+    let mut results = Vec::new();
+    if let Some(python) = configured {
+        seen.insert(fs::canonicalize(&python).unwrap_or_else(|_| python.clone()));
+        let version = interpreters::python_version(&python);
+        results.push(benchmark(&python, version, "configured", runs));
+    }
+    for interpreter in interpreters::discover() {
+        let python = PathBuf::from(&interpreter.path);
+        if !seen.insert(fs::canonicalize(&python).unwrap_or_else(|_| python.clone())) {
+            continue;
+        }
+        results.push(benchmark(&python, Some(interpreter.version), interpreter.source, runs));
+    }
+    results
+}
+
+// Time how long each interpreter takes to start, `runs` warm runs (default
+// 10) after a cold one, to spot a slow interpreter or heavy site-packages.
+#[tauri::command]
+pub async fn benchmark_interpreter(
+    runs: Option<u32>,
+    app: AppHandle,
+    state: tauri::State<'_, RunnerState>,
+) -> Result<Vec<InterpreterBenchmark>, String> {
+    let runs = runs.unwrap_or(DEFAULT_RUNS);
+    if runs == 0 || runs > MAX_RUNS {
+        return Err(format!("runs must be between 1 and {}", MAX_RUNS));
+    }
+    let config = state.config.lock().unwrap().expanded()?;
+    let working_dir = paths::working_dir(&app, &config);
+    let configured = match interpreters::resolve_interpreter(&config, &working_dir) {
+        Ok(python) => Some(python),
+        Err(e) => {
+            warn!("Not benchmarking the configured interpreter: {}", e);
+            None
+        }
+    };
+    info!("Benchmarking interpreter startup ({} runs each)", runs);
+    tauri::async_runtime::spawn_blocking(move || benchmark_all(configured, runs))
+        .await
+        .map_err(|e| format!("Interpreter benchmark failed: {}", e))
+}
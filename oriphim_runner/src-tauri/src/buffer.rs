@@ -0,0 +1,103 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+
+use crate::output::Stream;
+
+// Larger caps are clamped to this
+pub const MAX_LOG_BUFFER_LINES: usize = 100_000;
+// Cap of the stderr-only buffer, which outlives noisy stdout in the main one
+pub const STDERR_BUFFER_LINES: usize = 1000;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LogLine {
+    pub timestamp: String,
+    pub stream: Stream,
+    pub line: String,
+}
+
+// The most recent output lines from both streams, oldest first. Once `cap`
+// lines are held each new line drops the oldest.
+pub struct LogBuffer {
+    lines: VecDeque<LogLine>,
+    cap: usize,
+    // Lines ever pushed, so callers can tell which lines came after a point
+    pushed: u64,
+}
+
+impl LogBuffer {
+    pub fn new(cap: usize) -> Self {
+        let cap = cap.min(MAX_LOG_BUFFER_LINES);
+        Self {
+            lines: VecDeque::with_capacity(cap.min(4096)),
+            cap,
+            pushed: 0,
+        }
+    }
+
+    // Sequence number the next pushed line will get.
+    pub fn next_seq(&self) -> u64 {
+        self.pushed
+    }
+
+    pub fn push(&mut self, line: LogLine) {
+        if self.cap == 0 {
+            return;
+        }
+        while self.lines.len() >= self.cap {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line);
+        self.pushed += 1;
+    }
+
+    // Change the cap, dropping the oldest lines if shrinking. Returns the cap
+    // actually applied and how many lines were dropped.
+    pub fn set_cap(&mut self, cap: usize) -> (usize, usize) {
+        let cap = cap.min(MAX_LOG_BUFFER_LINES);
+        let dropped = self.lines.len().saturating_sub(cap);
+        self.lines.drain(..dropped);
+        if cap < self.cap {
+            // Give the memory back rather than just the lines
+            self.lines.shrink_to(cap.min(4096));
+        }
+        self.cap = cap;
+        (cap, dropped)
+    }
+
+    // The last `n` lines, oldest first.
+    pub fn tail(&self, n: usize) -> Vec<LogLine> {
+        let skip = self.lines.len().saturating_sub(n);
+        self.lines.iter().skip(skip).cloned().collect()
+    }
+
+    // The last `n` stderr lines, oldest first.
+    pub fn stderr_tail(&self, n: usize) -> Vec<&str> {
+        let mut tail: Vec<&str> = self
+            .lines
+            .iter()
+            .rev()
+            .filter(|line| matches!(line.stream, Stream::Stderr))
+            .take(n)
+            .map(|line| line.line.as_str())
+            .collect();
+        tail.reverse();
+        tail
+    }
+
+    // Up to the last `n` stderr lines pushed at or after `seq`, oldest first.
+    pub fn stderr_since(&self, seq: u64, n: usize) -> Vec<&str> {
+        let first_seq = self.pushed - self.lines.len() as u64;
+        let skip = seq.saturating_sub(first_seq) as usize;
+        let mut tail: Vec<&str> = self
+            .lines
+            .iter()
+            .skip(skip)
+            .rev()
+            .filter(|line| matches!(line.stream, Stream::Stderr))
+            .take(n)
+            .map(|line| line.line.as_str())
+            .collect();
+        tail.reverse();
+        tail
+    }
+}
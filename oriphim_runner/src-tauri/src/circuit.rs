@@ -0,0 +1,125 @@
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use log::{error, info};
+
+use crate::lifecycle::{self, LifecycleKind};
+use crate::RunnerState;
+
+// Automatic restarts from every trigger (crashes, memory_limit_mb, stalls)
+// counted together. Too many within the window trips the breaker, which blocks
+// automatic restarts until `reset_circuit_breaker`.
+#[derive(Default)]
+pub struct CircuitBreaker {
+    restarts: VecDeque<Instant>,
+    tripped_at: Option<DateTime<Local>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CircuitBreakerStatus {
+    pub tripped: bool,
+    pub tripped_at: Option<DateTime<Local>>,
+    // Automatic restarts within the current window
+    pub recent_restarts: usize,
+    pub max_restarts: u32,
+    pub window_secs: u64,
+}
+
+#[derive(Clone, Serialize)]
+struct TrippedEvent {
+    reason: String,
+    restarts: usize,
+    window_secs: u64,
+}
+
+impl CircuitBreaker {
+    pub fn is_tripped(&self) -> bool {
+        self.tripped_at.is_some()
+    }
+
+    fn prune(&mut self, window: Duration) {
+        while self.restarts.front().is_some_and(|at| at.elapsed() > window) {
+            self.restarts.pop_front();
+        }
+    }
+}
+
+// Ask before an automatic restart for `reason` goes ahead. Counts it if allowed;
+// trips the breaker instead when the window is already full.
+pub fn allow_restart(app: &AppHandle, state: &RunnerState, reason: &str) -> bool {
+    let (max_restarts, window) = {
+        let config = state.config.lock().unwrap();
+        (config.circuit_breaker_max_restarts, Duration::from_secs(config.circuit_breaker_window_secs))
+    };
+    let restarts = {
+        let mut breaker = state.circuit_breaker.lock().unwrap();
+        if breaker.is_tripped() {
+            info!("Not restarting ({}): the restart circuit breaker is tripped", reason);
+            return false;
+        }
+        breaker.prune(window);
+        if breaker.restarts.len() < max_restarts as usize {
+            breaker.restarts.push_back(Instant::now());
+            return true;
+        }
+        breaker.tripped_at = Some(Local::now());
+        breaker.restarts.len()
+    };
+
+    let message = format!(
+        "{} automatic restarts within {}s; halting automatic restarts until reset",
+        restarts,
+        window.as_secs()
+    );
+    error!("Restart circuit breaker tripped by {}: {}", reason, message);
+    lifecycle::record(LifecycleKind::Restart, None, None, format!("Circuit breaker tripped: {}", message));
+    let _ = app.emit_all(
+        "circuit-breaker-tripped",
+        TrippedEvent {
+            reason: reason.to_string(),
+            restarts,
+            window_secs: window.as_secs(),
+        },
+    );
+    let _ = app.emit_all("runner-status", crate::runner_status(state));
+    false
+}
+
+fn status(state: &RunnerState) -> CircuitBreakerStatus {
+    let (max_restarts, window_secs) = {
+        let config = state.config.lock().unwrap();
+        (config.circuit_breaker_max_restarts, config.circuit_breaker_window_secs)
+    };
+    let mut breaker = state.circuit_breaker.lock().unwrap();
+    breaker.prune(Duration::from_secs(window_secs));
+    CircuitBreakerStatus {
+        tripped: breaker.is_tripped(),
+        tripped_at: breaker.tripped_at,
+        recent_restarts: breaker.restarts.len(),
+        max_restarts,
+        window_secs,
+    }
+}
+
+#[tauri::command]
+pub async fn get_circuit_breaker(state: tauri::State<'_, RunnerState>) -> Result<CircuitBreakerStatus, String> {
+    Ok(status(&state))
+}
+
+// Close the breaker and forget the restarts counted so far.
+#[tauri::command]
+pub async fn reset_circuit_breaker(app: AppHandle, state: tauri::State<'_, RunnerState>) -> Result<String, String> {
+    {
+        let mut breaker = state.circuit_breaker.lock().unwrap();
+        if !breaker.is_tripped() {
+            return Err("The restart circuit breaker is not tripped".to_string());
+        }
+        *breaker = CircuitBreaker::default();
+    }
+    info!("Restart circuit breaker reset");
+    lifecycle::record(LifecycleKind::Restart, None, None, "Circuit breaker reset");
+    let _ = app.emit_all("runner-status", crate::runner_status(&state));
+    Ok("Automatic restarts re-enabled".to_string())
+}
@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::interpreters::CONDA_DIRS;
+
+#[cfg(windows)]
+const CONDA_NAMES: &[&str] = &["conda.exe", "conda.bat"];
+#[cfg(not(windows))]
+const CONDA_NAMES: &[&str] = &["conda"];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CondaEnv {
+    pub name: String,
+    pub prefix: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CondaInstall {
+    pub conda: String,
+    pub version: Option<String>,
+    pub envs: Vec<CondaEnv>,
+}
+
+// The parts of `conda info --json` we use
+#[derive(Deserialize)]
+struct CondaInfo {
+    conda_version: Option<String>,
+    root_prefix: Option<String>,
+    #[serde(default)]
+    envs: Vec<String>,
+}
+
+// The conda executable: $CONDA_EXE (set by an activated shell), then PATH,
+// then the usual install locations under the home directory.
+pub fn find() -> Option<PathBuf> {
+    if let Some(exe) = env::var_os("CONDA_EXE").map(PathBuf::from).filter(|exe| exe.is_file()) {
+        return Some(exe);
+    }
+    let mut dirs: Vec<PathBuf> = env::var_os("PATH")
+        .map(|path| env::split_paths(&path).collect())
+        .unwrap_or_default();
+    if let Some(home) = dirs::home_dir() {
+        for name in CONDA_DIRS {
+            let base = home.join(name);
+            dirs.extend(["bin", "condabin", "Scripts"].iter().map(|sub| base.join(sub)));
+        }
+    }
+    dirs.iter()
+        .flat_map(|dir| CONDA_NAMES.iter().map(move |name| dir.join(name)))
+        .find(|path| path.is_file())
+}
+
+// Arguments to `conda` that run `script` in `env`. --no-capture-output streams
+// output as it's printed instead of when the process exits.
+pub fn run_args(env: &str, script: &str) -> Vec<String> {
+    ["run", "-n", env, "--no-capture-output", "python", script]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect()
+}
+
+fn info(conda: &Path) -> Result<CondaInstall, String> {
+    let output = Command::new(conda)
+        .args(["info", "--json"])
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", conda.display(), e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "`conda info` exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let info: CondaInfo =
+        serde_json::from_slice(&output.stdout).map_err(|e| format!("Unexpected `conda info` output: {}", e))?;
+
+    let envs = info
+        .envs
+        .iter()
+        .map(|prefix| {
+            let name = if info.root_prefix.as_deref() == Some(prefix.as_str()) {
+                "base".to_string()
+            } else {
+                Path::new(prefix)
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_else(|| prefix.clone())
+            };
+            CondaEnv {
+                name,
+                prefix: prefix.clone(),
+            }
+        })
+        .collect();
+    Ok(CondaInstall {
+        conda: conda.to_string_lossy().to_string(),
+        version: info.conda_version,
+        envs,
+    })
+}
+
+// Find the conda install and list its environments, for picking `conda_env`.
+#[tauri::command]
+pub async fn detect_conda() -> Result<CondaInstall, String> {
+    tauri::async_runtime::spawn_blocking(|| {
+        let conda = find().ok_or("Conda is not installed: no conda on PATH, in CONDA_EXE or in the usual install locations")?;
+        info(&conda)
+    })
+    .await
+    .map_err(|e| format!("Conda detection failed: {}", e))?
+}
@@ -0,0 +1,954 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fmt;
+use std::fs;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+use log::{info, warn};
+
+use crate::affinity;
+use crate::buffer::MAX_LOG_BUFFER_LINES;
+use crate::conda;
+use crate::idle::IdleSignal;
+use crate::interpreters;
+use crate::launcher;
+use crate::logs::{LogFormat, DEFAULT_LOG_FORMAT};
+use crate::migrate::{self, CONFIG_VERSION};
+use crate::output::WatchMatcher;
+use crate::paths;
+use crate::readiness;
+
+// Stands in for secret values in an exported config
+pub const REDACTED: &str = "<redacted>";
+// Variables whose names contain one of these are treated as secrets
+const SECRET_NAME_HINTS: &[&str] = &["KEY", "TOKEN", "SECRET", "PASSWORD", "PASSWD", "CREDENTIAL", "AUTH"];
+
+// Sampling faster than this costs more than the chart gains.
+const MIN_METRICS_INTERVAL_MS: u64 = 250;
+
+// Settings only read when the runner starts. Changing one while it's running
+// takes effect from the next start; everything else applies immediately.
+pub const RESTART_REQUIRED_FIELDS: &[&str] = &[
+    "python_path",
+    "conda_env",
+    "launcher",
+    "script",
+    "script_args",
+    "working_dir",
+    "env_bootstrap",
+    "elevated",
+    "elevation_command",
+    "capture_output",
+    "terminal",
+    "output_redirect",
+    "merge_output",
+    "log_stdout_to_file",
+    "log_stderr_to_file",
+    "line_buffered",
+    "output_max_lines_per_sec",
+    "carriage_return",
+    "notify_on_pattern",
+    "cpu_affinity",
+    "metrics_enabled",
+    "metrics_interval_ms",
+    "ready_marker",
+    "readiness_probe",
+    "health_port",
+    "ready_timeout_secs",
+    "heartbeat_file",
+    "heartbeat_timeout_secs",
+    "restart_on_stall",
+    "idle_shutdown_secs",
+    "max_runtime_secs",
+    "idle_signal",
+    "idle_health_path",
+];
+
+// Runner configuration, stored as TOML in ~/.oriphim/config.toml.
+// Every field has a default so a partial (or missing) file is valid.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RunnerConfig {
+    // Schema version of the file; older files are migrated when loaded.
+    pub config_version: u32,
+    // Interpreter used to run main.py; `python` from PATH when unset.
+    pub python_path: Option<String>,
+    // Run main.py with `conda run -n <conda_env>` instead of `python_path`.
+    pub conda_env: Option<String>,
+    // Wrapper the interpreter is launched through, e.g. `poetry run`; it gets the
+    // interpreter and script as its arguments.
+    pub launcher: Option<String>,
+    // The script to run, relative to working_dir unless absolute.
+    pub script: String,
+    // Extra arguments passed to main.py. When they'd make the command line too
+    // long they go in an args file instead, passed as `@<file>`.
+    pub script_args: Vec<String>,
+    // Directory main.py is run from; the bundled `src` directory when unset.
+    pub working_dir: Option<String>,
+    // Shell command run before every start, e.g. `. ./.env` or `conda activate trading`.
+    // It runs under `sh` on Unix (use the POSIX `.` rather than `source`), `cmd` on Windows.
+    // The environment it exports is merged into the Python process's environment.
+    pub env_bootstrap: Option<String>,
+    // Run main.py with elevated privileges. Windows shows a UAC prompt; on Unix
+    // `elevation_command` (pkexec, `sudo -A`, ...) is put in front of the interpreter.
+    pub elevated: bool,
+    pub elevation_command: String,
+    // Stop the Python process together with any subprocesses it spawned.
+    pub kill_process_tree: bool,
+    // Signal sent to ask the Python process to exit before it is force-killed
+    // `stop_grace_secs` later. Ignored on Windows, which always sends CTRL_BREAK.
+    pub stop_signal: StopSignal,
+    pub stop_grace_secs: u64,
+    // Shown as a desktop notification when a stop has to force-kill the runner;
+    // `{grace}` is replaced with the grace period. Empty to suppress.
+    pub force_kill_message: String,
+    // Read the runner's stdout/stderr into events, the log file and the buffers.
+    // When false the runner inherits the app's stdio and nothing is read.
+    pub capture_output: bool,
+    // Run main.py in a terminal window (a console on Windows, Terminal.app,
+    // x-terminal-emulator) for interactive debugging. Its output isn't captured.
+    pub terminal: bool,
+    // Send stdout and stderr straight to this file (appended to; relative paths are
+    // taken from working_dir) instead of reading them. Nothing reaches the UI or runner log.
+    pub output_redirect: Option<PathBuf>,
+    // Interleave stdout and stderr in arrival order as a single `runner-output`
+    // event stream instead of separate `runner-stdout`/`runner-stderr` events.
+    pub merge_output: bool,
+    // Give each restart's output a fresh console (`console-new-session`) instead
+    // of continuing the current one after a separator (`console-continued`).
+    pub console_per_restart: bool,
+    // Which streams are written to the log file. Both still reach the UI and buffers.
+    pub log_stdout_to_file: bool,
+    pub log_stderr_to_file: bool,
+    // Low-latency output: runs Python unbuffered (PYTHONUNBUFFERED, unless the
+    // environment sets it) and reads in small chunks. Off trades latency for throughput.
+    pub line_buffered: bool,
+    // Beyond this many lines per second (both streams together), lines are
+    // dropped from events and the log file, reported by `runner-output-throttled`.
+    // Watch patterns still run on dropped lines.
+    pub output_max_lines_per_sec: Option<u32>,
+    // What to do with a bare `\r` in output, as progress bars print to redraw a line.
+    pub carriage_return: CarriageReturnMode,
+    // Output lines matching any of these emit a `pattern-matched` event.
+    pub watch_patterns: Vec<WatchPattern>,
+    // Also show a desktop notification when a watch pattern matches, at most
+    // one per pattern every 30 seconds.
+    pub notify_on_pattern: bool,
+    // Show a desktop notification when the runner crashes.
+    pub notify_on_crash: bool,
+    // Rotate ~/.oriphim/logs/runner.log once it reaches this size, keeping `log_max_files` old files.
+    pub log_max_bytes: u64,
+    pub log_max_files: usize,
+    // Layout of each output line in the log file (and `formatted` on output
+    // events), from {timestamp}, {level}, {source} and {message}.
+    pub log_format: String,
+    // Recent output lines kept in memory for error reports.
+    pub log_buffer_lines: usize,
+    // Also send lifecycle events (and with `system_log_stderr`, the Python
+    // process's stderr) to syslog on Unix or the Windows Event Log.
+    pub system_log: bool,
+    pub system_log_stderr: bool,
+    // Boot to the system tray without showing the main window.
+    pub start_hidden: bool,
+    // Prevent the system from sleeping while the runner is running. Applies to
+    // a running process straight away.
+    pub keep_awake: bool,
+    // Pin the Python process to these core indices. All cores when unset.
+    pub cpu_affinity: Option<Vec<usize>>,
+    // Emit `runner-metrics` (memory, CPU, threads, handles) every `metrics_interval_ms` while running.
+    pub metrics_enabled: bool,
+    pub metrics_interval_ms: u64,
+    // Safe-restart the runner once its memory stays above `memory_limit_mb` for
+    // `memory_limit_secs`. Needs `metrics_enabled`.
+    pub memory_limit_mb: Option<u64>,
+    pub memory_limit_secs: u64,
+    // Emit `runner-thread-warning` when the runner has more threads than this,
+    // to catch thread leaks. Needs `metrics_enabled`.
+    pub thread_count_warn: Option<usize>,
+    // Append a metrics sample every `metrics_history_interval_secs` to
+    // ~/.oriphim/metrics.jsonl, to spot slow leaks over days. Needs `metrics_enabled`.
+    pub metrics_history: bool,
+    pub metrics_history_interval_secs: u64,
+    // Rotate metrics.jsonl once it reaches this size, keeping one old file
+    pub metrics_history_max_bytes: u64,
+    // Enable troubleshooting commands that run arbitrary code, such as `run_python_oneshot`.
+    pub advanced_mode: bool,
+    // Accept start/stop/restart/status requests from local tools on ~/.oriphim/control.sock
+    // (a named pipe on Windows). Read when the app starts.
+    pub control_socket: bool,
+    // Serve Prometheus metrics at `GET /metrics` on `metrics_bind`:`metrics_port`.
+    // There is no authentication. Read when the app starts.
+    pub metrics_port: Option<u16>,
+    pub metrics_bind: String,
+    // The runner counts as ready once stdout contains `ready_marker` and/or
+    // `health_port` accepts TCP connections on localhost. With neither set it
+    // is ready once it has stayed up for a couple of seconds.
+    pub ready_marker: Option<String>,
+    pub health_port: Option<u16>,
+    // A single readiness check used instead of ready_marker/health_port.
+    pub readiness_probe: Option<ReadinessProbe>,
+    pub ready_timeout_secs: u64,
+    // Emit `runner-slow-start` when a start takes longer than this to become ready.
+    pub startup_latency_warn_ms: Option<u64>,
+    // A file the Python process touches periodically (its path is also passed
+    // in ORIPHIM_HEARTBEAT_FILE). Going `heartbeat_timeout_secs` without a touch
+    // emits `runner-stalled`, and safe-restarts the runner with `restart_on_stall`.
+    pub heartbeat_file: Option<PathBuf>,
+    pub heartbeat_timeout_secs: u64,
+    pub restart_on_stall: bool,
+    // Stop the runner once it has been idle for `idle_shutdown_secs`: no stdout, and/or
+    // `idle_health_path` on `health_port` answering `idle`, as chosen by `idle_signal`.
+    pub idle_shutdown_secs: Option<u64>,
+    pub idle_signal: IdleSignal,
+    pub idle_health_path: String,
+    // Gracefully stop the runner after it has run this long, for bounded batch jobs.
+    // Not treated as a crash, so auto_restart doesn't start it again.
+    pub max_runtime_secs: Option<u64>,
+    // Try spawning the interpreter this many times, `spawn_retry_delay_ms` apart,
+    // before a start fails; covers antivirus scans and filesystems still mounting at boot.
+    pub spawn_attempts: u32,
+    pub spawn_retry_delay_ms: u64,
+    // Fingerprint main.py, the interpreter version and `pip freeze` after each
+    // start, emitting `environment-changed` when it differs from the last start's.
+    pub fingerprint_on_start: bool,
+    // Restart the runner automatically when it crashes, waiting
+    // `restart_delay_secs` (doubling per consecutive crash, up to a minute)
+    // and giving up after `restart_max_attempts` crashes in a row.
+    pub auto_restart: bool,
+    pub restart_on_exit_codes: ExitCodeFilter,
+    pub restart_delay_secs: u64,
+    pub restart_max_attempts: u32,
+    // Across every restart trigger, allow at most `circuit_breaker_max_restarts`
+    // automatic restarts per `circuit_breaker_window_secs`; more trips a breaker
+    // that holds off automatic restarts until `reset_circuit_breaker`.
+    pub circuit_breaker_max_restarts: u32,
+    pub circuit_breaker_window_secs: u64,
+    // Run this shell command after a crash to collect a core dump or other
+    // evidence into ORIPHIM_CRASH_DIR, keeping the newest `crash_dump_max_count`.
+    pub crash_dump_command: Option<String>,
+    pub crash_dump_max_count: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CarriageReturnMode {
+    // Leave it in the line
+    Keep,
+    // Keep only the text after the last one, as a terminal would show it
+    Last,
+    // Like `Last`, plus a `runner-overwrite` event for every redraw as it happens
+    Overwrite,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum StopSignal {
+    Term,
+    Int,
+    Quit,
+}
+
+// How to tell the runner is ready, e.g. `{ type = "http-url", url = "http://127.0.0.1:8765/health" }`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum ReadinessProbe {
+    // stdout contains `marker`
+    StdoutMarker { marker: String },
+    // `host:port` accepts TCP connections
+    TcpPort {
+        #[serde(default = "default_probe_host")]
+        host: String,
+        port: u16,
+    },
+    // A GET of `url` (plain http) answers 2xx
+    HttpUrl { url: String },
+    // `path`, relative to the working directory, exists
+    FileExists { path: String },
+}
+
+fn default_probe_host() -> String {
+    "127.0.0.1".to_string()
+}
+
+// Either a plain substring (`"FATAL"`) or a regex table (`{ regex = "dead\\s*lock" }`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum WatchPattern {
+    Substring(String),
+    Regex { regex: String },
+}
+
+// Which non-zero exit codes trigger an auto-restart: either only the listed
+// codes (`[1, 75]`) or every code except the listed ones (`{ except = [2, 3] }`).
+// A process killed by a signal has no exit code and only restarts under `except`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ExitCodeFilter {
+    Only(Vec<i32>),
+    Except { except: Vec<i32> },
+}
+
+impl ExitCodeFilter {
+    pub fn should_restart(&self, code: Option<i32>) -> bool {
+        match (self, code) {
+            (_, Some(0)) => false,
+            (ExitCodeFilter::Only(codes), Some(code)) => codes.contains(&code),
+            (ExitCodeFilter::Only(_), None) => false,
+            (ExitCodeFilter::Except { except }, Some(code)) => !except.contains(&code),
+            (ExitCodeFilter::Except { .. }, None) => true,
+        }
+    }
+}
+
+impl Default for RunnerConfig {
+    fn default() -> Self {
+        Self {
+            config_version: CONFIG_VERSION,
+            python_path: None,
+            conda_env: None,
+            launcher: None,
+            script: "main.py".to_string(),
+            script_args: Vec::new(),
+            working_dir: None,
+            env_bootstrap: None,
+            elevated: false,
+            elevation_command: "pkexec".to_string(),
+            kill_process_tree: true,
+            // SIGINT raises KeyboardInterrupt, which most scripts already handle
+            stop_signal: StopSignal::Int,
+            stop_grace_secs: 10,
+            force_kill_message: "The Python runner did not exit within {grace}s and was force-killed. Unsaved data may have been lost.".to_string(),
+            capture_output: true,
+            terminal: false,
+            output_redirect: None,
+            merge_output: false,
+            console_per_restart: false,
+            log_stdout_to_file: true,
+            log_stderr_to_file: true,
+            line_buffered: true,
+            output_max_lines_per_sec: None,
+            carriage_return: CarriageReturnMode::Last,
+            watch_patterns: Vec::new(),
+            notify_on_pattern: false,
+            notify_on_crash: false,
+            log_max_bytes: 10 * 1024 * 1024,
+            log_max_files: 10,
+            log_format: DEFAULT_LOG_FORMAT.to_string(),
+            log_buffer_lines: 1000,
+            system_log: false,
+            system_log_stderr: false,
+            start_hidden: false,
+            keep_awake: false,
+            cpu_affinity: None,
+            metrics_enabled: true,
+            metrics_interval_ms: 2000,
+            memory_limit_mb: None,
+            thread_count_warn: None,
+            memory_limit_secs: 60,
+            metrics_history: false,
+            metrics_history_interval_secs: 60,
+            metrics_history_max_bytes: 10 * 1024 * 1024,
+            advanced_mode: false,
+            control_socket: false,
+            metrics_port: None,
+            metrics_bind: "127.0.0.1".to_string(),
+            ready_marker: None,
+            health_port: None,
+            readiness_probe: None,
+            ready_timeout_secs: 30,
+            startup_latency_warn_ms: None,
+            heartbeat_file: None,
+            heartbeat_timeout_secs: 60,
+            restart_on_stall: false,
+            idle_shutdown_secs: None,
+            max_runtime_secs: None,
+            idle_signal: IdleSignal::Output,
+            idle_health_path: "/idle".to_string(),
+            spawn_attempts: 3,
+            spawn_retry_delay_ms: 500,
+            fingerprint_on_start: true,
+            auto_restart: true,
+            restart_on_exit_codes: ExitCodeFilter::Except { except: Vec::new() },
+            restart_delay_secs: 2,
+            restart_max_attempts: 5,
+            circuit_breaker_max_restarts: 10,
+            circuit_breaker_window_secs: 600,
+            crash_dump_command: None,
+            crash_dump_max_count: 5,
+        }
+    }
+}
+
+impl RunnerConfig {
+    // Read ~/.oriphim/config.toml; a missing file gives the defaults. An
+    // invalid file is copied to config.toml.bak before the error is returned,
+    // so nothing written later can lose the user's edits.
+    pub fn load() -> Result<Self, ConfigError> {
+        let path = match paths::config_file() {
+            Some(path) => path,
+            None => {
+                warn!("Could not find home directory, using default config");
+                return Ok(Self::default());
+            }
+        };
+
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(_) => return Ok(Self::default()),
+        };
+        let contents = migrate::upgrade(&path, contents);
+        match toml::from_str(&contents) {
+            Ok(config) => {
+                info!("Loaded config from {}", path.display());
+                if let Err(e) = Self::expanded(&config) {
+                    warn!("{}: {}", path.display(), e);
+                }
+                Ok(config)
+            }
+            Err(e) => {
+                let error = ConfigError::new(&path, &contents, &e);
+                warn!("{}", error);
+                Err(error)
+            }
+        }
+    }
+
+    // Write the config back to ~/.oriphim/config.toml. Comments in a
+    // hand-edited file are not preserved.
+    pub fn save(&self) -> Result<(), String> {
+        let path = paths::config_file().ok_or("Could not find home directory")?;
+        // An invalid file on disk holds edits the user hasn't fixed yet
+        if let Ok(contents) = fs::read_to_string(&path) {
+            if toml::from_str::<RunnerConfig>(&contents).is_err() {
+                backup(&path).map_err(|e| format!("Not overwriting invalid {}: backup failed: {}", path.display(), e))?;
+            }
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let contents = toml::to_string_pretty(self).map_err(|e| format!("Failed to serialize config: {}", e))?;
+        fs::write(&path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        info!("Saved config to {}", path.display());
+        Ok(())
+    }
+
+    // Check the config for problems without applying it. Errors would break the
+    // next start; warnings are legal but probably not what the user meant.
+    pub fn validate(&self) -> Vec<ConfigIssue> {
+        let mut issues = Vec::new();
+
+        if let Some(working_dir) = &self.working_dir {
+            match expand(working_dir) {
+                Ok(dir) if !Path::new(&dir).is_dir() => {
+                    issues.push(ConfigIssue::error("working_dir", &format!("{} is not a directory", dir)));
+                }
+                Err(e) => issues.push(ConfigIssue::error("working_dir", &e)),
+                Ok(_) => {}
+            }
+        }
+        for (index, arg) in self.script_args.iter().enumerate() {
+            if let Err(e) = expand(arg) {
+                issues.push(ConfigIssue::error("script_args", &format!("Argument {}: {}", index + 1, e)));
+            }
+        }
+        if let Some(python_path) = &self.python_path {
+            match expand(python_path) {
+                Ok(path) if interpreters::resolve_python_path(&path).is_none() => {
+                    issues.push(ConfigIssue::error("python_path", &format!("{} does not exist", path)));
+                }
+                Err(e) => issues.push(ConfigIssue::error("python_path", &e)),
+                Ok(_) => {}
+            }
+        }
+        if let Some(env) = &self.conda_env {
+            if env.trim().is_empty() {
+                issues.push(ConfigIssue::error("conda_env", "Environment name is empty"));
+            } else if conda::find().is_none() {
+                issues.push(ConfigIssue::error("conda_env", "Conda is not installed"));
+            }
+            if self.python_path.is_some() {
+                issues.push(ConfigIssue::warning("python_path", "Ignored while conda_env is set"));
+            }
+        }
+        if let Some(launcher) = &self.launcher {
+            match expand(launcher) {
+                Ok(launcher) => match launcher.split_whitespace().next() {
+                    None => issues.push(ConfigIssue::error("launcher", "Launcher is empty")),
+                    Some(wrapper) if launcher::find(wrapper).is_none() => {
+                        issues.push(ConfigIssue::error("launcher", &format!("{} was not found", wrapper)));
+                    }
+                    Some(_) => {}
+                },
+                Err(e) => issues.push(ConfigIssue::error("launcher", &e)),
+            }
+        }
+        if let Some(Err(e)) = self.heartbeat_file.as_ref().map(|path| expand(&path.to_string_lossy())) {
+            issues.push(ConfigIssue::error("heartbeat_file", &e));
+        }
+        if let Err(e) = expand(&self.elevation_command) {
+            issues.push(ConfigIssue::error("elevation_command", &e));
+        }
+        if self.script.trim().is_empty() {
+            issues.push(ConfigIssue::error("script", "Script path is empty"));
+        } else if let Err(e) = expand(&self.script) {
+            issues.push(ConfigIssue::error("script", &e));
+        }
+        if let Some(path) = &self.output_redirect {
+            if path.as_os_str().is_empty() {
+                issues.push(ConfigIssue::error("output_redirect", "File path is empty"));
+            } else if let Err(e) = expand(&path.to_string_lossy()) {
+                issues.push(ConfigIssue::error("output_redirect", &e));
+            }
+        }
+
+        if let Some(bootstrap) = &self.env_bootstrap {
+            if bootstrap.trim().is_empty() {
+                issues.push(ConfigIssue::error("env_bootstrap", "Bootstrap command is empty"));
+            }
+        }
+
+        for (index, pattern) in self.watch_patterns.iter().enumerate() {
+            let field = format!("watch_patterns[{}]", index);
+            match pattern {
+                WatchPattern::Substring(text) if text.is_empty() => {
+                    issues.push(ConfigIssue::warning(&field, "Empty pattern matches every line"));
+                }
+                WatchPattern::Regex { .. } => {
+                    if let Err(e) = WatchMatcher::compile(pattern) {
+                        issues.push(ConfigIssue::error(&field, &format!("Invalid regex: {}", e)));
+                    }
+                }
+                _ => {}
+            }
+        }
+        if self.notify_on_pattern && self.watch_patterns.is_empty() {
+            issues.push(ConfigIssue::warning("notify_on_pattern", "No watch_patterns are configured"));
+        }
+
+        if self.stop_grace_secs == 0 {
+            issues.push(ConfigIssue::warning(
+                "stop_grace_secs",
+                "The runner will be force-killed without a chance to shut down",
+            ));
+        }
+
+        if self.log_max_bytes == 0 {
+            issues.push(ConfigIssue::warning("log_max_bytes", "Log rotation is disabled"));
+        } else if self.log_max_bytes < 64 * 1024 {
+            issues.push(ConfigIssue::warning("log_max_bytes", "Logs will rotate very frequently"));
+        }
+        if self.output_max_lines_per_sec == Some(0) {
+            issues.push(ConfigIssue::error("output_max_lines_per_sec", "Must be greater than 0"));
+        }
+        if let Err(e) = LogFormat::parse(&self.log_format) {
+            issues.push(ConfigIssue::error("log_format", &e));
+        }
+        if self.log_max_files == 0 {
+            issues.push(ConfigIssue::warning("log_max_files", "Rotated logs will be deleted immediately"));
+        }
+
+        if let Some(probe) = &self.readiness_probe {
+            if self.ready_marker.is_some() || self.health_port.is_some() {
+                issues.push(ConfigIssue::warning("readiness_probe", "Replaces ready_marker and health_port for readiness"));
+            }
+            match probe {
+                ReadinessProbe::StdoutMarker { marker } if marker.is_empty() => {
+                    issues.push(ConfigIssue::warning("readiness_probe", "Empty marker matches the first line of output"));
+                }
+                ReadinessProbe::StdoutMarker { .. } if !self.captures_output() => {
+                    issues.push(ConfigIssue::error("readiness_probe", "A stdout marker needs capture_output"));
+                }
+                ReadinessProbe::TcpPort { port: 0, .. } => {
+                    issues.push(ConfigIssue::error("readiness_probe", "Port 0 is not a valid port"));
+                }
+                ReadinessProbe::HttpUrl { url } => {
+                    if let Err(e) = readiness::parse_http_url(url) {
+                        issues.push(ConfigIssue::error("readiness_probe", &e));
+                    }
+                }
+                ReadinessProbe::FileExists { path } if path.is_empty() => {
+                    issues.push(ConfigIssue::error("readiness_probe", "File path is empty"));
+                }
+                _ => {}
+            }
+        }
+        if self.terminal && self.elevated {
+            issues.push(ConfigIssue::error("terminal", "Can't be combined with elevated"));
+        }
+        if self.terminal && self.output_redirect.is_some() {
+            issues.push(ConfigIssue::error("output_redirect", "Can't be combined with terminal"));
+        }
+        if !self.captures_output() {
+            if self.ready_marker.is_some() {
+                issues.push(ConfigIssue::error("ready_marker", "Needs capture_output to see the runner's output"));
+            }
+            if self.idle_shutdown_secs.is_some() && self.idle_signal != IdleSignal::Health {
+                issues.push(ConfigIssue::error("idle_signal", "The output signal needs capture_output"));
+            }
+            if !self.watch_patterns.is_empty() {
+                issues.push(ConfigIssue::warning("watch_patterns", "Never match without capture_output"));
+            }
+        }
+        if matches!(&self.ready_marker, Some(marker) if marker.is_empty()) {
+            issues.push(ConfigIssue::warning("ready_marker", "Empty marker matches the first line of output"));
+        }
+        if self.metrics_port == Some(0) {
+            issues.push(ConfigIssue::error("metrics_port", "Port 0 is not a valid metrics port"));
+        }
+        match self.metrics_bind.parse::<IpAddr>() {
+            Err(_) => issues.push(ConfigIssue::error("metrics_bind", "Must be an IP address such as 127.0.0.1")),
+            Ok(address) if self.metrics_port.is_some() && !address.is_loopback() => {
+                issues.push(ConfigIssue::warning("metrics_bind", "Anyone who can reach this address can read the metrics"));
+            }
+            Ok(_) => {}
+        }
+        if self.health_port == Some(0) {
+            issues.push(ConfigIssue::error("health_port", "Port 0 is not a valid health check port"));
+        }
+        if self.heartbeat_file.is_some() && self.heartbeat_timeout_secs == 0 {
+            issues.push(ConfigIssue::error("heartbeat_timeout_secs", "Must be greater than 0"));
+        }
+        if self.restart_on_stall && self.heartbeat_file.is_none() {
+            issues.push(ConfigIssue::warning("restart_on_stall", "Has no effect without heartbeat_file"));
+        }
+        if self.idle_shutdown_secs == Some(0) {
+            issues.push(ConfigIssue::error("idle_shutdown_secs", "Must be greater than 0"));
+        }
+        if self.max_runtime_secs == Some(0) {
+            issues.push(ConfigIssue::error("max_runtime_secs", "Must be greater than 0"));
+        }
+        if self.idle_shutdown_secs.is_some() && self.idle_signal != IdleSignal::Output && self.health_port.is_none() {
+            issues.push(ConfigIssue::error("idle_signal", "The health signal needs health_port"));
+        }
+        if !self.idle_health_path.starts_with('/') {
+            issues.push(ConfigIssue::error("idle_health_path", "Must start with /"));
+        }
+        if self.ready_timeout_secs == 0 {
+            issues.push(ConfigIssue::warning("ready_timeout_secs", "Readiness will time out immediately"));
+        }
+
+        if let ExitCodeFilter::Only(codes) = &self.restart_on_exit_codes {
+            if codes.contains(&0) {
+                issues.push(ConfigIssue::warning("restart_on_exit_codes", "Exit code 0 never triggers a restart"));
+            }
+            if codes.is_empty() && self.auto_restart {
+                issues.push(ConfigIssue::warning("restart_on_exit_codes", "No exit code will trigger a restart"));
+            }
+        }
+        if self.spawn_attempts == 0 {
+            issues.push(ConfigIssue::error("spawn_attempts", "Must be at least 1"));
+        }
+        if self.circuit_breaker_window_secs == 0 {
+            issues.push(ConfigIssue::error("circuit_breaker_window_secs", "Must be greater than 0"));
+        }
+        if self.circuit_breaker_max_restarts == 0 {
+            issues.push(ConfigIssue::warning("circuit_breaker_max_restarts", "Every automatic restart trips the breaker"));
+        }
+        if self.auto_restart && self.restart_max_attempts == 0 {
+            issues.push(ConfigIssue::warning("restart_max_attempts", "Auto-restart will never make an attempt"));
+        }
+
+        if self.metrics_enabled && self.metrics_interval_ms < MIN_METRICS_INTERVAL_MS {
+            issues.push(ConfigIssue::warning(
+                "metrics_interval_ms",
+                &format!("Intervals below {}ms are raised to {}ms", MIN_METRICS_INTERVAL_MS, MIN_METRICS_INTERVAL_MS),
+            ));
+        }
+
+        if let Some(cores) = &self.cpu_affinity {
+            if let Err(message) = affinity::validate(cores) {
+                issues.push(ConfigIssue::error("cpu_affinity", &message));
+            }
+        }
+
+        if self.elevated {
+            if cfg!(not(windows)) && self.elevation_command.trim().is_empty() {
+                issues.push(ConfigIssue::error("elevation_command", "Must name a program such as pkexec or sudo"));
+            }
+            if self.env_bootstrap.is_some() || self.heartbeat_file.is_some() {
+                issues.push(ConfigIssue::warning(
+                    "elevated",
+                    "Most elevation tools drop environment variables, including env_bootstrap's and ORIPHIM_HEARTBEAT_FILE",
+                ));
+            }
+        }
+
+        if self.system_log_stderr && !self.system_log {
+            issues.push(ConfigIssue::warning("system_log_stderr", "Has no effect without system_log"));
+        }
+
+        if self.memory_limit_mb.is_some() && !self.metrics_enabled {
+            issues.push(ConfigIssue::warning(
+                "memory_limit_mb",
+                "The memory limit is only enforced with metrics_enabled",
+            ));
+        }
+        if self.memory_limit_mb == Some(0) {
+            issues.push(ConfigIssue::error("memory_limit_mb", "Must be greater than 0"));
+        }
+        if self.thread_count_warn.is_some() && !self.metrics_enabled {
+            issues.push(ConfigIssue::warning(
+                "thread_count_warn",
+                "The thread count is only checked with metrics_enabled",
+            ));
+        }
+        if self.thread_count_warn == Some(0) {
+            issues.push(ConfigIssue::error("thread_count_warn", "Must be greater than 0"));
+        }
+        if self.metrics_history && !self.metrics_enabled {
+            issues.push(ConfigIssue::warning("metrics_history", "Nothing is recorded without metrics_enabled"));
+        }
+
+        if self.log_buffer_lines > MAX_LOG_BUFFER_LINES {
+            issues.push(ConfigIssue::warning(
+                "log_buffer_lines",
+                &format!("Values above {} are lowered to {}", MAX_LOG_BUFFER_LINES, MAX_LOG_BUFFER_LINES),
+            ));
+        }
+
+        issues
+    }
+
+    // The restart-required fields whose values differ between the two configs.
+    pub fn restart_required_changes(&self, other: &RunnerConfig) -> Vec<&'static str> {
+        let (ours, theirs) = match (serde_json::to_value(self), serde_json::to_value(other)) {
+            (Ok(ours), Ok(theirs)) => (ours, theirs),
+            _ => return Vec::new(),
+        };
+        RESTART_REQUIRED_FIELDS
+            .iter()
+            .copied()
+            .filter(|field| ours.get(field) != theirs.get(field))
+            .collect()
+    }
+
+    // Interpreter to run Python with
+    pub fn interpreter(&self) -> String {
+        self.python_path.clone().unwrap_or_else(|| "python".to_string())
+    }
+
+    // A copy for sharing, with secret-looking `NAME=value` assignments in the
+    // shell command fields replaced by REDACTED. `${VAR}` references are kept.
+    pub fn redacted(&self) -> Self {
+        let mut config = self.clone();
+        config.env_bootstrap = self.env_bootstrap.as_deref().map(redact_assignments);
+        config.crash_dump_command = self.crash_dump_command.as_deref().map(redact_assignments);
+        config
+    }
+
+    // Fields still holding a REDACTED placeholder, which must be filled in
+    // before the config is usable.
+    pub fn redacted_fields(&self) -> Vec<&'static str> {
+        let mut fields = Vec::new();
+        if self.env_bootstrap.as_deref().is_some_and(|bootstrap| bootstrap.contains(REDACTED)) {
+            fields.push("env_bootstrap");
+        }
+        if self.crash_dump_command.as_deref().is_some_and(|command| command.contains(REDACTED)) {
+            fields.push("crash_dump_command");
+        }
+        fields
+    }
+
+    // A copy with `~` and `${VAR}` expanded in the path and command fields, as
+    // used to launch the runner. The stored config keeps the unexpanded values.
+    pub fn expanded(&self) -> Result<Self, String> {
+        let field = |name: &str, value: &str| expand(value).map_err(|e| format!("{}: {}", name, e));
+        let mut config = self.clone();
+        config.python_path = self.python_path.as_deref().map(|path| field("python_path", path)).transpose()?;
+        config.launcher = self.launcher.as_deref().map(|launcher| field("launcher", launcher)).transpose()?;
+        config.working_dir = self.working_dir.as_deref().map(|dir| field("working_dir", dir)).transpose()?;
+        config.script = field("script", &self.script)?;
+        config.script_args = self
+            .script_args
+            .iter()
+            .enumerate()
+            .map(|(index, arg)| field(&format!("script_args[{}]", index), arg))
+            .collect::<Result<_, _>>()?;
+        config.heartbeat_file = self
+            .heartbeat_file
+            .as_ref()
+            .map(|path| field("heartbeat_file", &path.to_string_lossy()).map(PathBuf::from))
+            .transpose()?;
+        config.elevation_command = field("elevation_command", &self.elevation_command)?;
+        config.output_redirect = self
+            .output_redirect
+            .as_ref()
+            .map(|path| field("output_redirect", &path.to_string_lossy()).map(PathBuf::from))
+            .transpose()?;
+        Ok(config)
+    }
+
+    // Whether the runner's output is read: a terminal window keeps it to itself,
+    // and output_redirect hands it to a file.
+    pub fn captures_output(&self) -> bool {
+        self.capture_output && !self.terminal && self.output_redirect.is_none()
+    }
+
+    // The text the stdout reader watches for: the probe's marker, or
+    // `ready_marker` unless another probe replaces it.
+    pub fn ready_marker(&self) -> Option<String> {
+        match &self.readiness_probe {
+            Some(ReadinessProbe::StdoutMarker { marker }) => Some(marker.clone()),
+            Some(_) => None,
+            None => self.ready_marker.clone(),
+        }
+    }
+
+    pub fn metrics_interval(&self) -> Duration {
+        Duration::from_millis(self.metrics_interval_ms.max(MIN_METRICS_INTERVAL_MS))
+    }
+}
+
+// Whether a variable named `name` probably holds a secret.
+pub fn is_secret_name(name: &str) -> bool {
+    let name = name.to_uppercase();
+    SECRET_NAME_HINTS.iter().any(|hint| name.contains(hint))
+}
+
+fn redact_assignments(command: &str) -> String {
+    static ASSIGNMENT: OnceLock<Regex> = OnceLock::new();
+    let assignment = ASSIGNMENT
+        .get_or_init(|| Regex::new(r#"\b([A-Za-z_][A-Za-z0-9_]*)=("[^"]*"|'[^']*'|[^\s;&|]+)"#).unwrap());
+    assignment
+        .replace_all(command, |captures: &regex::Captures| {
+            let name = &captures[1];
+            let value = captures[2].trim_matches(|c| c == '"' || c == '\'');
+            if is_secret_name(name) && !value.starts_with('$') {
+                format!("{}={}", name, REDACTED)
+            } else {
+                captures[0].to_string()
+            }
+        })
+        .to_string()
+}
+
+// Expand a leading `~` to the home directory and `${VAR}` to the variable's value.
+// `$$` is a literal `$`; any other `$` is left alone.
+fn expand(value: &str) -> Result<String, String> {
+    let mut expanded = String::new();
+    let mut rest = value;
+    if value == "~" || value.starts_with("~/") || value.starts_with("~\\") {
+        let home = dirs::home_dir().ok_or("Could not find home directory to expand ~")?;
+        expanded.push_str(&home.to_string_lossy());
+        rest = &value[1..];
+    }
+
+    while let Some(start) = rest.find('$') {
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        if let Some(after) = after.strip_prefix('$') {
+            expanded.push('$');
+            rest = after;
+        } else if let Some(after) = after.strip_prefix('{') {
+            let end = after.find('}').ok_or_else(|| format!("Unclosed ${{ in \"{}\"", value))?;
+            let name = &after[..end];
+            let var = env::var(name)
+                .map_err(|_| format!("Environment variable {} used in \"{}\" is not set", name, value))?;
+            expanded.push_str(&var);
+            rest = &after[end + 1..];
+        } else {
+            expanded.push('$');
+            rest = after;
+        }
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+// An unparseable config.toml, as reported in the `config-error` event.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigError {
+    pub path: String,
+    pub message: String,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    // Where the invalid file was copied, if that succeeded
+    pub backup: Option<String>,
+}
+
+impl ConfigError {
+    fn new(path: &Path, contents: &str, error: &toml::de::Error) -> Self {
+        // Spans are byte offsets; report them 1-based like an editor would
+        let position = error.span().map(|span| {
+            let before = &contents[..span.start.min(contents.len())];
+            let line = before.matches('\n').count() + 1;
+            let column = before.rsplit('\n').next().map_or(0, |line| line.chars().count()) + 1;
+            (line, column)
+        });
+        let backup = match backup(path) {
+            Ok(backup) => Some(backup.display().to_string()),
+            Err(e) => {
+                warn!("Failed to back up invalid config file {}: {}", path.display(), e);
+                None
+            }
+        };
+        Self {
+            path: path.display().to_string(),
+            message: error.message().to_string(),
+            line: position.map(|(line, _)| line),
+            column: position.map(|(_, column)| column),
+            backup,
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid config file {}", self.path)?;
+        if let (Some(line), Some(column)) = (self.line, self.column) {
+            write!(f, " at line {}, column {}", line, column)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+// Copy `path` to `<path>.bak`, replacing any older backup.
+fn backup(path: &Path) -> std::io::Result<PathBuf> {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(".bak");
+    let backup = PathBuf::from(backup);
+    fs::copy(path, &backup)?;
+    info!("Backed up {} to {}", path.display(), backup.display());
+    Ok(backup)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigIssue {
+    pub field: String,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl ConfigIssue {
+    fn error(field: &str, message: &str) -> Self {
+        Self {
+            field: field.to_string(),
+            severity: Severity::Error,
+            message: message.to_string(),
+        }
+    }
+
+    fn warning(field: &str, message: &str) -> Self {
+        Self {
+            field: field.to_string(),
+            severity: Severity::Warning,
+            message: message.to_string(),
+        }
+    }
+}
+
+// Validate a candidate config from the settings UI without touching the running one.
+#[tauri::command]
+pub async fn validate_config(candidate: RunnerConfig) -> Result<Vec<ConfigIssue>, String> {
+    Ok(candidate.validate())
+}
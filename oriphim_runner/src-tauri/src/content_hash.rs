@@ -0,0 +1,8 @@
+// Included by both script.rs and build.rs, so the hashes embedded at build
+// time and the ones `verify_install` computes always agree. Not a module.
+
+// SHA-256 of `contents` as lowercase hex.
+pub fn content_hash(contents: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    Sha256::digest(contents).iter().map(|byte| format!("{:02x}", byte)).collect()
+}
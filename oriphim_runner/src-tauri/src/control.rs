@@ -0,0 +1,188 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use log::{debug, error, info, warn};
+
+use crate::RunnerState;
+
+// Windows named pipe; scoped to the user so two accounts don't collide
+#[cfg(windows)]
+fn pipe_name() -> String {
+    let user = std::env::var("USERNAME").unwrap_or_else(|_| "default".to_string());
+    format!(r"\\.\pipe\oriphim-runner-{}", user)
+}
+
+// One request per line, e.g. {"command": "status"}
+#[derive(Deserialize)]
+struct Request {
+    command: String,
+}
+
+// One response line per request
+#[derive(Serialize)]
+struct Response {
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+async fn handle(app: &AppHandle, line: &str) -> Response {
+    let request: Request = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(e) => {
+            return Response { ok: false, result: None, error: Some(format!("Invalid request: {}", e)) };
+        }
+    };
+    debug!("Control request: {}", request.command);
+    let result = match request.command.as_str() {
+        "start" => crate::start_python_runner(app.clone(), app.state::<RunnerState>()).await.map(Value::from),
+        "stop" => crate::stop_python_runner(app.clone(), app.state::<RunnerState>()).await.map(Value::from),
+        "restart" => crate::safe_restart(app.clone(), app.state::<RunnerState>()).await.map(Value::from),
+        "status" => serde_json::to_value(crate::runner_status(&app.state::<RunnerState>()))
+            .map_err(|e| format!("Failed to serialize status: {}", e)),
+        other => Err(format!("Unknown command '{}' (expected start, stop, restart or status)", other)),
+    };
+    match result {
+        Ok(result) => Response { ok: true, result: Some(result), error: None },
+        Err(error) => Response { ok: false, result: None, error: Some(error) },
+    }
+}
+
+// Answer requests on one connection until the client hangs up.
+async fn serve<S: AsyncRead + AsyncWrite + Unpin>(app: AppHandle, stream: S) {
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut lines = BufReader::new(reader).lines();
+    loop {
+        let line = match lines.next_line().await {
+            Ok(Some(line)) => line,
+            Ok(None) => break,
+            Err(e) => {
+                debug!("Control connection closed: {}", e);
+                break;
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = handle(&app, &line).await;
+        let mut reply = serde_json::to_string(&response).unwrap_or_else(|_| r#"{"ok":false}"#.to_string());
+        reply.push('\n');
+        if writer.write_all(reply.as_bytes()).await.is_err() {
+            break;
+        }
+    }
+}
+
+// Serve the control interface on ~/.oriphim/control.sock, readable and
+// writable only by the current user. A socket left by a crashed instance is
+// replaced; one another instance is still listening on is left alone.
+#[cfg(unix)]
+pub fn start(app: AppHandle) {
+    use tokio::net::UnixStream;
+
+    tauri::async_runtime::spawn(async move {
+        let path = match crate::paths::control_socket() {
+            Some(path) => path,
+            None => {
+                warn!("Could not find home directory, control socket not started");
+                return;
+            }
+        };
+        if path.exists() {
+            if UnixStream::connect(&path).await.is_ok() {
+                warn!("{} is in use by another instance, control socket not started", path.display());
+                return;
+            }
+            let _ = std::fs::remove_file(&path);
+        }
+        if let Some(dir) = path.parent() {
+            if let Err(e) = std::fs::create_dir_all(dir) {
+                error!("Failed to create {}: {}", dir.display(), e);
+                return;
+            }
+        }
+        let listener = match bind_private(&path) {
+            Ok(listener) => listener,
+            Err(e) => {
+                error!("Failed to listen on {}: {}", path.display(), e);
+                return;
+            }
+        };
+        info!("Control socket listening on {}", path.display());
+
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tauri::async_runtime::spawn(serve(app.clone(), stream));
+                }
+                Err(e) => warn!("Control socket accept failed: {}", e),
+            }
+        }
+    });
+}
+
+// Bind the socket inside a fresh 0700 directory, restrict it to 0600 and only
+// then move it into place, so no other user can connect while it still has
+// the umask's permissions.
+#[cfg(unix)]
+pub(crate) fn bind_private(path: &std::path::Path) -> std::io::Result<tokio::net::UnixListener> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+
+    let dir = path.with_extension(format!("sock.{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::DirBuilder::new().mode(0o700).create(&dir)?;
+    let staged = dir.join("control.sock");
+    let result = tokio::net::UnixListener::bind(&staged).and_then(|listener| {
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o600))?;
+        std::fs::rename(&staged, path)?;
+        Ok(listener)
+    });
+    let _ = std::fs::remove_dir_all(&dir);
+    result
+}
+
+// Serve the control interface on a named pipe for local clients only. Pipes
+// get a default security descriptor that only lets the creating user (and
+// administrators) write to them.
+#[cfg(windows)]
+pub fn start(app: AppHandle) {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    tauri::async_runtime::spawn(async move {
+        let name = pipe_name();
+        let options = || {
+            let mut options = ServerOptions::new();
+            options.reject_remote_clients(true);
+            options
+        };
+        // Fails if another instance already owns the pipe
+        let mut server = match options().first_pipe_instance(true).create(&name) {
+            Ok(server) => server,
+            Err(e) => {
+                error!("Failed to create control pipe {}: {}", name, e);
+                return;
+            }
+        };
+        info!("Control pipe listening on {}", name);
+
+        loop {
+            if let Err(e) = server.connect().await {
+                warn!("Control pipe connect failed: {}", e);
+                continue;
+            }
+            // A fresh instance for the next client before serving this one
+            let next = match options().create(&name) {
+                Ok(next) => next,
+                Err(e) => {
+                    error!("Failed to create control pipe {}: {}", name, e);
+                    return;
+                }
+            };
+            let connected = std::mem::replace(&mut server, next);
+            tauri::async_runtime::spawn(serve(app.clone(), connected));
+        }
+    });
+}
@@ -0,0 +1,99 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::env;
+use std::process::ExitStatus;
+use tauri::{AppHandle, Manager};
+use log::{info, warn};
+
+use crate::process::ManagedProcess;
+use crate::RunnerState;
+
+// Added to an armed run's environment unless it already sets them
+const DEBUG_ENV: &[(&str, &str)] = &[
+    // Dump every thread's stack on a fatal signal such as a segfault
+    ("PYTHONFAULTHANDLER", "1"),
+    // Trace imports on stderr, to show what was loading when it died
+    ("PYTHONVERBOSE", "1"),
+];
+pub const DEBUG_LOG_LEVEL: &str = "DEBUG";
+
+#[derive(Clone, Serialize)]
+struct CrashDebugFinishedEvent {
+    crashed: bool,
+    exit_code: Option<i32>,
+}
+
+// Add the debug variables to `vars` if the next start is armed, and say whether it is.
+pub fn apply_env(state: &RunnerState, vars: &mut HashMap<String, String>) -> bool {
+    if !*state.crash_debug_armed.lock().unwrap() {
+        return false;
+    }
+    for (name, value) in DEBUG_ENV {
+        if env::var_os(name).is_none() {
+            vars.entry(name.to_string()).or_insert_with(|| value.to_string());
+        }
+    }
+    true
+}
+
+// Mark `process` as the debug run, which uses up the arming.
+pub fn begin(state: &RunnerState, process: &mut ManagedProcess) {
+    *state.crash_debug_armed.lock().unwrap() = false;
+    process.crash_debug = true;
+    info!("Python runner started with crash debugging (PID {})", process.child.id());
+}
+
+// Turn the debug run's logging up. Call once the process locks are released;
+// the write runs as its own task.
+pub fn raise_log_level(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<RunnerState>();
+        let command = format!("set-level {}\n", DEBUG_LOG_LEVEL).into_bytes();
+        match crate::write_stdin(&state, command.clone()).await {
+            Ok(()) => {
+                crate::stdin_recording::record(&state, &command);
+                *state.log_level.lock().unwrap() = Some(DEBUG_LOG_LEVEL.to_string());
+            }
+            Err(e) => warn!("Failed to raise the log level for crash debugging: {}", e),
+        }
+    });
+}
+
+// The debug run has ended; later starts are back to normal.
+pub fn finished(app: &AppHandle, status: ExitStatus) {
+    let crashed = !status.success();
+    if crashed {
+        info!("Crash debugging captured a crash ({}); diagnostics are off for the next start", status);
+    } else {
+        info!("Crash debug run exited cleanly; diagnostics are off for the next start");
+    }
+    let _ = app.emit_all(
+        "crash-debug-finished",
+        CrashDebugFinishedEvent {
+            crashed,
+            exit_code: status.code(),
+        },
+    );
+}
+
+// Run the next start with extra diagnostics for an intermittent crash:
+// faulthandler, import tracing and DEBUG logging. Only that one run gets them,
+// whether it crashes or exits cleanly. `armed: false` disarms. Not kept across
+// app restarts.
+#[tauri::command]
+pub async fn arm_crash_debug(
+    armed: Option<bool>,
+    app: AppHandle,
+    state: tauri::State<'_, RunnerState>,
+) -> Result<String, String> {
+    let armed = armed.unwrap_or(true);
+    *state.crash_debug_armed.lock().unwrap() = armed;
+    let message = if armed {
+        "Crash debugging armed for the next start"
+    } else {
+        "Crash debugging disarmed"
+    };
+    info!("{}", message);
+    let _ = app.emit_all("runner-status", crate::runner_status(&state));
+    Ok(message.to_string())
+}
@@ -0,0 +1,130 @@
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use log::{info, warn};
+
+use crate::paths;
+use crate::traceback::CrashReport;
+use crate::RunnerState;
+
+// A dump command still running after this is killed
+const DUMP_TIMEOUT: Duration = Duration::from_secs(120);
+
+// The most recent crash, for `get_last_crash`.
+#[derive(Debug, Clone, Serialize)]
+pub struct LastCrash {
+    pub timestamp: DateTime<Local>,
+    pub pid: u32,
+    pub exit_code: Option<i32>,
+    pub status: String,
+    // Directory holding what `crash_dump_command` collected, once it has finished
+    pub dump_dir: Option<String>,
+    // Parsed from the Python traceback on stderr, if there was one
+    pub report: Option<CrashReport>,
+}
+
+#[derive(Clone, Serialize)]
+struct CrashDumpEvent {
+    pid: u32,
+    dump_dir: String,
+}
+
+// Run `command` to collect a dump for the crashed process `pid` into a fresh
+// directory under ~/.oriphim/crashes, passed to it as ORIPHIM_CRASH_DIR along
+// with ORIPHIM_CRASH_PID and ORIPHIM_CRASH_EXIT_CODE. Emits `runner-crash-dump`
+// and records the directory in the last crash once it's done; directories the
+// command leaves empty are removed. Only the newest `max_dumps` are kept.
+pub fn collect(app: &AppHandle, command: String, pid: u32, exit_code: Option<i32>, max_dumps: usize) {
+    let crashes_dir = match paths::crashes_dir() {
+        Some(dir) => dir,
+        None => return,
+    };
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let dir = crashes_dir.join(format!("{}-{}", Local::now().format("%Y%m%d-%H%M%S"), pid));
+        if let Err(e) = fs::create_dir_all(&dir) {
+            warn!("Failed to create {}: {}", dir.display(), e);
+            return;
+        }
+
+        info!("Collecting crash dump: {}", command);
+        if let Err(e) = run(&command, &dir, pid, exit_code).await {
+            warn!("{}", e);
+        }
+
+        let collected = fs::read_dir(&dir).is_ok_and(|mut entries| entries.next().is_some());
+        if !collected {
+            warn!("Crash dump command produced no files");
+            let _ = fs::remove_dir(&dir);
+        } else {
+            info!("Crash dump saved to {}", dir.display());
+            let dump_dir = dir.display().to_string();
+            let state = app.state::<RunnerState>();
+            if let Some(crash) = state.last_crash.lock().unwrap().as_mut().filter(|crash| crash.pid == pid) {
+                crash.dump_dir = Some(dump_dir.clone());
+            }
+            let _ = app.emit_all("runner-crash-dump", CrashDumpEvent { pid, dump_dir });
+        }
+        prune(&crashes_dir, max_dumps);
+    });
+}
+
+async fn run(command: &str, dir: &Path, pid: u32, exit_code: Option<i32>) -> Result<(), String> {
+    #[cfg(windows)]
+    let mut child = tokio::process::Command::new("cmd");
+    #[cfg(windows)]
+    child.arg("/C").arg(command);
+
+    #[cfg(not(windows))]
+    let mut child = tokio::process::Command::new("sh");
+    #[cfg(not(windows))]
+    child.arg("-c").arg(command);
+
+    child
+        .current_dir(dir)
+        .env("ORIPHIM_CRASH_DIR", dir)
+        .env("ORIPHIM_CRASH_PID", pid.to_string())
+        .env("ORIPHIM_CRASH_EXIT_CODE", exit_code.map(|code| code.to_string()).unwrap_or_default())
+        .kill_on_drop(true);
+
+    let output = tokio::time::timeout(DUMP_TIMEOUT, child.output())
+        .await
+        .map_err(|_| format!("Crash dump command timed out after {}s", DUMP_TIMEOUT.as_secs()))?
+        .map_err(|e| format!("Failed to run crash dump command: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Crash dump command exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+// Remove all but the newest `keep` dump directories. Their names start with a
+// timestamp, so name order is age order.
+fn prune(crashes_dir: &Path, keep: usize) {
+    let mut dirs: Vec<PathBuf> = match fs::read_dir(crashes_dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.is_dir())
+            .collect(),
+        Err(_) => return,
+    };
+    dirs.sort();
+    let excess = dirs.len().saturating_sub(keep);
+    for dir in &dirs[..excess] {
+        match fs::remove_dir_all(dir) {
+            Ok(()) => info!("Removed old crash dump {}", dir.display()),
+            Err(e) => warn!("Failed to remove old crash dump {}: {}", dir.display(), e),
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn get_last_crash(state: tauri::State<'_, RunnerState>) -> Result<Option<LastCrash>, String> {
+    Ok(state.last_crash.lock().unwrap().clone())
+}
@@ -0,0 +1,197 @@
+use regex::Regex;
+use serde::Serialize;
+use std::sync::OnceLock;
+
+use crate::RunnerState;
+
+// stderr lines searched for known failure messages
+const STDERR_LINES: usize = 100;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnosis {
+    // Short machine-readable cause, e.g. "missing_module"
+    pub cause: &'static str,
+    // What went wrong, in plain English
+    pub explanation: String,
+    // What to try next
+    pub suggestion: String,
+    // First line of the recorded error
+    pub error: String,
+    pub exit_code: Option<i32>,
+}
+
+// What a failure looked like: the recorded error, the crash, and stderr.
+struct Evidence<'a> {
+    error: &'a str,
+    exit_code: Option<i32>,
+    // Type and message of the exception that ended the process, if parsed
+    exception: Option<(&'a str, &'a str)>,
+    stderr: &'a str,
+}
+
+fn missing_module_regex() -> &'static Regex {
+    static MODULE: OnceLock<Regex> = OnceLock::new();
+    MODULE.get_or_init(|| Regex::new(r#"No module named '([^']+)'"#).unwrap())
+}
+
+fn diagnosis(cause: &'static str, explanation: String, suggestion: impl Into<String>) -> (&'static str, String, String) {
+    (cause, explanation, suggestion.into())
+}
+
+// Match the evidence against known causes, most specific first.
+fn explain(evidence: &Evidence) -> (&'static str, String, String) {
+    let error = evidence.error;
+    let stderr = evidence.stderr;
+    let either = |needle: &str| error.contains(needle) || stderr.contains(needle);
+
+    if error.contains("conda is not installed") {
+        return diagnosis(
+            "conda_missing",
+            "conda_env is set, but conda could not be found.".to_string(),
+            "Install Miniconda or Anaconda, or remove conda_env from config.toml to use python_path.",
+        );
+    }
+    if error.contains("No Python interpreter found") || (error.contains("python_path") && error.contains("does not exist")) {
+        return diagnosis(
+            "interpreter_missing",
+            "No Python interpreter could be found to run the script.".to_string(),
+            "Install Python 3, or set python_path in config.toml to the interpreter you use (discover_interpreters lists the ones found).",
+        );
+    }
+    if error.contains("Launcher") && error.contains("was not found") {
+        return diagnosis(
+            "launcher_missing",
+            "The configured launcher program could not be found.".to_string(),
+            "Install it or put it on PATH, or remove launcher from config.toml.",
+        );
+    }
+    if error.starts_with("Invalid config") {
+        return diagnosis(
+            "invalid_config",
+            "The settings in config.toml could not be used.".to_string(),
+            "Fix the setting named in the error (validate_config lists every problem) and start again.",
+        );
+    }
+    if error.contains("Elevation was cancelled") {
+        return diagnosis(
+            "elevation_cancelled",
+            "The elevation prompt was dismissed, so the runner never started.".to_string(),
+            "Start again and approve the prompt, or turn off elevated in config.toml.",
+        );
+    }
+    if error.contains("command line is too long") {
+        return diagnosis(
+            "command_line_too_long",
+            "The command used to start the script is longer than the system allows.".to_string(),
+            "Shorten script_args, or pass the values through a file or environment variable instead.",
+        );
+    }
+    if stderr.contains("can't open file") || (stderr.contains("No such file or directory") && stderr.contains("main.py")) {
+        return diagnosis(
+            "script_missing",
+            "Python started, but main.py was not found in the working directory.".to_string(),
+            "Check working_dir in config.toml (get_effective_working_dir shows the directory in use) and that main.py is there.",
+        );
+    }
+    if error.contains("Failed to start Python runner") && (error.contains("os error 2") || error.contains("not found")) {
+        return diagnosis(
+            "interpreter_missing",
+            "The interpreter program could not be launched because it doesn't exist.".to_string(),
+            "Check python_path in config.toml, or clear it to let the runner find Python itself.",
+        );
+    }
+    if either("Permission denied") || either("os error 13") || matches!(evidence.exception, Some(("PermissionError", _))) {
+        return diagnosis(
+            "permission_denied",
+            "The script or interpreter was not allowed to access a file it needed.".to_string(),
+            "Check the permissions of the working directory and interpreter, or that no other program has the file locked.",
+        );
+    }
+    if let Some(captures) = missing_module_regex().captures(stderr) {
+        let module = &captures[1];
+        let package = module.split('.').next().unwrap_or(module);
+        return diagnosis(
+            "missing_module",
+            format!("The script imports '{}', which isn't installed for this interpreter.", module),
+            format!(
+                "Install it into the interpreter the runner uses, e.g. `python -m pip install {}`, or point python_path at the environment that has it.",
+                package
+            ),
+        );
+    }
+    if either("Address already in use") || either("Errno 98") || either("Errno 48") || either("WinError 10048") {
+        return diagnosis(
+            "port_in_use",
+            "The script tried to listen on a network port that another program is already using.".to_string(),
+            "Stop the other program (possibly an older runner that didn't exit), or configure the script to use a different port.",
+        );
+    }
+    if let Some(("SyntaxError" | "IndentationError" | "TabError", message)) = evidence.exception {
+        return diagnosis(
+            "syntax_error",
+            format!("The script has a syntax error: {}.", message),
+            "Fix the line shown in the traceback, or restore the script (update_script reinstalls the bundled copy).",
+        );
+    }
+    if error.starts_with("Readiness timeout") {
+        return diagnosis(
+            "readiness_timeout",
+            format!("The script kept running but never signalled that it was ready ({}).", error),
+            "Check the log for what it was doing; if it is just slow, raise ready_timeout_secs, otherwise check that ready_marker or readiness_probe match what the script does.",
+        );
+    }
+    if let Some((exception_type, message)) = evidence.exception {
+        return diagnosis(
+            "python_exception",
+            format!("The script stopped with an unhandled {}: {}", exception_type, message),
+            "Look at the traceback in the log for the line that raised it. get_last_crash has the full report.",
+        );
+    }
+    if error.contains("exited before becoming ready") || error.contains("crashed") {
+        let code = evidence.exit_code.map(|code| format!(" with exit code {}", code)).unwrap_or_default();
+        return diagnosis(
+            "early_exit",
+            format!("The script exited{} without printing a Python traceback.", code),
+            "Check the last lines of the log for its own error message, or run it with the same interpreter from a terminal.",
+        );
+    }
+    diagnosis(
+        "unknown",
+        "The cause of the failure wasn't recognized.".to_string(),
+        "Use copy_last_error to copy the full report, including stderr, when asking for help.",
+    )
+}
+
+// Explain the most recent start failure or crash in plain English, with a suggested fix.
+#[tauri::command]
+pub async fn diagnose_last_failure(state: tauri::State<'_, RunnerState>) -> Result<Diagnosis, String> {
+    diagnose(&state)
+}
+
+pub fn diagnose(state: &RunnerState) -> Result<Diagnosis, String> {
+    let report = state.last_error.lock().unwrap().clone().ok_or("No failure has been recorded")?;
+    let last_crash = state.last_crash.lock().unwrap().clone();
+    let stderr = state.stderr_buffer.lock().unwrap().stderr_tail(STDERR_LINES).join("\n");
+
+    let error = report.lines().next().unwrap_or_default();
+    let exception = last_crash
+        .as_ref()
+        .and_then(|crash| crash.report.as_ref())
+        .map(|report| (report.exception.exception_type.as_str(), report.exception.message.as_str()));
+    let exit_code = last_crash.as_ref().and_then(|crash| crash.exit_code);
+    // The report carries the stderr captured when the failure was recorded
+    let stderr = format!("{}\n{}", report, stderr);
+    let (cause, explanation, suggestion) = explain(&Evidence {
+        error,
+        exit_code,
+        exception,
+        stderr: &stderr,
+    });
+    Ok(Diagnosis {
+        cause,
+        explanation,
+        suggestion,
+        error: error.to_string(),
+        exit_code,
+    })
+}
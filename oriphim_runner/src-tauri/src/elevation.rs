@@ -0,0 +1,57 @@
+use std::path::Path;
+use std::process::{Command, ExitStatus};
+
+// Exit code of the launcher when the user dismissed the elevation prompt:
+// ERROR_CANCELLED on Windows (from our PowerShell wrapper), and what pkexec
+// returns when its authentication dialog is dismissed.
+#[cfg(windows)]
+const CANCELLED_EXIT_CODE: i32 = 1223;
+#[cfg(not(windows))]
+const CANCELLED_EXIT_CODE: i32 = 126;
+
+// Build a command that runs `program args...` in `working_dir` with elevated
+// privileges. Elevation tools generally start the program in a clean environment
+// (pkexec always, sudo without -E), so variables set on the returned command may
+// not reach it.
+//
+// On Windows this is a PowerShell `Start-Process -Verb RunAs` that waits for the
+// elevated process and exits with its code. The elevated process lives in its own
+// console, so its output isn't captured and stopping the runner only stops the
+// wrapper. On Unix `elevation_command` (e.g. `pkexec` or `sudo -A`) is prefixed,
+// with a `cd` since pkexec doesn't keep the working directory.
+#[cfg(windows)]
+pub fn command(_elevation_command: &str, program: &str, args: &[&str], working_dir: &Path) -> Command {
+    // PowerShell single-quoted strings only need ' doubled
+    let quote = |value: &str| format!("'{}'", value.replace('\'', "''"));
+    let arg_list: Vec<String> = args.iter().map(|arg| quote(arg)).collect();
+    let script = format!(
+        "try {{ $p = Start-Process -FilePath {} -ArgumentList {} -WorkingDirectory {} -Verb RunAs -Wait -PassThru -ErrorAction Stop }} catch {{ exit {} }}; exit $p.ExitCode",
+        quote(program),
+        arg_list.join(","),
+        quote(&working_dir.to_string_lossy()),
+        CANCELLED_EXIT_CODE,
+    );
+    let mut command = Command::new("powershell");
+    command.args(["-NoProfile", "-NonInteractive", "-Command", &script]);
+    command.current_dir(working_dir);
+    command
+}
+
+#[cfg(not(windows))]
+pub fn command(elevation_command: &str, program: &str, args: &[&str], working_dir: &Path) -> Command {
+    let mut prefix = elevation_command.split_whitespace();
+    let mut command = Command::new(prefix.next().unwrap_or("pkexec"));
+    command
+        .args(prefix)
+        .args(["sh", "-c", "cd \"$0\" && exec \"$@\""])
+        .arg(working_dir)
+        .arg(program)
+        .args(args);
+    command.current_dir(working_dir);
+    command
+}
+
+// Whether an elevated launch ended because the user declined the prompt.
+pub fn was_cancelled(status: ExitStatus) -> bool {
+    status.code() == Some(CANCELLED_EXIT_CODE)
+}
@@ -0,0 +1,212 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use log::{debug, info, warn};
+
+use crate::config::RunnerConfig;
+use crate::conda;
+use crate::interpreters;
+use crate::paths;
+use crate::saved_state::SavedState;
+use crate::script;
+use crate::RunnerState;
+
+// pip can take a while on a cold cache or a large environment
+const FREEZE_TIMEOUT: Duration = Duration::from_secs(60);
+
+// What the runner's behavior depends on besides the config: the script, the
+// interpreter and the installed packages.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvironmentFingerprint {
+    pub computed_at: DateTime<Local>,
+    // SHA-256 of main.py; None if it couldn't be read
+    pub script_hash: Option<String>,
+    pub interpreter: String,
+    pub interpreter_version: Option<String>,
+    // `pip freeze` lines, sorted; None if pip couldn't be run
+    pub packages: Option<Vec<String>>,
+    // Hash of the three above, the value to compare
+    pub fingerprint: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EnvironmentChange {
+    pub previous: EnvironmentFingerprint,
+    pub current: EnvironmentFingerprint,
+    // e.g. ["script", "packages"]
+    pub changed: Vec<&'static str>,
+    pub packages_added: Vec<String>,
+    pub packages_removed: Vec<String>,
+}
+
+impl EnvironmentFingerprint {
+    // None when nothing that goes into the fingerprint differs.
+    fn compare(previous: &Self, current: &Self) -> Option<EnvironmentChange> {
+        if previous.fingerprint == current.fingerprint {
+            return None;
+        }
+        let mut changed = Vec::new();
+        if previous.script_hash != current.script_hash {
+            changed.push("script");
+        }
+        if previous.interpreter_version != current.interpreter_version {
+            changed.push("interpreter_version");
+        }
+        if previous.packages != current.packages {
+            changed.push("packages");
+        }
+        let set = |fingerprint: &Self| -> BTreeSet<String> { fingerprint.packages.iter().flatten().cloned().collect() };
+        let (before, after) = (set(previous), set(current));
+        Some(EnvironmentChange {
+            previous: previous.clone(),
+            current: current.clone(),
+            changed,
+            packages_added: after.difference(&before).cloned().collect(),
+            packages_removed: before.difference(&after).cloned().collect(),
+        })
+    }
+}
+
+// The interpreter as the runner launches it, with `args` for Python: through
+// `conda run` under conda_env, else the resolved interpreter.
+fn python_command(config: &RunnerConfig, working_dir: &Path, args: &[&str]) -> Result<(String, Vec<String>), String> {
+    match &config.conda_env {
+        Some(env) => {
+            let conda = conda::find().ok_or_else(|| format!("conda_env is set to '{}' but conda is not installed", env))?;
+            let mut run_args = conda::run_args(env, args[0]);
+            run_args.extend(args[1..].iter().map(|arg| arg.to_string()));
+            Ok((conda.to_string_lossy().to_string(), run_args))
+        }
+        None => {
+            let python = interpreters::resolve_interpreter(config, working_dir)?;
+            Ok((python.to_string_lossy().to_string(), args.iter().map(|arg| arg.to_string()).collect()))
+        }
+    }
+}
+
+// Run Python with `args` and return its stdout, or None if it failed or timed out.
+async fn python_output(config: &RunnerConfig, working_dir: &Path, args: &[&str]) -> Result<Option<String>, String> {
+    let (program, args) = python_command(config, working_dir, args)?;
+    let child = tokio::process::Command::new(&program)
+        .args(&args)
+        .current_dir(working_dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn();
+    let output = match child {
+        Ok(child) => tokio::time::timeout(FREEZE_TIMEOUT, child.wait_with_output()).await,
+        Err(e) => {
+            debug!("Failed to run {}: {}", program, e);
+            return Ok(None);
+        }
+    };
+    match output {
+        Ok(Ok(output)) if output.status.success() => {
+            // Python 2 prints its version to stderr
+            let text = if output.stdout.is_empty() { &output.stderr } else { &output.stdout };
+            Ok(Some(String::from_utf8_lossy(text).to_string()))
+        }
+        Ok(Ok(output)) => {
+            debug!("{} {} failed: {}", program, args.join(" "), String::from_utf8_lossy(&output.stderr).trim());
+            Ok(None)
+        }
+        Ok(Err(e)) => {
+            debug!("Failed to read output of {}: {}", program, e);
+            Ok(None)
+        }
+        Err(_) => {
+            warn!("{} {} timed out after {}s", program, args.join(" "), FREEZE_TIMEOUT.as_secs());
+            Ok(None)
+        }
+    }
+}
+
+pub async fn compute(config: &RunnerConfig, working_dir: &Path) -> Result<EnvironmentFingerprint, String> {
+    let script_hash = fs::read(working_dir.join(&config.script)).ok().map(|contents| script::content_hash(&contents));
+    let (interpreter, _) = python_command(config, working_dir, &["--version"])?;
+    let interpreter_version = python_output(config, working_dir, &["--version"])
+        .await?
+        .and_then(|text| text.trim().strip_prefix("Python ").map(str::to_string));
+    let packages = python_output(config, working_dir, &["-m", "pip", "freeze"]).await?.map(|text| {
+        let mut packages: Vec<String> = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_string)
+            .collect();
+        packages.sort_by_key(|package| package.to_lowercase());
+        packages
+    });
+
+    let summary = format!(
+        "{}\n{}\n{}",
+        script_hash.as_deref().unwrap_or("-"),
+        interpreter_version.as_deref().unwrap_or("-"),
+        packages.as_ref().map(|packages| packages.join("\n")).unwrap_or_else(|| "-".to_string())
+    );
+    Ok(EnvironmentFingerprint {
+        computed_at: Local::now(),
+        script_hash,
+        interpreter,
+        interpreter_version,
+        packages,
+        fingerprint: script::content_hash(summary.as_bytes()),
+    })
+}
+
+// Fingerprint the environment of a start in the background, compare it with the
+// previous start's and emit `environment-changed` if they differ.
+pub fn check_at_start(app: AppHandle, config: RunnerConfig, working_dir: PathBuf) {
+    tauri::async_runtime::spawn(async move {
+        let current = match compute(&config, &working_dir).await {
+            Ok(current) => current,
+            Err(e) => {
+                warn!("Could not fingerprint the runner environment: {}", e);
+                return;
+            }
+        };
+        let previous = SavedState::load().environment_fingerprint;
+        if let Some(change) = previous.as_ref().and_then(|previous| EnvironmentFingerprint::compare(previous, &current)) {
+            info!(
+                "Runner environment changed since the last start ({}): {} packages added, {} removed",
+                change.changed.join(", "),
+                change.packages_added.len(),
+                change.packages_removed.len()
+            );
+            let _ = app.emit_all("environment-changed", change);
+        }
+        if let Err(e) = SavedState::update(|state| state.environment_fingerprint = Some(current)) {
+            warn!("Failed to save environment fingerprint: {}", e);
+        }
+    });
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FingerprintReport {
+    pub current: EnvironmentFingerprint,
+    // Recorded at the last start
+    pub last_start: Option<EnvironmentFingerprint>,
+    // Differences from `last_start`, if any
+    pub change: Option<EnvironmentChange>,
+}
+
+// Fingerprint the environment now, without recording it.
+#[tauri::command]
+pub async fn get_environment_fingerprint(
+    app: AppHandle,
+    state: tauri::State<'_, RunnerState>,
+) -> Result<FingerprintReport, String> {
+    let config = state.config.lock().unwrap().expanded()?;
+    let working_dir = paths::working_dir(&app, &config);
+    let current = compute(&config, &working_dir).await?;
+    let last_start = SavedState::load().environment_fingerprint;
+    let change = last_start.as_ref().and_then(|previous| EnvironmentFingerprint::compare(previous, &current));
+    Ok(FingerprintReport { current, last_start, change })
+}
@@ -0,0 +1,270 @@
+use serde::Serialize;
+
+use crate::RunnerState;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OpenFile {
+    // File descriptor, where the platform exposes one
+    pub fd: Option<u32>,
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OpenSocket {
+    // tcp, tcp6, udp or udp6
+    pub protocol: String,
+    pub local_address: String,
+    pub remote_address: Option<String>,
+    // e.g. LISTEN or ESTABLISHED; None for UDP
+    pub state: Option<String>,
+    // Accepting connections (TCP) or bound (UDP)
+    pub listening: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessHandles {
+    pub pid: u32,
+    pub files: Vec<OpenFile>,
+    pub sockets: Vec<OpenSocket>,
+    // What couldn't be listed on this platform
+    pub note: Option<String>,
+}
+
+// Open files and sockets of the running Python process: from /proc on Linux,
+// `lsof` on macOS and `netstat` (sockets only) on Windows.
+#[tauri::command]
+pub async fn get_process_handles(state: tauri::State<'_, RunnerState>) -> Result<ProcessHandles, String> {
+    let pid = {
+        let process_guard = state.python_process.lock().unwrap();
+        let process = process_guard.as_ref().ok_or("Python runner is not running")?;
+        process.verify_owned_process()?;
+        process.child.id()
+    };
+    tokio::task::spawn_blocking(move || list(pid))
+        .await
+        .map_err(|e| format!("Failed to list process handles: {}", e))?
+}
+
+#[cfg(not(target_os = "linux"))]
+fn run(program: &str, args: &[&str]) -> Result<String, String> {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+#[cfg(target_os = "linux")]
+fn list(pid: u32) -> Result<ProcessHandles, String> {
+    use std::collections::HashSet;
+    use std::fs;
+
+    let fd_dir = format!("/proc/{}/fd", pid);
+    let entries = fs::read_dir(&fd_dir).map_err(|e| format!("Failed to read {}: {}", fd_dir, e))?;
+    let mut files = Vec::new();
+    let mut socket_inodes = HashSet::new();
+    for entry in entries.flatten() {
+        let target = match fs::read_link(entry.path()) {
+            Ok(target) => target.to_string_lossy().to_string(),
+            // Closed since the directory was read
+            Err(_) => continue,
+        };
+        if let Some(inode) = target.strip_prefix("socket:[").and_then(|rest| rest.strip_suffix(']')) {
+            socket_inodes.insert(inode.to_string());
+            continue;
+        }
+        files.push(OpenFile {
+            fd: entry.file_name().to_string_lossy().parse().ok(),
+            path: target,
+        });
+    }
+    files.sort_by_key(|file| file.fd);
+
+    let mut sockets = Vec::new();
+    for protocol in ["tcp", "tcp6", "udp", "udp6"] {
+        // The table covers the whole network namespace; keep this process's entries
+        let table = match fs::read_to_string(format!("/proc/{}/net/{}", pid, protocol)) {
+            Ok(table) => table,
+            Err(_) => continue,
+        };
+        for row in table.lines().skip(1) {
+            let fields: Vec<&str> = row.split_whitespace().collect();
+            if fields.len() < 10 || !socket_inodes.contains(fields[9]) {
+                continue;
+            }
+            let tcp = protocol.starts_with("tcp");
+            let state = tcp.then(|| tcp_state(fields[3]).to_string());
+            let remote_address = decode_address(fields[2]).filter(|_| tcp && fields[3] != "0A");
+            sockets.push(OpenSocket {
+                protocol: protocol.to_string(),
+                local_address: decode_address(fields[1]).unwrap_or_else(|| fields[1].to_string()),
+                remote_address,
+                listening: !tcp || fields[3] == "0A",
+                state,
+            });
+        }
+    }
+
+    Ok(ProcessHandles {
+        pid,
+        files,
+        sockets,
+        note: None,
+    })
+}
+
+// `0100007F:1F90` -> `127.0.0.1:8080`. Addresses are stored as 32-bit words in
+// host (little-endian) order.
+#[cfg(target_os = "linux")]
+fn decode_address(hex: &str) -> Option<String> {
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    let (address, port) = hex.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+    let mut bytes = Vec::new();
+    for word in (0..address.len()).step_by(8) {
+        let word = u32::from_str_radix(address.get(word..word + 8)?, 16).ok()?;
+        bytes.extend(word.to_le_bytes());
+    }
+    match bytes.len() {
+        4 => Some(format!("{}:{}", Ipv4Addr::from(<[u8; 4]>::try_from(bytes).ok()?), port)),
+        16 => Some(format!("[{}]:{}", Ipv6Addr::from(<[u8; 16]>::try_from(bytes).ok()?), port)),
+        _ => None,
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn tcp_state(code: &str) -> &'static str {
+    match code {
+        "01" => "ESTABLISHED",
+        "02" => "SYN_SENT",
+        "03" => "SYN_RECV",
+        "04" => "FIN_WAIT1",
+        "05" => "FIN_WAIT2",
+        "06" => "TIME_WAIT",
+        "07" => "CLOSE",
+        "08" => "CLOSE_WAIT",
+        "09" => "LAST_ACK",
+        "0A" => "LISTEN",
+        "0B" => "CLOSING",
+        _ => "UNKNOWN",
+    }
+}
+
+// `lsof -F` prints one field per line: `f` starts a descriptor, followed by its
+// type (`t`), name (`n`), protocol (`P`) and TCP state (`TST=`).
+#[cfg(target_os = "macos")]
+fn list(pid: u32) -> Result<ProcessHandles, String> {
+    #[derive(Default)]
+    struct Entry {
+        fd: String,
+        kind: String,
+        name: String,
+        protocol: String,
+        state: Option<String>,
+    }
+
+    let output = run("lsof", &["-nP", "-a", "-p", &pid.to_string(), "-F", "ftnPT"])?;
+    let mut entries: Vec<Entry> = Vec::new();
+    for line in output.lines() {
+        let (field, value) = match line.chars().next() {
+            Some(field) => (field, &line[1..]),
+            None => continue,
+        };
+        if field == 'f' {
+            entries.push(Entry {
+                fd: value.to_string(),
+                ..Default::default()
+            });
+            continue;
+        }
+        let entry = match entries.last_mut() {
+            Some(entry) => entry,
+            None => continue,
+        };
+        match field {
+            't' => entry.kind = value.to_string(),
+            'n' => entry.name = value.to_string(),
+            'P' => entry.protocol = value.to_lowercase(),
+            'T' => {
+                if let Some(state) = value.strip_prefix("ST=") {
+                    entry.state = Some(state.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut files = Vec::new();
+    let mut sockets = Vec::new();
+    for entry in entries {
+        if entry.kind == "IPv4" || entry.kind == "IPv6" {
+            let protocol = match entry.kind.as_str() {
+                "IPv6" => format!("{}6", entry.protocol),
+                _ => entry.protocol.clone(),
+            };
+            let (local_address, remote_address) = match entry.name.split_once("->") {
+                Some((local, remote)) => (local.to_string(), Some(remote.to_string())),
+                None => (entry.name.clone(), None),
+            };
+            sockets.push(OpenSocket {
+                listening: entry.protocol == "udp" || entry.state.as_deref() == Some("LISTEN"),
+                protocol,
+                local_address,
+                remote_address,
+                state: entry.state,
+            });
+        } else {
+            files.push(OpenFile {
+                // cwd, txt, mem, ... aren't descriptors
+                fd: entry.fd.trim_end_matches(|c: char| c.is_ascii_alphabetic()).parse().ok(),
+                path: entry.name,
+            });
+        }
+    }
+
+    Ok(ProcessHandles {
+        pid,
+        files,
+        sockets,
+        note: None,
+    })
+}
+
+#[cfg(windows)]
+fn list(pid: u32) -> Result<ProcessHandles, String> {
+    // Proto, local, remote, state, PID for TCP; UDP has no remote state column
+    let output = run("netstat", &["-ano"])?;
+    let pid_text = pid.to_string();
+    let mut sockets = Vec::new();
+    for line in output.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let (protocol, local, remote, state) = match fields.as_slice() {
+            ["TCP", local, remote, state, owner] if *owner == pid_text => ("tcp", local, Some(remote), Some(state)),
+            ["UDP", local, _, owner] if *owner == pid_text => ("udp", local, None, None),
+            _ => continue,
+        };
+        let protocol = if local.starts_with('[') { format!("{}6", protocol) } else { protocol.to_string() };
+        let listening = state.map_or(true, |state| *state == "LISTENING");
+        sockets.push(OpenSocket {
+            protocol,
+            local_address: local.to_string(),
+            remote_address: remote.filter(|_| !listening).map(|remote| remote.to_string()),
+            state: state.map(|state| state.to_string()),
+            listening,
+        });
+    }
+
+    Ok(ProcessHandles {
+        pid,
+        files: Vec::new(),
+        sockets,
+        note: Some("Open files are not listed on Windows".to_string()),
+    })
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn list(pid: u32) -> Result<ProcessHandles, String> {
+    let _ = run;
+    Err(format!("Listing handles of process {} is not supported on this platform", pid))
+}
@@ -0,0 +1,130 @@
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{AppHandle, Manager};
+use log::{debug, error, info, warn};
+
+use crate::circuit;
+use crate::config::RunnerConfig;
+use crate::lifecycle::{self, LifecycleKind};
+use crate::RunnerState;
+
+// Passed to the Python process so it knows which file to touch
+pub const HEARTBEAT_ENV_VAR: &str = "ORIPHIM_HEARTBEAT_FILE";
+const MAX_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Serialize)]
+struct StalledEvent {
+    heartbeat_file: String,
+    stale_secs: u64,
+}
+
+// The heartbeat settings for one run, copied from the config at start.
+#[derive(Clone)]
+pub struct HeartbeatCheck {
+    pub path: PathBuf,
+    timeout: Duration,
+    restart: bool,
+}
+
+impl HeartbeatCheck {
+    // None unless `heartbeat_file` is set. Relative paths are taken from the
+    // runner's working directory.
+    pub fn from_config(config: &RunnerConfig, working_dir: &Path) -> Option<Self> {
+        let path = config.heartbeat_file.as_ref()?;
+        Some(Self {
+            path: working_dir.join(path),
+            timeout: Duration::from_secs(config.heartbeat_timeout_secs),
+            restart: config.restart_on_stall,
+        })
+    }
+}
+
+// Remove the heartbeat file so a leftover from an earlier run doesn't look fresh.
+pub fn remove(path: &Path) {
+    match fs::remove_file(path) {
+        Ok(()) => debug!("Removed heartbeat file {}", path.display()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => warn!("Failed to remove heartbeat file {}: {}", path.display(), e),
+    }
+}
+
+// How long since the file was last touched. Until the first touch, the time
+// since the process started.
+fn staleness(path: &Path, started: Instant) -> Duration {
+    match fs::metadata(path).and_then(|metadata| metadata.modified()) {
+        Ok(modified) => SystemTime::now().duration_since(modified).unwrap_or_default(),
+        Err(_) => started.elapsed(),
+    }
+}
+
+// Watch the heartbeat file of the process started as `run_id`: emit
+// `runner-stalled` once it goes `heartbeat_timeout_secs` without a touch, and
+// safe-restart the runner if `restart_on_stall` is set. Stops, removing the
+// file, once the process exits or is replaced.
+pub fn watch(app: AppHandle, run_id: u64, check: HeartbeatCheck) {
+    tauri::async_runtime::spawn(async move {
+        let started = Instant::now();
+        let poll_interval = (check.timeout / 4).clamp(Duration::from_millis(250), MAX_POLL_INTERVAL);
+        let mut stalled = false;
+
+        loop {
+            tokio::time::sleep(poll_interval).await;
+            let state = app.state::<RunnerState>();
+            {
+                let process_guard = state.python_process.lock().unwrap();
+                match process_guard.as_ref() {
+                    Some(process) if process.run_id == run_id => {}
+                    current => {
+                        // Leave the file alone if a new run is already using it
+                        let reused = current.is_some_and(|process| process.heartbeat_file.as_ref() == Some(&check.path));
+                        if !reused {
+                            remove(&check.path);
+                        }
+                        break;
+                    }
+                }
+            }
+
+            let stale = staleness(&check.path, started);
+            if stale < check.timeout {
+                if stalled {
+                    info!("Python runner heartbeat resumed");
+                    stalled = false;
+                }
+                continue;
+            }
+            if stalled {
+                continue;
+            }
+            stalled = true;
+
+            warn!("No heartbeat from the Python runner for {}s ({})", stale.as_secs(), check.path.display());
+            let _ = app.emit_all(
+                "runner-stalled",
+                StalledEvent {
+                    heartbeat_file: check.path.display().to_string(),
+                    stale_secs: stale.as_secs(),
+                },
+            );
+            if check.restart && circuit::allow_restart(&app, &app.state::<RunnerState>(), "heartbeat stall") {
+                lifecycle::record(
+                    LifecycleKind::Restart,
+                    None,
+                    None,
+                    format!("No heartbeat for {}s", stale.as_secs()),
+                );
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = crate::safe_restart(app.clone(), app.state::<RunnerState>()).await {
+                        error!("Stall restart failed: {}", e);
+                    }
+                });
+                break;
+            }
+        }
+
+        debug!("Heartbeat watch for run {} stopped", run_id);
+    });
+}
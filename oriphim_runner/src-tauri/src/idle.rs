@@ -0,0 +1,194 @@
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use log::{debug, error, info};
+
+use crate::config::RunnerConfig;
+use crate::RunnerState;
+
+const MAX_POLL_INTERVAL: Duration = Duration::from_secs(5);
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(1);
+
+// What counts as idle for `idle_shutdown_secs`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdleSignal {
+    // Nothing printed to stdout
+    Output,
+    // `idle_health_path` on `health_port` answering `idle`
+    Health,
+    // Both at once
+    Both,
+}
+
+#[derive(Clone, Serialize)]
+struct IdleStoppedEvent {
+    idle_secs: u64,
+    signal: IdleSignal,
+}
+
+// The idle shutdown settings for one run, copied from the config at start.
+#[derive(Clone)]
+pub struct IdleCheck {
+    timeout: Duration,
+    signal: IdleSignal,
+    // Port and path of the idle endpoint, for the health signals
+    endpoint: Option<(u16, String)>,
+    kill_tree: bool,
+}
+
+impl IdleCheck {
+    // None unless `idle_shutdown_secs` is set.
+    pub fn from_config(config: &RunnerConfig) -> Option<Self> {
+        let secs = config.idle_shutdown_secs?;
+        Some(Self {
+            timeout: Duration::from_secs(secs),
+            signal: config.idle_signal,
+            endpoint: config.health_port.map(|port| (port, config.idle_health_path.clone())),
+            kill_tree: config.kill_process_tree,
+        })
+    }
+}
+
+// Ask the idle endpoint whether the runner has work. Anything but a 200 whose
+// body is `idle` (including no answer) counts as busy.
+fn reports_idle(port: u16, path: &str) -> bool {
+    let query = || -> std::io::Result<String> {
+        let mut stream = TcpStream::connect_timeout(&SocketAddr::from(([127, 0, 0, 1], port)), HEALTH_TIMEOUT)?;
+        stream.set_read_timeout(Some(HEALTH_TIMEOUT))?;
+        stream.set_write_timeout(Some(HEALTH_TIMEOUT))?;
+        write!(stream, "GET {} HTTP/1.0\r\nHost: 127.0.0.1:{}\r\n\r\n", path, port)?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response)?;
+        Ok(response)
+    };
+    let response = match query() {
+        Ok(response) => response,
+        Err(e) => {
+            debug!("Idle endpoint on port {} did not answer: {}", port, e);
+            return false;
+        }
+    };
+    let (head, body) = response.split_once("\r\n\r\n").unwrap_or((&response, ""));
+    let ok = head.lines().next().is_some_and(|status| status.split_whitespace().nth(1) == Some("200"));
+    ok && body.trim().eq_ignore_ascii_case("idle")
+}
+
+// How long stdout has been quiet, counting from `since` when nothing has been
+// printed after it.
+fn output_silent_for(state: &RunnerState, since: Instant) -> Duration {
+    let run_time = since.elapsed();
+    state.stats.stdout_silent_for().map_or(run_time, |silent| silent.min(run_time))
+}
+
+async fn health_reports_idle(endpoint: Option<(u16, String)>) -> bool {
+    match endpoint {
+        Some((port, path)) => tokio::task::spawn_blocking(move || reports_idle(port, &path))
+            .await
+            .unwrap_or(false),
+        None => false,
+    }
+}
+
+// Stop the process started as `run_id` once it has been idle, by `signal`, for
+// `idle_shutdown_secs`, emitting `runner-idle-stopped`. Ends once the process
+// exits or is replaced.
+pub fn watch(app: AppHandle, run_id: u64, check: IdleCheck) {
+    tauri::async_runtime::spawn(async move {
+        let poll_interval = (check.timeout / 4).clamp(Duration::from_millis(250), MAX_POLL_INTERVAL);
+        let watch_started = Instant::now();
+        // When the health signal last showed activity
+        let mut health_active = Instant::now();
+
+        loop {
+            tokio::time::sleep(poll_interval).await;
+            let state = app.state::<RunnerState>();
+            match state.python_process.lock().unwrap().as_ref() {
+                Some(process) if process.run_id == run_id => {}
+                _ => break,
+            }
+
+            if check.signal != IdleSignal::Output && !health_reports_idle(check.endpoint.clone()).await {
+                health_active = Instant::now();
+            }
+
+            let output_idle = output_silent_for(&state, watch_started);
+            let idle_for = match check.signal {
+                IdleSignal::Output => output_idle,
+                IdleSignal::Health => health_active.elapsed(),
+                IdleSignal::Both => output_idle.min(health_active.elapsed()),
+            };
+            if idle_for < check.timeout {
+                continue;
+            }
+
+            info!("Python runner idle for {}s, stopping it", idle_for.as_secs());
+            if let Err(e) = crate::stop_runner(&app, &state, check.kill_tree).await {
+                error!("Idle shutdown failed: {}", e);
+                break;
+            }
+            let _ = app.emit_all(
+                "runner-idle-stopped",
+                IdleStoppedEvent {
+                    idle_secs: idle_for.as_secs(),
+                    signal: check.signal,
+                },
+            );
+            break;
+        }
+
+        debug!("Idle watch for run {} stopped", run_id);
+    });
+}
+
+// Stop the runner only if it is idle right now: stdout silent for at least
+// `quiet_secs` and/or the idle endpoint answering `idle`, per `signal`
+// (idle_signal by default). Otherwise fails with a `NotIdle` error and leaves
+// it running.
+#[tauri::command]
+pub async fn stop_if_idle(
+    quiet_secs: u64,
+    signal: Option<IdleSignal>,
+    app: AppHandle,
+    state: tauri::State<'_, RunnerState>,
+) -> Result<String, String> {
+    let (signal, endpoint, kill_tree, captures_output) = {
+        let config = state.config.lock().unwrap();
+        (
+            signal.unwrap_or(config.idle_signal),
+            config.health_port.map(|port| (port, config.idle_health_path.clone())),
+            config.kill_process_tree,
+            config.captures_output(),
+        )
+    };
+    if signal != IdleSignal::Health && !captures_output {
+        return Err("The output signal needs capture_output".to_string());
+    }
+    if signal != IdleSignal::Output && endpoint.is_none() {
+        return Err("The health signal needs health_port".to_string());
+    }
+    let started_at = match state.python_process.lock().unwrap().as_ref() {
+        Some(process) => process.started_at,
+        None => return Err("Python runner is not running".to_string()),
+    };
+
+    let mut busy = Vec::new();
+    if signal != IdleSignal::Health {
+        let silent = output_silent_for(&state, started_at);
+        if silent < Duration::from_secs(quiet_secs) {
+            busy.push(format!("stdout was active {}s ago", silent.as_secs()));
+        }
+    }
+    if signal != IdleSignal::Output && !health_reports_idle(endpoint).await {
+        busy.push("the idle endpoint did not report idle".to_string());
+    }
+    if !busy.is_empty() {
+        info!("Not stopping the Python runner: {}", busy.join(", "));
+        return Err(format!("NotIdle: {}", busy.join(", ")));
+    }
+
+    info!("Python runner is idle, stopping it");
+    crate::stop_runner(&app, &state, kill_tree).await
+}
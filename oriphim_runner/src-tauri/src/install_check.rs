@@ -0,0 +1,135 @@
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+use log::{info, warn};
+
+use crate::paths;
+use crate::script::content_hash;
+use crate::RunnerState;
+
+// `RESOURCE_CHECKSUMS`: (file name, content_hash) of each bundled .py file, from build.rs
+include!(concat!(env!("OUT_DIR"), "/resource_checksums.rs"));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileStatus {
+    Ok,
+    Modified,
+    Missing,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileCheck {
+    pub name: String,
+    pub expected_hash: String,
+    pub actual_hash: Option<String>,
+    pub status: FileStatus,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InstallReport {
+    pub bundled_dir: String,
+    // The shipped files against the checksums from the build
+    pub bundled: Vec<FileCheck>,
+    // The copies the runner starts, when it doesn't run the bundled ones
+    pub working_dir: Option<String>,
+    pub working_copy: Vec<FileCheck>,
+    // Intact in the bundle but changed or missing in the working directory,
+    // i.e. what `restore_from_bundle` can put back
+    pub restorable: Vec<String>,
+    // Every bundled file matches
+    pub ok: bool,
+}
+
+fn check_dir(dir: &Path) -> Vec<FileCheck> {
+    RESOURCE_CHECKSUMS
+        .iter()
+        .map(|(name, expected)| {
+            let actual_hash = fs::read(dir.join(name)).ok().map(|contents| content_hash(&contents));
+            let status = match &actual_hash {
+                None => FileStatus::Missing,
+                Some(actual) if actual == expected => FileStatus::Ok,
+                Some(_) => FileStatus::Modified,
+            };
+            FileCheck { name: name.to_string(), expected_hash: expected.to_string(), actual_hash, status }
+        })
+        .collect()
+}
+
+fn dirs(app: &AppHandle) -> Result<(PathBuf, Option<PathBuf>), String> {
+    let bundled = paths::bundled_src_dir(app).ok_or("No bundled Python sources found to verify")?;
+    let config = app.state::<RunnerState>().config.lock().unwrap().expanded()?;
+    let working_dir = paths::working_dir(app, &config);
+    let separate = fs::canonicalize(&working_dir).ok() != fs::canonicalize(&bundled).ok();
+    Ok((bundled, separate.then_some(working_dir)))
+}
+
+fn verify(app: &AppHandle) -> Result<InstallReport, String> {
+    let (bundled_dir, working_dir) = dirs(app)?;
+    let bundled = check_dir(&bundled_dir);
+    let working_copy = working_dir.as_deref().map(check_dir).unwrap_or_default();
+    let restorable = working_copy
+        .iter()
+        .zip(&bundled)
+        .filter(|(copy, shipped)| copy.status != FileStatus::Ok && shipped.status == FileStatus::Ok)
+        .map(|(copy, _)| copy.name.clone())
+        .collect();
+    Ok(InstallReport {
+        bundled_dir: bundled_dir.display().to_string(),
+        ok: bundled.iter().all(|check| check.status == FileStatus::Ok),
+        bundled,
+        working_dir: working_dir.map(|dir| dir.display().to_string()),
+        working_copy,
+        restorable,
+    })
+}
+
+// Check the bundled Python sources against the checksums embedded at build
+// time, to catch a corrupted install or a partial update, and compare the
+// working directory's copies if the runner uses its own.
+#[tauri::command]
+pub async fn verify_install(app: AppHandle) -> Result<InstallReport, String> {
+    let report = verify(&app)?;
+    for check in report.bundled.iter().filter(|check| check.status != FileStatus::Ok) {
+        warn!("Bundled {} is {:?}: expected {}", check.name, check.status, check.expected_hash);
+    }
+    if report.ok {
+        info!("Verified {} bundled files in {}", report.bundled.len(), report.bundled_dir);
+    }
+    if !report.restorable.is_empty() {
+        info!("Working copies differ from the bundle: {}", report.restorable.join(", "));
+    }
+    Ok(report)
+}
+
+// Copy intact bundled files over the working directory's drifted copies, each
+// kept as `<name>.bak`. `names` must be listed as restorable by verify_install;
+// `confirm` must be true. Takes effect from the next start.
+#[tauri::command]
+pub async fn restore_from_bundle(names: Vec<String>, confirm: bool, app: AppHandle) -> Result<Vec<String>, String> {
+    if !confirm {
+        return Err("Restoring replaces files in the working directory; pass confirm to proceed".to_string());
+    }
+    let report = verify(&app)?;
+    let bundled_dir = PathBuf::from(&report.bundled_dir);
+    let working_dir = report
+        .working_dir
+        .as_ref()
+        .map(PathBuf::from)
+        .ok_or("The runner uses the bundled files directly; nothing to restore")?;
+    if let Some(name) = names.iter().find(|name| !report.restorable.contains(name)) {
+        return Err(format!("{} can't be restored: it isn't bundled, is unchanged, or the bundled copy is damaged", name));
+    }
+
+    for name in &names {
+        let target = working_dir.join(name);
+        if target.exists() {
+            let backup = working_dir.join(format!("{}.bak", name));
+            fs::copy(&target, &backup).map_err(|e| format!("Failed to back up {}: {}", target.display(), e))?;
+        }
+        fs::copy(bundled_dir.join(name), &target).map_err(|e| format!("Failed to restore {}: {}", target.display(), e))?;
+        info!("Restored {} from the bundle", target.display());
+    }
+    Ok(names)
+}
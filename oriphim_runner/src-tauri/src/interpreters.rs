@@ -0,0 +1,434 @@
+use serde::Serialize;
+use std::collections::HashSet;
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use log::debug;
+
+use crate::config::RunnerConfig;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PythonInterpreter {
+    pub path: String,
+    pub version: String,
+    // Where it was found: "path", "system", "pyenv" or "conda"
+    pub source: &'static str,
+}
+
+#[cfg(windows)]
+const EXECUTABLE_NAMES: &[&str] = &["python.exe", "python3.exe"];
+#[cfg(not(windows))]
+const EXECUTABLE_NAMES: &[&str] = &["python3", "python"];
+
+// Conda distributions install into one of these under the home directory
+pub const CONDA_DIRS: &[&str] = &["miniconda3", "anaconda3", "miniforge3", "mambaforge"];
+
+// Find every Python interpreter on PATH, in common install locations and in
+// pyenv/conda installs. Each is run once with `--version`; anything that
+// doesn't answer like a Python interpreter is dropped.
+pub fn discover() -> Vec<PythonInterpreter> {
+    let mut candidates: Vec<(PathBuf, &'static str)> = Vec::new();
+
+    if let Some(path_var) = env::var_os("PATH") {
+        for dir in env::split_paths(&path_var) {
+            add_executables(&mut candidates, &dir, "path");
+        }
+    }
+    for dir in system_dirs() {
+        add_executables(&mut candidates, &dir, "system");
+    }
+    for dir in pyenv_dirs() {
+        add_executables(&mut candidates, &dir, "pyenv");
+    }
+    for dir in conda_dirs() {
+        add_executables(&mut candidates, &dir, "conda");
+    }
+
+    // The same interpreter is often reachable through several symlinks
+    let mut seen = HashSet::new();
+    let mut interpreters = Vec::new();
+    for (path, source) in candidates {
+        let resolved = match fs::canonicalize(&path) {
+            Ok(resolved) => resolved,
+            Err(_) => continue,
+        };
+        if !seen.insert(resolved) {
+            continue;
+        }
+        match python_version(&path) {
+            Some(version) => interpreters.push(PythonInterpreter {
+                path: path.to_string_lossy().to_string(),
+                version,
+                source,
+            }),
+            None => debug!("Skipping {}: not a working Python interpreter", path.display()),
+        }
+    }
+    interpreters
+}
+
+// Run `python --version` and return e.g. "3.11.4". Python 2 prints to stderr.
+pub fn python_version(path: &Path) -> Option<String> {
+    let output = Command::new(path).arg("--version").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = if output.stdout.is_empty() { &output.stderr } else { &output.stdout };
+    String::from_utf8_lossy(text)
+        .trim()
+        .strip_prefix("Python ")
+        .map(str::to_string)
+}
+
+fn add_executables(candidates: &mut Vec<(PathBuf, &'static str)>, dir: &Path, source: &'static str) {
+    for name in EXECUTABLE_NAMES {
+        let path = dir.join(name);
+        if path.is_file() {
+            candidates.push((path, source));
+        }
+    }
+}
+
+// Subdirectories of `dir`, or nothing if it doesn't exist
+fn subdirs(dir: &Path) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.is_dir())
+                .collect()
+        })
+        .unwrap_or_default();
+    dirs.sort();
+    dirs
+}
+
+#[cfg(not(windows))]
+fn system_dirs() -> Vec<PathBuf> {
+    ["/usr/bin", "/usr/local/bin", "/opt/homebrew/bin", "/opt/local/bin"]
+        .iter()
+        .map(PathBuf::from)
+        .collect()
+}
+
+#[cfg(windows)]
+fn system_dirs() -> Vec<PathBuf> {
+    // The python.org installer uses %LOCALAPPDATA%\Programs\Python\Python3X
+    // per user, or C:\Program Files\Python3X for all users
+    let mut roots = Vec::new();
+    if let Some(local) = dirs::data_local_dir() {
+        roots.push(local.join("Programs").join("Python"));
+    }
+    if let Some(program_files) = env::var_os("ProgramFiles") {
+        roots.push(PathBuf::from(program_files));
+    }
+    roots
+        .iter()
+        .flat_map(|root| subdirs(root))
+        .filter(|dir| {
+            dir.file_name()
+                .map(|name| name.to_string_lossy().starts_with("Python"))
+                .unwrap_or(false)
+        })
+        .collect()
+}
+
+fn pyenv_dirs() -> Vec<PathBuf> {
+    let root = match env::var_os("PYENV_ROOT") {
+        Some(root) => PathBuf::from(root),
+        None => match dirs::home_dir() {
+            Some(home) => home.join(".pyenv"),
+            None => return Vec::new(),
+        },
+    };
+    subdirs(&root.join("versions"))
+        .into_iter()
+        .map(|version| bin_dir(&version))
+        .collect()
+}
+
+fn conda_dirs() -> Vec<PathBuf> {
+    let home = match dirs::home_dir() {
+        Some(home) => home,
+        None => return Vec::new(),
+    };
+    let mut dirs = Vec::new();
+    for name in CONDA_DIRS {
+        let base = home.join(name);
+        if !base.is_dir() {
+            continue;
+        }
+        dirs.push(bin_dir(&base));
+        dirs.extend(subdirs(&base.join("envs")).iter().map(|env| bin_dir(env)));
+    }
+    dirs
+}
+
+// Windows environments keep python.exe at their root rather than in bin/
+fn bin_dir(prefix: &Path) -> PathBuf {
+    if cfg!(windows) {
+        prefix.to_path_buf()
+    } else {
+        prefix.join("bin")
+    }
+}
+
+// Where resolve_interpreter looks besides the config. Read from the process
+// environment in practice; the tests supply their own.
+pub struct Lookup {
+    pub path_var: Option<OsString>,
+    // An activated virtualenv ($VIRTUAL_ENV) or conda env ($CONDA_PREFIX)
+    pub virtual_env: Option<PathBuf>,
+    pub conda_prefix: Option<PathBuf>,
+    // PATHEXT on Windows: extensions tried for a python_path given without one
+    pub path_ext: Option<OsString>,
+    // Searched after PATH, which is minimal when launched from a GUI (notably
+    // on macOS, where Homebrew's bin directory is missing)
+    pub fallback_dirs: Vec<PathBuf>,
+}
+
+impl Lookup {
+    pub fn from_env() -> Self {
+        #[cfg(windows)]
+        let fallback_dirs = {
+            // The py launcher installs into the Windows directory
+            let mut dirs = system_dirs();
+            dirs.extend(env::var_os("SystemRoot").map(PathBuf::from));
+            dirs
+        };
+        #[cfg(not(windows))]
+        let fallback_dirs = system_dirs();
+        Self {
+            path_var: env::var_os("PATH"),
+            virtual_env: env::var_os("VIRTUAL_ENV").map(PathBuf::from),
+            conda_prefix: env::var_os("CONDA_PREFIX").map(PathBuf::from),
+            path_ext: cfg!(windows).then(|| env::var_os("PATHEXT").unwrap_or_else(|| DEFAULT_PATHEXT.into())),
+            fallback_dirs,
+        }
+    }
+}
+
+// The interpreter to run main.py with (expects an expanded config):
+// 1. `python_path`, which must exist (a bare name is looked up on PATH)
+// 2. an activated virtualenv, then `.venv` or `venv` in the working directory
+// 3. an activated conda env
+// 4. python3/python on PATH, then in the usual install locations, then the py
+//    launcher on Windows
+pub fn resolve_interpreter(config: &RunnerConfig, working_dir: &Path) -> Result<PathBuf, String> {
+    resolve_with(config, working_dir, &Lookup::from_env())
+}
+
+fn resolve_with(config: &RunnerConfig, working_dir: &Path, lookup: &Lookup) -> Result<PathBuf, String> {
+    let path_dirs: Vec<PathBuf> = lookup
+        .path_var
+        .as_ref()
+        .map(|path| env::split_paths(path).collect())
+        .unwrap_or_default();
+
+    if let Some(python_path) = &config.python_path {
+        return find_python_path(python_path, &path_dirs, lookup)
+            .ok_or_else(|| format!("python_path {} does not exist", python_path));
+    }
+
+    let venvs = lookup
+        .virtual_env
+        .iter()
+        .cloned()
+        .chain([".venv", "venv"].iter().map(|name| working_dir.join(name)));
+    for venv in venvs {
+        if let Some(python) = find_in(&[venv_bin_dir(&venv)], EXECUTABLE_NAMES) {
+            return Ok(python);
+        }
+    }
+    if let Some(prefix) = &lookup.conda_prefix {
+        if let Some(python) = find_in(&[bin_dir(prefix)], EXECUTABLE_NAMES) {
+            return Ok(python);
+        }
+    }
+
+    let search: Vec<PathBuf> = path_dirs.iter().chain(&lookup.fallback_dirs).cloned().collect();
+    find_in(&search, EXECUTABLE_NAMES)
+        .or_else(|| find_in(&search, LAUNCHER_NAMES))
+        .ok_or_else(|| "No Python interpreter found; install Python or set python_path".to_string())
+}
+
+// Used when PATHEXT isn't set
+const DEFAULT_PATHEXT: &str = ".COM;.EXE;.BAT;.CMD";
+
+// `python_path` as given if it contains a directory, otherwise looked up on
+// PATH. On Windows a name without an extension also tries each PATHEXT one.
+fn find_python_path(python_path: &str, path_dirs: &[PathBuf], lookup: &Lookup) -> Option<PathBuf> {
+    let path = Path::new(python_path);
+    let mut names = vec![python_path.to_string()];
+    if let Some(path_ext) = lookup.path_ext.as_ref().filter(|_| path.extension().is_none()) {
+        let path_ext = path_ext.to_string_lossy();
+        names.extend(
+            path_ext
+                .split(';')
+                .filter(|extension| !extension.is_empty())
+                .map(|extension| format!("{}{}", python_path, extension.to_lowercase())),
+        );
+    }
+    if path.components().count() > 1 {
+        names.into_iter().map(PathBuf::from).find(|path| path.is_file())
+    } else {
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        find_in(path_dirs, &names)
+    }
+}
+
+// Where `python_path` (already expanded) resolves to, as a start would find it.
+pub fn resolve_python_path(python_path: &str) -> Option<PathBuf> {
+    let lookup = Lookup::from_env();
+    let path_dirs: Vec<PathBuf> = lookup.path_var.as_ref().map(|path| env::split_paths(path).collect()).unwrap_or_default();
+    find_python_path(python_path, &path_dirs, &lookup)
+}
+
+#[cfg(windows)]
+const LAUNCHER_NAMES: &[&str] = &["py.exe"];
+#[cfg(not(windows))]
+const LAUNCHER_NAMES: &[&str] = &[];
+
+// The first of `names`, in order of preference, in any of `dirs`.
+fn find_in(dirs: &[PathBuf], names: &[&str]) -> Option<PathBuf> {
+    names
+        .iter()
+        .flat_map(|name| dirs.iter().map(move |dir| dir.join(name)))
+        .find(|path| path.is_file())
+}
+
+// Windows virtualenvs keep python.exe in Scripts rather than bin (or the root, like conda)
+fn venv_bin_dir(venv: &Path) -> PathBuf {
+    if cfg!(windows) {
+        venv.join("Scripts")
+    } else {
+        venv.join("bin")
+    }
+}
+
+// Runs every candidate with `--version`, so keep it off the async runtime.
+#[tauri::command]
+pub async fn discover_interpreters() -> Result<Vec<PythonInterpreter>, String> {
+    tauri::async_runtime::spawn_blocking(discover)
+        .await
+        .map_err(|e| format!("Interpreter discovery failed: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn touch(dir: &Path, name: &str) -> PathBuf {
+        fs::create_dir_all(dir).unwrap();
+        let path = dir.join(name);
+        fs::write(&path, "").unwrap();
+        path
+    }
+
+    fn lookup(path_dirs: &[&Path]) -> Lookup {
+        Lookup {
+            path_var: Some(env::join_paths(path_dirs).unwrap()),
+            virtual_env: None,
+            conda_prefix: None,
+            path_ext: None,
+            fallback_dirs: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn explicit_python_path_wins() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let explicit = touch(&root.join("custom"), EXECUTABLE_NAMES[0]);
+        touch(&venv_bin_dir(&root.join(".venv")), EXECUTABLE_NAMES[0]);
+        let config = RunnerConfig {
+            python_path: Some(explicit.to_string_lossy().to_string()),
+            ..RunnerConfig::default()
+        };
+        assert_eq!(resolve_with(&config, root, &lookup(&[])).unwrap(), explicit);
+    }
+
+    #[test]
+    fn missing_python_path_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        touch(&root.join("bin"), EXECUTABLE_NAMES[0]);
+        let config = RunnerConfig {
+            python_path: Some(root.join("nope").join("python").to_string_lossy().to_string()),
+            ..RunnerConfig::default()
+        };
+        assert!(resolve_with(&config, root, &lookup(&[&root.join("bin")])).is_err());
+    }
+
+    #[test]
+    fn bare_python_path_is_looked_up_on_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let on_path = touch(&root.join("bin"), "python3.12");
+        let config = RunnerConfig {
+            python_path: Some("python3.12".to_string()),
+            ..RunnerConfig::default()
+        };
+        assert_eq!(resolve_with(&config, root, &lookup(&[&root.join("bin")])).unwrap(), on_path);
+    }
+
+    #[test]
+    fn path_ext_is_tried_for_a_name_without_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let exe = touch(&root.join("bin"), "python.exe");
+        let config = RunnerConfig {
+            python_path: Some("python".to_string()),
+            ..RunnerConfig::default()
+        };
+        let mut lookup = lookup(&[&root.join("bin")]);
+        assert!(resolve_with(&config, root, &lookup).is_err());
+        lookup.path_ext = Some(".COM;.EXE".into());
+        assert_eq!(resolve_with(&config, root, &lookup).unwrap(), exe);
+    }
+
+    #[test]
+    fn venv_before_conda_before_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let on_path = touch(&root.join("path"), EXECUTABLE_NAMES[0]);
+        let conda = touch(&bin_dir(&root.join("conda")), EXECUTABLE_NAMES[0]);
+        let local_venv = touch(&venv_bin_dir(&root.join(".venv")), EXECUTABLE_NAMES[0]);
+        let active_venv = touch(&venv_bin_dir(&root.join("active")), EXECUTABLE_NAMES[0]);
+        let config = RunnerConfig::default();
+
+        let mut lookup = lookup(&[&root.join("path")]);
+        lookup.conda_prefix = Some(root.join("conda"));
+        lookup.virtual_env = Some(root.join("active"));
+        assert_eq!(resolve_with(&config, root, &lookup).unwrap(), active_venv);
+
+        lookup.virtual_env = None;
+        assert_eq!(resolve_with(&config, root, &lookup).unwrap(), local_venv);
+
+        fs::remove_dir_all(root.join(".venv")).unwrap();
+        assert_eq!(resolve_with(&config, root, &lookup).unwrap(), conda);
+
+        lookup.conda_prefix = None;
+        assert_eq!(resolve_with(&config, root, &lookup).unwrap(), on_path);
+    }
+
+    #[test]
+    fn path_prefers_names_in_order_then_fallbacks() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let first = root.join("first");
+        let second = root.join("second");
+        touch(&first, EXECUTABLE_NAMES[1]);
+        let preferred = touch(&second, EXECUTABLE_NAMES[0]);
+        let config = RunnerConfig::default();
+        assert_eq!(resolve_with(&config, root, &lookup(&[&first, &second])).unwrap(), preferred);
+
+        let mut lookup = lookup(&[&root.join("empty")]);
+        assert!(resolve_with(&config, root, &lookup).is_err());
+        lookup.fallback_dirs = vec![second];
+        assert_eq!(resolve_with(&config, root, &lookup).unwrap(), preferred);
+    }
+}
@@ -0,0 +1,81 @@
+use std::io;
+
+// Keeps the system from sleeping while held; dropping it releases the hold.
+//
+// Windows uses SetThreadExecutionState, which is tied to the calling thread, so
+// a dedicated thread holds it. macOS and Linux run `caffeinate` and
+// `systemd-inhibit` helpers that also exit on their own once the runner does,
+// so a crashed app can't leave the machine unable to sleep.
+pub struct SleepInhibitor {
+    #[cfg(windows)]
+    _release: std::sync::mpsc::Sender<()>,
+    #[cfg(not(windows))]
+    helper: std::process::Child,
+}
+
+impl SleepInhibitor {
+    #[cfg(windows)]
+    pub fn acquire(_pid: u32) -> io::Result<Self> {
+        use std::sync::mpsc;
+        use windows_sys::Win32::System::Power::{SetThreadExecutionState, ES_CONTINUOUS, ES_SYSTEM_REQUIRED};
+
+        let (release, released) = mpsc::channel::<()>();
+        let (acquired_tx, acquired) = mpsc::channel();
+        std::thread::Builder::new()
+            .name("keep-awake".to_string())
+            .spawn(move || {
+                let ok = unsafe { SetThreadExecutionState(ES_CONTINUOUS | ES_SYSTEM_REQUIRED) } != 0;
+                let _ = acquired_tx.send(ok);
+                if ok {
+                    // Returns once the inhibitor, and with it the sender, is dropped
+                    let _ = released.recv();
+                    unsafe { SetThreadExecutionState(ES_CONTINUOUS) };
+                }
+            })?;
+        match acquired.recv() {
+            Ok(true) => Ok(Self { _release: release }),
+            _ => Err(io::Error::new(io::ErrorKind::Other, "SetThreadExecutionState failed")),
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    pub fn acquire(pid: u32) -> io::Result<Self> {
+        // -i prevents idle sleep; -w exits when the runner does
+        let helper = helper_command("caffeinate")
+            .arg("-i")
+            .arg("-w")
+            .arg(pid.to_string())
+            .spawn()?;
+        Ok(Self { helper })
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn acquire(pid: u32) -> io::Result<Self> {
+        // The inhibitor lasts as long as the command it runs, here a `tail`
+        // that exits when the runner does
+        let helper = helper_command("systemd-inhibit")
+            .arg("--what=sleep:idle")
+            .arg("--who=Oriphim Runner")
+            .arg("--why=Python runner is active")
+            .arg("--mode=block")
+            .args(["tail", &format!("--pid={}", pid), "-f", "/dev/null"])
+            .spawn()?;
+        Ok(Self { helper })
+    }
+}
+
+#[cfg(not(windows))]
+fn helper_command(program: &str) -> std::process::Command {
+    use std::process::{Command, Stdio};
+    let mut command = Command::new(program);
+    command.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null());
+    command
+}
+
+#[cfg(not(windows))]
+impl Drop for SleepInhibitor {
+    fn drop(&mut self) {
+        let _ = self.helper.kill();
+        let _ = self.helper.wait();
+    }
+}
@@ -0,0 +1,98 @@
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use log::warn;
+
+use crate::RunnerState;
+
+// Starts remembered for get_startup_latencies
+const MAX_SAMPLES: usize = 100;
+// Starts averaged for the moving average
+const AVERAGE_WINDOW: usize = 10;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LatencySample {
+    pub timestamp: DateTime<Local>,
+    // From spawn until the readiness checks passed
+    pub latency_ms: u64,
+}
+
+// Start-to-ready time of recent starts, oldest first.
+#[derive(Default)]
+pub struct StartupLatencies {
+    samples: VecDeque<LatencySample>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StartupLatencyReport {
+    pub samples: Vec<LatencySample>,
+    // Mean of the last AVERAGE_WINDOW starts
+    pub moving_average_ms: Option<u64>,
+}
+
+impl StartupLatencies {
+    fn push(&mut self, sample: LatencySample) {
+        if self.samples.len() == MAX_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    fn moving_average_ms(&self) -> Option<u64> {
+        let window: Vec<u64> = self.samples.iter().rev().take(AVERAGE_WINDOW).map(|s| s.latency_ms).collect();
+        if window.is_empty() {
+            return None;
+        }
+        Some(window.iter().sum::<u64>() / window.len() as u64)
+    }
+}
+
+#[derive(Clone, Serialize)]
+struct SlowStartEvent {
+    latency_ms: u64,
+    threshold_ms: u64,
+    moving_average_ms: Option<u64>,
+}
+
+// Record how long the current start took to become ready, warning when it
+// exceeds `startup_latency_warn_ms`.
+pub fn record(app: &AppHandle, state: &RunnerState, latency: Duration) {
+    let latency_ms = latency.as_millis() as u64;
+    let moving_average_ms = {
+        let mut latencies = state.startup_latencies.lock().unwrap();
+        latencies.push(LatencySample {
+            timestamp: Local::now(),
+            latency_ms,
+        });
+        latencies.moving_average_ms()
+    };
+
+    let threshold_ms = state.config.lock().unwrap().startup_latency_warn_ms;
+    if let Some(threshold_ms) = threshold_ms.filter(|&threshold| latency_ms > threshold) {
+        warn!("Python runner took {}ms to become ready (threshold {}ms)", latency_ms, threshold_ms);
+        let _ = app.emit_all(
+            "runner-slow-start",
+            SlowStartEvent {
+                latency_ms,
+                threshold_ms,
+                moving_average_ms,
+            },
+        );
+    }
+}
+
+// Recent start-to-ready times, newest `limit` (default all), and their moving average.
+#[tauri::command]
+pub async fn get_startup_latencies(
+    limit: Option<usize>,
+    state: tauri::State<'_, RunnerState>,
+) -> Result<StartupLatencyReport, String> {
+    let latencies = state.startup_latencies.lock().unwrap();
+    let skip = latencies.samples.len().saturating_sub(limit.unwrap_or(MAX_SAMPLES));
+    Ok(StartupLatencyReport {
+        samples: latencies.samples.iter().skip(skip).cloned().collect(),
+        moving_average_ms: latencies.moving_average_ms(),
+    })
+}
@@ -0,0 +1,32 @@
+use std::env;
+use std::path::{Path, PathBuf};
+
+#[cfg(windows)]
+const EXTENSIONS: &[&str] = &["", ".exe", ".bat", ".cmd"];
+#[cfg(not(windows))]
+const EXTENSIONS: &[&str] = &[""];
+
+// Resolve the wrapper program: a path as given, otherwise the first match on PATH.
+pub fn find(program: &str) -> Option<PathBuf> {
+    let path = Path::new(program);
+    if path.components().count() > 1 {
+        return EXTENSIONS
+            .iter()
+            .map(|ext| PathBuf::from(format!("{}{}", program, ext)))
+            .find(|path| path.is_file());
+    }
+    let dirs = env::var_os("PATH")?;
+    env::split_paths(&dirs)
+        .flat_map(|dir| EXTENSIONS.iter().map(move |ext| dir.join(format!("{}{}", program, ext))))
+        .find(|path| path.is_file())
+}
+
+// Prepend `launcher` (split on whitespace, e.g. `poetry run`) to the command,
+// so the wrapper receives the interpreter and script as its arguments.
+pub fn wrap(launcher: &str, program: String, args: Vec<String>) -> Result<(String, Vec<String>), String> {
+    let mut words = launcher.split_whitespace();
+    let wrapper = words.next().ok_or("launcher is empty")?;
+    let wrapper = find(wrapper).ok_or_else(|| format!("Launcher '{}' was not found", wrapper))?;
+    let wrapped = words.map(str::to_string).chain(std::iter::once(program)).chain(args).collect();
+    Ok((wrapper.to_string_lossy().to_string(), wrapped))
+}
@@ -0,0 +1,232 @@
+use chrono::{DateTime, Duration, Local};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::sync::Mutex;
+use log::warn;
+
+use crate::paths;
+use crate::system_log::{self, Level};
+
+// Entries older than this are dropped from lifecycle.jsonl
+const RETENTION_DAYS: i64 = 30;
+const DEFAULT_EVENT_LIMIT: usize = 100;
+
+// Serializes appends and pruning across threads
+static FILE_LOCK: Mutex<()> = Mutex::new(());
+// Label set with `set_session_label`, cleared by each manual start
+static SESSION_LABEL: Mutex<Option<String>> = Mutex::new(None);
+
+pub fn session_label() -> Option<String> {
+    SESSION_LABEL.lock().unwrap().clone()
+}
+
+pub fn set_session_label(label: Option<String>) {
+    *SESSION_LABEL.lock().unwrap() = label;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LifecycleKind {
+    Start,
+    StartFailed,
+    Stop,
+    // Exited on its own with status 0
+    Exit,
+    Crash,
+    // An automatic restart was scheduled, or given up on
+    Restart,
+}
+
+impl LifecycleKind {
+    fn level(self) -> Level {
+        match self {
+            LifecycleKind::Start | LifecycleKind::Stop | LifecycleKind::Exit => Level::Info,
+            LifecycleKind::Restart => Level::Warning,
+            LifecycleKind::StartFailed | LifecycleKind::Crash => Level::Error,
+        }
+    }
+}
+
+// One entry in the runner's own audit trail, ~/.oriphim/lifecycle.jsonl.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LifecycleEvent {
+    pub timestamp: DateTime<Local>,
+    pub kind: LifecycleKind,
+    pub pid: Option<u32>,
+    pub exit_code: Option<i32>,
+    pub reason: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_label: Option<String>,
+    // How the process was launched, on `start` entries
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<StartContext>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartContext {
+    pub command_line: String,
+    pub working_dir: String,
+    // None under conda_env, or if `--version` failed
+    pub interpreter_version: Option<String>,
+}
+
+impl LifecycleEvent {
+    // e.g. "crash (pid 1234, exit code 1): exit status: 1"
+    fn summary(&self) -> String {
+        let kind = serde_json::to_value(self.kind)
+            .ok()
+            .and_then(|kind| kind.as_str().map(String::from))
+            .unwrap_or_default();
+        let details: Vec<String> = self
+            .pid
+            .map(|pid| format!("pid {}", pid))
+            .into_iter()
+            .chain(self.exit_code.map(|code| format!("exit code {}", code)))
+            .collect();
+        let details: Vec<String> = details
+            .into_iter()
+            .chain(self.session_label.as_ref().map(|label| format!("session '{}'", label)))
+            .collect();
+        if details.is_empty() {
+            format!("{}: {}", kind, self.reason)
+        } else {
+            format!("{} ({}): {}", kind, details.join(", "), self.reason)
+        }
+    }
+}
+
+// Append an entry. Failures are logged rather than returned so the audit trail
+// can never get in the way of starting or stopping the runner.
+pub fn record(kind: LifecycleKind, pid: Option<u32>, exit_code: Option<i32>, reason: impl Into<String>) {
+    write(LifecycleEvent {
+        timestamp: Local::now(),
+        kind,
+        pid,
+        exit_code,
+        reason: reason.into(),
+        session_label: session_label(),
+        start: None,
+    });
+}
+
+// Record a start along with its full launch context.
+pub fn record_start(pid: u32, context: StartContext) {
+    write(LifecycleEvent {
+        timestamp: Local::now(),
+        kind: LifecycleKind::Start,
+        pid: Some(pid),
+        exit_code: None,
+        reason: context.command_line.clone(),
+        session_label: session_label(),
+        start: Some(context),
+    });
+}
+
+fn write(event: LifecycleEvent) {
+    system_log::write(event.kind.level(), &event.summary());
+    if let Err(e) = append(&event) {
+        warn!("Failed to record lifecycle event: {}", e);
+    }
+}
+
+fn append(event: &LifecycleEvent) -> Result<(), String> {
+    let path = paths::lifecycle_file().ok_or("Could not find home directory")?;
+    let _lock = FILE_LOCK.lock().unwrap();
+
+    if oldest_entry_expired(&path) {
+        prune_locked(&path)?;
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let line = serde_json::to_string(event).map_err(|e| format!("Failed to serialize lifecycle event: {}", e))?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    writeln!(file, "{}", line).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn cutoff() -> DateTime<Local> {
+    Local::now() - Duration::days(RETENTION_DAYS)
+}
+
+// Entries are appended in time order, so only the first one needs checking.
+fn oldest_entry_expired(path: &std::path::Path) -> bool {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(_) => return false,
+    };
+    let mut first = String::new();
+    if BufReader::new(file).read_line(&mut first).is_err() {
+        return false;
+    }
+    match serde_json::from_str::<LifecycleEvent>(&first) {
+        Ok(event) => event.timestamp < cutoff(),
+        // Rewrite a file that starts with garbage too
+        Err(_) => !first.trim().is_empty(),
+    }
+}
+
+// Rewrite the file without entries past the retention period, or that can't be parsed.
+fn prune_locked(path: &std::path::Path) -> Result<(), String> {
+    let cutoff = cutoff();
+    let kept: Vec<String> = read_events(path)
+        .into_iter()
+        .filter(|event| event.timestamp >= cutoff)
+        .filter_map(|event| serde_json::to_string(&event).ok())
+        .collect();
+    let mut contents = kept.join("\n");
+    if !contents.is_empty() {
+        contents.push('\n');
+    }
+    fs::write(path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+// Drop old entries, e.g. at launch.
+pub fn prune() {
+    let path = match paths::lifecycle_file() {
+        Some(path) if path.exists() => path,
+        _ => return,
+    };
+    let _lock = FILE_LOCK.lock().unwrap();
+    if let Err(e) = prune_locked(&path) {
+        warn!("Failed to prune lifecycle log: {}", e);
+    }
+}
+
+fn read_events(path: &std::path::Path) -> Vec<LifecycleEvent> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(_) => return Vec::new(),
+    };
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str(&line) {
+            Ok(event) => Some(event),
+            Err(e) => {
+                warn!("Skipping invalid lifecycle entry: {}", e);
+                None
+            }
+        })
+        .collect()
+}
+
+// The most recent `limit` (default 100) lifecycle entries, oldest first.
+#[tauri::command]
+pub async fn get_lifecycle_events(limit: Option<usize>) -> Result<Vec<LifecycleEvent>, String> {
+    let path = paths::lifecycle_file().ok_or("Could not find home directory")?;
+    let mut events = {
+        let _lock = FILE_LOCK.lock().unwrap();
+        read_events(&path)
+    };
+    let limit = limit.unwrap_or(DEFAULT_EVENT_LIMIT);
+    if events.len() > limit {
+        events.drain(..events.len() - limit);
+    }
+    Ok(events)
+}
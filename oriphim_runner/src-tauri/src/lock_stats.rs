@@ -0,0 +1,124 @@
+use serde::Serialize;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LockResult, Mutex, MutexGuard, PoisonError, TryLockError};
+use std::time::Instant;
+
+use crate::RunnerState;
+
+#[derive(Default)]
+struct Counters {
+    acquisitions: AtomicU64,
+    // Acquisitions that found the lock held and had to wait
+    contended: AtomicU64,
+    total_wait_us: AtomicU64,
+    max_wait_us: AtomicU64,
+    max_hold_us: AtomicU64,
+}
+
+// A Mutex that counts how often `lock` had to wait, for how long, and the
+// longest time the lock was held. `lock().unwrap()` works as with Mutex.
+pub struct TrackedMutex<T> {
+    name: &'static str,
+    inner: Mutex<T>,
+    counters: Counters,
+}
+
+pub struct TrackedGuard<'a, T> {
+    guard: MutexGuard<'a, T>,
+    counters: &'a Counters,
+    acquired: Instant,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LockStats {
+    pub name: &'static str,
+    pub acquisitions: u64,
+    pub contended: u64,
+    pub total_wait_us: u64,
+    pub max_wait_us: u64,
+    pub max_hold_us: u64,
+}
+
+impl<T> TrackedMutex<T> {
+    pub fn new(name: &'static str, value: T) -> Self {
+        Self { name, inner: Mutex::new(value), counters: Counters::default() }
+    }
+
+    pub fn lock(&self) -> LockResult<TrackedGuard<'_, T>> {
+        self.counters.acquisitions.fetch_add(1, Ordering::Relaxed);
+        let result = match self.inner.try_lock() {
+            Ok(guard) => Ok(guard),
+            Err(TryLockError::Poisoned(e)) => Err(e),
+            Err(TryLockError::WouldBlock) => {
+                let waiting = Instant::now();
+                let result = self.inner.lock();
+                let waited = waiting.elapsed().as_micros() as u64;
+                self.counters.contended.fetch_add(1, Ordering::Relaxed);
+                self.counters.total_wait_us.fetch_add(waited, Ordering::Relaxed);
+                self.counters.max_wait_us.fetch_max(waited, Ordering::Relaxed);
+                result
+            }
+        };
+        let wrap = |guard| TrackedGuard { guard, counters: &self.counters, acquired: Instant::now() };
+        result.map(wrap).map_err(|e| PoisonError::new(wrap(e.into_inner())))
+    }
+
+    pub fn stats(&self) -> LockStats {
+        let counters = &self.counters;
+        LockStats {
+            name: self.name,
+            acquisitions: counters.acquisitions.load(Ordering::Relaxed),
+            contended: counters.contended.load(Ordering::Relaxed),
+            total_wait_us: counters.total_wait_us.load(Ordering::Relaxed),
+            max_wait_us: counters.max_wait_us.load(Ordering::Relaxed),
+            max_hold_us: counters.max_hold_us.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl<T> Deref for TrackedGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T> DerefMut for TrackedGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+impl<T> Drop for TrackedGuard<'_, T> {
+    fn drop(&mut self) {
+        let held = self.acquired.elapsed().as_micros() as u64;
+        self.counters.max_hold_us.fetch_max(held, Ordering::Relaxed);
+    }
+}
+
+// Contention for every lock in RunnerState since startup, most waited on first.
+pub fn snapshot(state: &RunnerState) -> Vec<LockStats> {
+    let mut stats = state.lock_stats();
+    stats.sort_by_key(|lock| std::cmp::Reverse((lock.total_wait_us, lock.contended)));
+    stats
+}
+
+// For tracking down UI hangs: which RunnerState locks are waited on, and for how long.
+#[tauri::command]
+pub async fn get_lock_stats(state: tauri::State<'_, RunnerState>) -> Result<Vec<LockStats>, String> {
+    Ok(snapshot(&state))
+}
+
+// The busiest locks as text, appended to error reports in debug builds.
+pub fn report(state: &RunnerState) -> String {
+    let mut report = String::from("--- lock stats ---");
+    for lock in snapshot(state).iter().filter(|lock| lock.contended > 0) {
+        report.push_str(&format!(
+            "\n{}: {}/{} contended, {}us total wait, {}us max wait, {}us max hold",
+            lock.name, lock.contended, lock.acquisitions, lock.total_wait_us, lock.max_wait_us, lock.max_hold_us
+        ));
+    }
+    report
+}
@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use tauri::{AppHandle, Manager, Window, WindowBuilder, WindowUrl};
+use log::{info, warn};
+
+use crate::paths;
+
+pub const LOG_WINDOW_LABEL: &str = "logs";
+
+// Position and size in logical pixels, so they survive a change of display scaling.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+// ~/.oriphim/window.json maps window labels to their last geometry.
+fn load_all() -> HashMap<String, WindowGeometry> {
+    paths::window_state_file()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+pub fn load_geometry(label: &str) -> Option<WindowGeometry> {
+    load_all().get(label).copied()
+}
+
+pub fn save_geometry(window: &Window) {
+    let geometry = match current_geometry(window) {
+        Ok(geometry) => geometry,
+        Err(e) => {
+            warn!("Could not read geometry of window '{}': {}", window.label(), e);
+            return;
+        }
+    };
+    let path = match paths::window_state_file() {
+        Some(path) => path,
+        None => return,
+    };
+
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+
+    let mut all = load_all();
+    all.insert(window.label().to_string(), geometry);
+    let result = serde_json::to_string_pretty(&all)
+        .map_err(|e| e.to_string())
+        .and_then(|contents| fs::write(&path, contents).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        warn!("Failed to save window geometry to {}: {}", path.display(), e);
+    }
+}
+
+fn current_geometry(window: &Window) -> tauri::Result<WindowGeometry> {
+    let scale = window.scale_factor()?;
+    let position = window.outer_position()?.to_logical::<f64>(scale);
+    let size = window.inner_size()?.to_logical::<f64>(scale);
+    Ok(WindowGeometry {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+    })
+}
+
+// Open the live console in its own window, or focus it if it's already open.
+// Unlike the main window it really closes; its geometry is saved when it does.
+#[tauri::command]
+pub async fn open_log_window(app: AppHandle) -> Result<String, String> {
+    if let Some(window) = app.get_window(LOG_WINDOW_LABEL) {
+        let _ = window.show();
+        let _ = window.set_focus();
+        return Ok("Log window focused".to_string());
+    }
+
+    let mut builder = WindowBuilder::new(&app, LOG_WINDOW_LABEL, WindowUrl::App("logs.html".into()))
+        .title("Oriphim Runner Logs")
+        .min_inner_size(400.0, 200.0);
+    builder = match load_geometry(LOG_WINDOW_LABEL) {
+        Some(geometry) => builder
+            .position(geometry.x, geometry.y)
+            .inner_size(geometry.width, geometry.height),
+        None => builder.inner_size(700.0, 450.0).center(),
+    };
+    builder
+        .build()
+        .map_err(|e| format!("Failed to open log window: {}", e))?;
+
+    info!("Opened log window");
+    Ok("Log window opened".to_string())
+}
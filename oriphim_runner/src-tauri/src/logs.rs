@@ -0,0 +1,362 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+// How long lines are dropped after a failed write before writing is tried again
+pub const RETRY_INTERVAL: Duration = Duration::from_secs(60);
+
+// A change in whether the log file can be written, reported by `log_line`.
+pub enum LoggingChange {
+    Disabled(String),
+    Enabled,
+}
+
+// Size-rotated log file for the Python runner's output.
+//
+// Lines are only ever written whole, and rotation happens between writes, so a
+// line can never be split across two files. Rotated files are shifted to
+// `runner.1.log`, `runner.2.log`, ... and the oldest beyond `max_files` is removed.
+pub struct LogFile {
+    path: PathBuf,
+    writer: Option<BufWriter<File>>,
+    written: u64,
+    max_bytes: u64,
+    max_files: usize,
+    // When the last write failed, while lines are being dropped
+    failed_at: Option<Instant>,
+    // The last rotation failed; it is retried after the next write
+    rotation_failed: bool,
+}
+
+impl LogFile {
+    pub fn open(path: PathBuf, max_bytes: u64, max_files: usize) -> io::Result<Self> {
+        let mut log = Self::closed(path, max_bytes, max_files);
+        log.reopen()?;
+        Ok(log)
+    }
+
+    // A log file that is opened by the first write, for one that couldn't be
+    // opened up front.
+    pub fn closed(path: PathBuf, max_bytes: u64, max_files: usize) -> Self {
+        Self {
+            path,
+            writer: None,
+            written: 0,
+            max_bytes,
+            max_files,
+            failed_at: None,
+            rotation_failed: false,
+        }
+    }
+
+    // Rotation limits apply from the next write.
+    pub fn set_limits(&mut self, max_bytes: u64, max_files: usize) {
+        self.max_bytes = max_bytes;
+        self.max_files = max_files;
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn write_line(&mut self, line: &str) -> io::Result<()> {
+        if self.writer.is_none() {
+            self.reopen()?;
+        }
+        let writer = self.writer.as_mut().expect("log file was just reopened");
+        writer.write_all(line.as_bytes())?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+        self.written += line.len() as u64 + 1;
+
+        // The line is already written, so a failed rotation doesn't fail the write
+        if self.max_bytes > 0 && self.written >= self.max_bytes {
+            match self.rotate() {
+                Ok(()) => self.rotation_failed = false,
+                Err(e) => {
+                    if !std::mem::replace(&mut self.rotation_failed, true) {
+                        log::warn!("Failed to rotate {}, retrying after the next line: {}", self.path.display(), e);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Write `line` unless writing failed less than RETRY_INTERVAL ago, in which
+    // case it is dropped. A failure closes the file so the retry reopens it.
+    // Returns a change in whether the file is being written.
+    pub fn log_line(&mut self, line: &str) -> Option<LoggingChange> {
+        let failing = self.failed_at.is_some();
+        if self.failed_at.is_some_and(|failed_at| failed_at.elapsed() < RETRY_INTERVAL) {
+            return None;
+        }
+        match self.write_line(line) {
+            Ok(()) => {
+                self.failed_at = None;
+                failing.then_some(LoggingChange::Enabled)
+            }
+            Err(e) => {
+                self.failed_at = Some(Instant::now());
+                self.writer = None;
+                (!failing).then(|| LoggingChange::Disabled(e.to_string()))
+            }
+        }
+    }
+
+    // Flush and close the current file, shift it into the rotation, and start a fresh one.
+    pub fn rotate(&mut self) -> io::Result<()> {
+        if let Some(mut writer) = self.writer.take() {
+            writer.flush()?;
+        }
+
+        if self.max_files == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            let _ = fs::remove_file(self.rotated_path(self.max_files));
+            for index in (1..self.max_files).rev() {
+                let from = self.rotated_path(index);
+                if from.exists() {
+                    fs::rename(&from, self.rotated_path(index + 1))?;
+                }
+            }
+            fs::rename(&self.path, self.rotated_path(1))?;
+        }
+
+        self.reopen()
+    }
+
+    // Flush any buffered bytes and fsync the active file so it survives a crash
+    // or power loss.
+    pub fn sync(&mut self) -> io::Result<()> {
+        if let Some(writer) = self.writer.as_mut() {
+            writer.flush()?;
+            writer.get_ref().sync_all()?;
+        }
+        Ok(())
+    }
+
+    // Copy the active file to `dest`. Lines are flushed as they're written, so
+    // the copy holds every whole line logged so far.
+    pub fn snapshot(&self, dest: &Path) -> io::Result<u64> {
+        if let Some(dir) = dest.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        if !self.path.exists() {
+            File::create(dest)?;
+            return Ok(0);
+        }
+        fs::copy(&self.path, dest)
+    }
+
+    fn reopen(&mut self) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        self.written = file.metadata()?.len();
+        self.writer = Some(BufWriter::new(file));
+        Ok(())
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        rotated_path(&self.path, index)
+    }
+}
+
+// Where rotation `index` of `path` is kept: `runner.log` -> `runner.1.log`.
+pub fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    match path.extension() {
+        Some(ext) => path.with_file_name(format!("{}.{}.{}", stem, index, ext.to_string_lossy())),
+        None => path.with_file_name(format!("{}.{}", stem, index)),
+    }
+}
+
+// How each output line is written to the log file: the timestamp, then the stream
+pub const DEFAULT_LOG_FORMAT: &str = "{timestamp} [{source}] {message}";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Timestamp,
+    Level,
+    Source,
+    Message,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Piece {
+    Text(String),
+    Field(Field),
+}
+
+// A parsed `log_format` template. `{{` and `}}` stand for literal braces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogFormat {
+    pieces: Vec<Piece>,
+}
+
+impl Default for LogFormat {
+    fn default() -> Self {
+        Self::parse(DEFAULT_LOG_FORMAT).expect("default log format is valid")
+    }
+}
+
+impl LogFormat {
+    pub fn parse(template: &str) -> Result<Self, String> {
+        let mut pieces = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(format!("Unclosed {{{}, expected }} after the placeholder name", name)),
+                        }
+                    }
+                    let field = match name.as_str() {
+                        "timestamp" => Field::Timestamp,
+                        "level" => Field::Level,
+                        "source" => Field::Source,
+                        "message" => Field::Message,
+                        _ => {
+                            return Err(format!(
+                                "Unknown placeholder {{{}}}, expected {{timestamp}}, {{level}}, {{source}} or {{message}}",
+                                name
+                            ))
+                        }
+                    };
+                    if !text.is_empty() {
+                        pieces.push(Piece::Text(std::mem::take(&mut text)));
+                    }
+                    pieces.push(Piece::Field(field));
+                }
+                '}' => return Err("Unmatched }, write }} for a literal brace".to_string()),
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            pieces.push(Piece::Text(text));
+        }
+        if !pieces.contains(&Piece::Field(Field::Message)) {
+            return Err("The template has no {message}".to_string());
+        }
+        Ok(Self { pieces })
+    }
+
+    // Parse `template`, falling back to the default so a bad template doesn't
+    // stop output from being logged. `validate` reports the error.
+    pub fn compile(template: &str) -> Self {
+        Self::parse(template).unwrap_or_else(|e| {
+            log::error!("Ignoring invalid log_format: {}", e);
+            Self::default()
+        })
+    }
+
+    pub fn is_default(&self) -> bool {
+        static DEFAULT: OnceLock<LogFormat> = OnceLock::new();
+        self == DEFAULT.get_or_init(LogFormat::default)
+    }
+
+    pub fn format(&self, timestamp: &str, level: &str, source: &str, message: &str) -> String {
+        let mut line = String::with_capacity(timestamp.len() + message.len() + 16);
+        for piece in &self.pieces {
+            line.push_str(match piece {
+                Piece::Text(text) => text,
+                Piece::Field(Field::Timestamp) => timestamp,
+                Piece::Field(Field::Level) => level,
+                Piece::Field(Field::Source) => source,
+                Piece::Field(Field::Message) => message,
+            });
+        }
+        line
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_all_lines(log: &LogFile) -> Vec<String> {
+        // Oldest rotated file first, then the active file.
+        let mut lines = Vec::new();
+        for index in (1..=log.max_files).rev() {
+            if let Ok(contents) = fs::read_to_string(log.rotated_path(index)) {
+                lines.extend(contents.lines().map(String::from));
+            }
+        }
+        lines.extend(fs::read_to_string(log.path()).unwrap().lines().map(String::from));
+        lines
+    }
+
+    #[test]
+    fn rotation_never_splits_or_drops_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = LogFile::open(dir.path().join("runner.log"), 64, 100).unwrap();
+
+        let expected: Vec<String> = (0..50).map(|i| format!("line {:03} {}", i, "x".repeat(i % 7))).collect();
+        for (i, line) in expected.iter().enumerate() {
+            log.write_line(line).unwrap();
+            if i == 20 {
+                log.rotate().unwrap();
+            }
+        }
+
+        assert!(log.rotated_path(2).exists(), "expected several rotations");
+        assert_eq!(read_all_lines(&log), expected);
+    }
+
+    #[test]
+    fn rotation_keeps_at_most_max_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = LogFile::open(dir.path().join("runner.log"), 0, 2).unwrap();
+
+        for i in 0..5 {
+            log.write_line(&format!("line {}", i)).unwrap();
+            log.rotate().unwrap();
+        }
+
+        assert!(log.rotated_path(2).exists());
+        assert!(!log.rotated_path(3).exists());
+        assert_eq!(fs::read_to_string(log.rotated_path(1)).unwrap(), "line 4\n");
+    }
+
+    #[test]
+    fn failed_rotation_keeps_writing_and_is_retried() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = LogFile::open(dir.path().join("runner.log"), 16, 1).unwrap();
+        // A non-empty directory where the rotated file goes makes the rename fail
+        fs::create_dir_all(log.rotated_path(1).join("blocker")).unwrap();
+
+        log.write_line("first line, over the limit").unwrap();
+        assert!(log.log_line("second line").is_none());
+        assert_eq!(fs::read_to_string(log.path()).unwrap(), "first line, over the limit\nsecond line\n");
+
+        fs::remove_dir_all(log.rotated_path(1)).unwrap();
+        log.write_line("third line").unwrap();
+        assert_eq!(
+            fs::read_to_string(log.rotated_path(1)).unwrap(),
+            "first line, over the limit\nsecond line\nthird line\n"
+        );
+    }
+
+    #[test]
+    fn unclosed_placeholder_is_an_error() {
+        assert!(LogFormat::parse("{timestamp} {message").is_err());
+        assert!(LogFormat::parse("{timestamp} {message}").is_ok());
+    }
+}
@@ -1,137 +1,1716 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod affinity;
+mod argsfile;
+mod artifacts;
+mod benchmark;
+mod buffer;
+mod circuit;
+mod crash_debug;
+mod crash_dump;
+mod diagnose;
+mod elevation;
+mod fingerprint;
+mod conda;
+mod control;
+mod config;
+mod handles;
+mod heartbeat;
+mod install_check;
+mod idle;
+mod interpreters;
+mod latency;
+mod launcher;
+mod keep_awake;
+mod lifecycle;
+mod lock_stats;
+mod log_window;
+mod logs;
+mod max_runtime;
+mod metrics;
+mod metrics_history;
+mod migrate;
+mod notify;
+mod oneshot;
+mod output;
+mod overrides;
+mod paths;
+mod process;
+mod prometheus;
+mod readiness;
+mod saved_state;
+mod schedule;
+mod script;
+mod shutdown_test;
+mod single_instance;
+mod stats;
+mod support_bundle;
+mod stdin_recording;
+mod supervisor;
+mod survivor;
+mod traceback;
+mod system_log;
+mod terminal;
+mod workspace;
+
 use tauri::{
-    CustomMenuItem, Manager, SystemTray, SystemTrayEvent, SystemTrayMenu,
-    SystemTrayMenuItem, SystemTraySubmenu, WindowEvent
+    ClipboardManager, CustomMenuItem, Manager, SystemTray, SystemTrayEvent, SystemTrayMenu,
+    SystemTrayMenuItem, WindowEvent
 };
+use std::collections::BTreeMap;
+use std::path::Path;
 use std::process::{Command, Stdio};
+use std::sync::atomic::Ordering;
 use std::sync::{Arc, Mutex};
-use std::thread;
-use log::{info, error, warn};
+use std::time::{Duration, Instant};
+use log::{debug, info, error, warn};
+use serde::Serialize;
+use buffer::{LogBuffer, LogLine, STDERR_BUFFER_LINES};
+use circuit::CircuitBreaker;
+use config::{RunnerConfig, Severity};
+use crash_dump::LastCrash;
+use heartbeat::HeartbeatCheck;
+use keep_awake::SleepInhibitor;
+use latency::StartupLatencies;
+use lifecycle::{LifecycleKind, StartContext};
+use lock_stats::TrackedMutex;
+use logs::{LogFile, LogFormat};
+use metrics::ProcessMetrics;
+use output::{NotifyLimiter, OutputOptions, OutputThrottle, WatchMatcher};
+use overrides::ConfigOverrides;
+use process::ManagedProcess;
+use readiness::{Health, ReadinessChecks};
+use saved_state::SavedState;
+use schedule::Schedule;
+use stats::{SessionStats, SessionStatsSnapshot};
+use supervisor::RestartState;
+use survivor::ReconnectedProcess;
+
+// Log levels the Python script accepts via the `set-level` stdin command
+const LOG_LEVELS: &[&str] = &["DEBUG", "INFO", "WARNING", "ERROR", "CRITICAL"];
+
+// stderr lines included in error reports
+const ERROR_REPORT_STDERR_LINES: usize = 20;
+
+// Label of the window defined in tauri.conf.json
+const MAIN_WINDOW_LABEL: &str = "main";
 
 // Runner state management
 #[derive(Clone)]
 struct RunnerState {
-    python_process: Arc<Mutex<Option<std::process::Child>>>,
-    is_running: Arc<Mutex<bool>>,
+    python_process: Arc<TrackedMutex<Option<ManagedProcess>>>,
+    is_running: Arc<TrackedMutex<bool>>,
+    config: Arc<TrackedMutex<RunnerConfig>>,
+    // Level last sent with `set-level`; None while the script uses its own default
+    log_level: Arc<TrackedMutex<Option<String>>>,
+    // `watch_patterns` compiled when the config was loaded
+    watch_patterns: Arc<TrackedMutex<Vec<WatchMatcher>>>,
+    // `log_format`, likewise
+    log_format: Arc<TrackedMutex<LogFormat>>,
+    circuit_breaker: Arc<TrackedMutex<CircuitBreaker>>,
+    // ~/.oriphim/logs/runner.log, shared by the output reader threads
+    log_file: Arc<TrackedMutex<Option<LogFile>>>,
+    // Recent output from both streams, capped at `log_buffer_lines`
+    log_buffer: Arc<TrackedMutex<LogBuffer>>,
+    // Just stderr, for crash reports and `get_recent_stderr`
+    stderr_buffer: Arc<TrackedMutex<LogBuffer>>,
+    stats: Arc<SessionStats>,
+    // Latest `runner-metrics` sample, cleared when the process goes away
+    metrics: Arc<TrackedMutex<Option<ProcessMetrics>>>,
+    health: Arc<TrackedMutex<Health>>,
+    restart: Arc<TrackedMutex<RestartState>>,
+    // Full report of the most recent start failure or crash, for `copy_last_error`
+    last_error: Arc<TrackedMutex<Option<String>>>,
+    // Scheduled start/stop times, persisted in state.json
+    schedule: Arc<TrackedMutex<Schedule>>,
+    last_crash: Arc<TrackedMutex<Option<LastCrash>>>,
+    startup_latencies: Arc<TrackedMutex<StartupLatencies>>,
+    operations: Arc<TrackedMutex<Operations>>,
+    // Set by `launch_with_overrides` until the next normal start
+    overrides: Arc<TrackedMutex<Option<ConfigOverrides>>>,
+    // A runner left running by the previous session, found at startup
+    reconnected: Arc<TrackedMutex<Option<ReconnectedProcess>>>,
+    // Set by `arm_crash_debug` until the next start uses it
+    crash_debug_armed: Arc<TrackedMutex<bool>>,
+    // Set between `start_recording_stdin` and `stop_recording_stdin`
+    stdin_recording: Arc<TrackedMutex<Option<stdin_recording::Recorder>>>,
+}
+
+// Something waiting for the stop in progress to finish
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum QueuedOperation {
+    Start,
+}
+
+// A stop outlives the process leaving the state, so a start arriving during its
+// grace period waits here instead of running alongside the old process.
+#[derive(Default)]
+struct Operations {
+    stopping: bool,
+    queued: Option<QueuedOperation>,
+}
+
+// Extra time on top of stop_grace_secs for the force kill and reader join
+const STOP_QUEUE_MARGIN: Duration = Duration::from_secs(10);
+const STOP_QUEUE_POLL: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Serialize)]
+struct RunnerStatus {
+    running: bool,
+    log_level: Option<String>,
+    process_group_id: Option<u32>,
+    health: Health,
+    session_label: Option<String>,
+    // A stop is still waiting for the old process to exit
+    stopping: bool,
+    queued: Option<QueuedOperation>,
+    // OS-reported start time of the process, for "running since"
+    started_at: Option<chrono::DateTime<chrono::Local>>,
+    // Output mode of the running process (`line_buffered`), None when stopped
+    line_buffered: Option<bool>,
+    // Automatic restarts are halted until `reset_circuit_breaker`
+    circuit_breaker_tripped: bool,
+    // `launch_with_overrides` variant of the running process
+    variant: Option<String>,
+    // Seconds left on `snooze_notifications`
+    notifications_snoozed_secs: Option<u64>,
+    // PID of a runner from the previous session that is still running
+    reconnected_pid: Option<u32>,
+    // `arm_crash_debug` applies to the next start
+    crash_debug_armed: bool,
+    // The running process was started with crash debugging
+    crash_debug: bool,
 }
 
 impl RunnerState {
-    fn new() -> Self {
+    fn new(config: RunnerConfig) -> Self {
+        let watch_patterns = WatchMatcher::compile_all(&config.watch_patterns);
+        let log_format = LogFormat::compile(&config.log_format);
+        let log_file = open_log_file(&config);
+        let log_buffer = LogBuffer::new(config.log_buffer_lines);
+        let saved = SavedState::load();
         Self {
-            python_process: Arc::new(Mutex::new(None)),
-            is_running: Arc::new(Mutex::new(false)),
+            python_process: Arc::new(TrackedMutex::new("python_process", None)),
+            is_running: Arc::new(TrackedMutex::new("is_running", false)),
+            config: Arc::new(TrackedMutex::new("config", config)),
+            log_level: Arc::new(TrackedMutex::new("log_level", None)),
+            watch_patterns: Arc::new(TrackedMutex::new("watch_patterns", watch_patterns)),
+            log_format: Arc::new(TrackedMutex::new("log_format", log_format)),
+            circuit_breaker: Arc::new(TrackedMutex::new("circuit_breaker", CircuitBreaker::default())),
+            log_file: Arc::new(TrackedMutex::new("log_file", log_file)),
+            log_buffer: Arc::new(TrackedMutex::new("log_buffer", log_buffer)),
+            stderr_buffer: Arc::new(TrackedMutex::new("stderr_buffer", LogBuffer::new(STDERR_BUFFER_LINES))),
+            stats: Arc::new(SessionStats::default()),
+            metrics: Arc::new(TrackedMutex::new("metrics", None)),
+            health: Arc::new(TrackedMutex::new("health", Health::Stopped)),
+            restart: Arc::new(TrackedMutex::new("restart", RestartState::default())),
+            last_error: Arc::new(TrackedMutex::new("last_error", None)),
+            schedule: Arc::new(TrackedMutex::new("schedule", saved.schedule)),
+            last_crash: Arc::new(TrackedMutex::new("last_crash", None)),
+            startup_latencies: Arc::new(TrackedMutex::new("startup_latencies", StartupLatencies::default())),
+            operations: Arc::new(TrackedMutex::new("operations", Operations::default())),
+            overrides: Arc::new(TrackedMutex::new("overrides", None)),
+            reconnected: Arc::new(TrackedMutex::new("reconnected", None)),
+            crash_debug_armed: Arc::new(TrackedMutex::new("crash_debug_armed", false)),
+            stdin_recording: Arc::new(TrackedMutex::new("stdin_recording", None)),
         }
     }
+
+    // One entry per TrackedMutex field above
+    fn lock_stats(&self) -> Vec<lock_stats::LockStats> {
+        vec![
+            self.python_process.stats(),
+            self.is_running.stats(),
+            self.config.stats(),
+            self.log_level.stats(),
+            self.watch_patterns.stats(),
+            self.log_format.stats(),
+            self.circuit_breaker.stats(),
+            self.log_file.stats(),
+            self.log_buffer.stats(),
+            self.stderr_buffer.stats(),
+            self.metrics.stats(),
+            self.health.stats(),
+            self.restart.stats(),
+            self.last_error.stats(),
+            self.schedule.stats(),
+            self.last_crash.stats(),
+            self.startup_latencies.stats(),
+            self.operations.stats(),
+            self.overrides.stats(),
+            self.reconnected.stats(),
+            self.crash_debug_armed.stats(),
+            self.stdin_recording.stats(),
+        ]
+    }
+}
+
+// A log directory that can't be written now is retried by the first lines written.
+fn open_log_file(config: &RunnerConfig) -> Option<LogFile> {
+    paths::logs_dir().map(|dir| {
+        let path = dir.join("runner.log");
+        LogFile::open(path.clone(), config.log_max_bytes, config.log_max_files).unwrap_or_else(|e| {
+            error!("Failed to open runner log file: {}", e);
+            LogFile::closed(path, config.log_max_bytes, config.log_max_files)
+        })
+    })
 }
 
 // Tauri commands
 #[tauri::command]
-async fn start_python_runner(state: tauri::State<'_, RunnerState>) -> Result<String, String> {
+async fn start_python_runner(app: tauri::AppHandle, state: tauri::State<'_, RunnerState>) -> Result<String, String> {
+    // A normal start runs the saved config again
+    *state.overrides.lock().unwrap() = None;
+    begin_start(&app, &state, false).await
+}
+
+// A manual start ends any crash loop in progress and begins a new session.
+// `restart` is set when it replaces the runner, as `safe_restart` does.
+async fn begin_start(app: &tauri::AppHandle, state: &RunnerState, restart: bool) -> Result<String, String> {
+    supervisor::reset_restarts(state);
+    lifecycle::set_session_label(None);
+    start_runner(app, state, restart).await
+}
+
+// Spawn the runner, replacing any running process. Also used by the
+// supervisor for automatic restarts, which pass `restart` so the console is
+// continued or replaced as `console_per_restart` says.
+async fn start_runner(app: &tauri::AppHandle, state: &RunnerState, restart: bool) -> Result<String, String> {
+    wait_for_stop(app, state).await?;
     info!("Starting Python runner...");
     
-    let mut process_guard = state.python_process.lock().unwrap();
-    let mut running_guard = state.is_running.lock().unwrap();
+    // Run the optional bootstrap before taking the process locks; it can be slow.
+    let env_bootstrap = state.config.lock().unwrap().env_bootstrap.clone();
+    let mut bootstrap_vars = match env_bootstrap {
+        Some(bootstrap) => process::bootstrap_env(&bootstrap)
+            .map_err(|e| fail_start(state, e, &format!("env_bootstrap: {}", bootstrap)))?,
+        None => Default::default(),
+    };
     
-    // Kill existing process if running
-    if let Some(mut child) = process_guard.take() {
-        let _ = child.kill();
-        let _ = child.wait();
+    let overrides = state.overrides.lock().unwrap().clone();
+    if let Some(overrides) = &overrides {
+        bootstrap_vars.extend(overrides.env.clone());
+    }
+    let crash_debug = crash_debug::apply_env(state, &mut bootstrap_vars);
+    let started_config = state.config.lock().unwrap().clone();
+    // Kept unexpanded on the process so it compares equal to the stored config
+    let mut launch_config = started_config
+        .expanded()
+        .map_err(|e| fail_start(state, format!("Invalid config: {}", e), &started_config.interpreter()))?;
+    if let Some(script_args) = overrides.as_ref().and_then(|overrides| overrides.script_args.clone()) {
+        launch_config.script_args = script_args;
+    }
+    let capture_output = started_config.captures_output();
+    let (python_path, working_dir, kill_tree, merge_output, notify_on_pattern, metrics_interval, ready_marker, readiness_checks, keep_awake, heartbeat, cpu_affinity) = {
+        let config = &launch_config;
+        let python_path = config.interpreter();
+        let working_dir = paths::working_dir(app, config);
+        let metrics_interval = Some(config.metrics_interval()).filter(|_| config.metrics_enabled);
+        let heartbeat = HeartbeatCheck::from_config(config, &working_dir);
+        let readiness_checks = ReadinessChecks::from_config(config, &working_dir);
+        (
+            python_path,
+            working_dir,
+            config.kill_process_tree,
+            config.merge_output,
+            config.notify_on_pattern,
+            metrics_interval,
+            config.ready_marker(),
+            readiness_checks,
+            config.keep_awake,
+            heartbeat,
+            config.cpu_affinity.clone(),
+        )
+    };
+    let (program, args, interpreter_version) = match &launch_config.conda_env {
+        Some(env) => {
+            let conda = conda::find().ok_or_else(|| {
+                fail_start(state, format!("conda_env is set to '{}' but conda is not installed", env), &python_path)
+            })?;
+            let mut args = conda::run_args(env, &launch_config.script);
+            args.extend(launch_config.script_args.iter().cloned());
+            (conda.to_string_lossy().to_string(), args, None)
+        }
+        None => {
+            let python = interpreters::resolve_interpreter(&launch_config, &working_dir)
+                .map_err(|e| fail_start(state, e, &python_path))?;
+            let args = std::iter::once(launch_config.script.clone()).chain(launch_config.script_args.iter().cloned()).collect();
+            let version = interpreters::python_version(&python);
+            (python.to_string_lossy().to_string(), args, version)
+        }
+    };
+    let (program, args) = match &launch_config.launcher {
+        Some(wrapper) => launcher::wrap(wrapper, program, args).map_err(|e| fail_start(state, e, wrapper))?,
+        None => (program, args),
+    };
+    argsfile::remove();
+    let env_size = argsfile::env_size(bootstrap_vars.iter());
+    let (args, args_file) = argsfile::fit(&program, args, launch_config.script_args.len(), env_size)
+        .map_err(|e| fail_start(state, e, &program))?;
+    if let Some(path) = &args_file {
+        info!("Command line too long, passing script_args through {}", path);
     }
-    
     // Start new Python process
-    match Command::new("python")
-        .arg("main.py")
-        .current_dir("src")
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()
-    {
-        Ok(child) => {
-            *process_guard = Some(child);
+    let mut command = if launch_config.terminal {
+        info!("Starting the Python runner in a terminal window; its output won't be captured");
+        terminal::command(&program, &args, &working_dir).map_err(|e| fail_start(state, e, &program))?
+    } else if launch_config.elevated {
+        warn!("Starting the Python runner elevated; an elevation prompt will appear");
+        let _ = app.emit_all("runner-elevation-prompt", ());
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        elevation::command(&launch_config.elevation_command, &program, &args, &working_dir)
+    } else {
+        let mut command = Command::new(&program);
+        command.args(&args).current_dir(&working_dir);
+        command
+    };
+    command.envs(&bootstrap_vars).stdin(Stdio::piped());
+    if let Some(path) = &args_file {
+        command.env(argsfile::ARGS_FILE_ENV_VAR, path);
+    }
+    // Python block-buffers a piped stdout, which would hold lines back regardless of how we read
+    let unbuffered_set = bootstrap_vars.contains_key("PYTHONUNBUFFERED") || std::env::var_os("PYTHONUNBUFFERED").is_some();
+    if launch_config.line_buffered && !unbuffered_set {
+        command.env("PYTHONUNBUFFERED", "1");
+    }
+    let output_redirect = launch_config.output_redirect.as_ref().map(|path| working_dir.join(path));
+    if let Some(path) = &output_redirect {
+        let (stdout, stderr) = output::redirect(path).map_err(|e| fail_start(state, e, &program))?;
+        info!("Redirecting the Python runner's output to {}", path.display());
+        command.stdout(stdout).stderr(stderr);
+    } else if capture_output {
+        command.stdout(Stdio::piped()).stderr(Stdio::piped());
+    } else {
+        command.stdout(Stdio::inherit()).stderr(Stdio::inherit());
+    }
+    if let Some(heartbeat) = &heartbeat {
+        heartbeat::remove(&heartbeat.path);
+        command.env(heartbeat::HEARTBEAT_ENV_VAR, &heartbeat.path);
+    }
+    // Output from earlier runs stays in the buffer; remember where this one's starts
+    let stderr_start = state.stderr_buffer.lock().unwrap().next_seq();
+    // Never run alongside a runner left by the previous session
+    if let Some(pid) = survivor::stop(state, kill_tree) {
+        info!("Replaced the reconnected Python runner (PID {})", pid);
+    }
+    let spawn_attempts = launch_config.spawn_attempts.max(1);
+    let spawn_retry_delay = Duration::from_millis(launch_config.spawn_retry_delay_ms);
+    let mut spawn_errors = Vec::new();
+    let (mut process_guard, mut running_guard, spawned) = loop {
+        {
+            let mut process_guard = state.python_process.lock().unwrap();
+            let running_guard = state.is_running.lock().unwrap();
+
+            // Kill existing process if running
+            if let Some(mut process) = process_guard.take() {
+                let _ = process.kill(kill_tree);
+                SessionStats::increment(&state.stats.restarts);
+                lifecycle::record(LifecycleKind::Stop, Some(process.child.id()), None, "Replaced by a new start");
+                tauri::async_runtime::spawn(async move {
+                    process.join_readers(process::READER_JOIN_TIMEOUT).await;
+                    process.reap_in_background();
+                });
+            }
+
+            match process::spawn(&mut command) {
+                Ok(process) => break (process_guard, running_guard, Ok(process)),
+                Err(e) => {
+                    spawn_errors.push(format!("attempt {}: {}", spawn_errors.len() + 1, e));
+                    if spawn_errors.len() as u32 >= spawn_attempts {
+                        let error = if spawn_attempts == 1 { e.to_string() } else { spawn_errors.join("; ") };
+                        break (process_guard, running_guard, Err(error));
+                    }
+                    warn!(
+                        "Spawning the Python runner failed ({}/{}), retrying in {}ms: {}",
+                        spawn_errors.len(),
+                        spawn_attempts,
+                        spawn_retry_delay.as_millis(),
+                        e
+                    );
+                }
+            }
+        }
+        // Not a crash, so this stays out of the supervisor's backoff
+        tokio::time::sleep(spawn_retry_delay).await;
+    };
+    match spawned {
+        Ok(mut process) => {
+            let pid = process.child.id();
+            let command_line = process.command_line.clone();
+            if capture_output {
+                let options = OutputOptions {
+                    merge_output,
+                    line_buffered: started_config.line_buffered,
+                    throttle: started_config.output_max_lines_per_sec.map(|limit| Arc::new(Mutex::new(OutputThrottle::new(limit)))),
+                    carriage_return: started_config.carriage_return,
+                    watch_patterns: state.watch_patterns.lock().unwrap().clone(),
+                    notify_on_pattern,
+                    notify_limiter: Mutex::new(NotifyLimiter::default()),
+                    log_file: state.log_file.clone(),
+                    log_stdout_to_file: started_config.log_stdout_to_file,
+                    log_stderr_to_file: started_config.log_stderr_to_file,
+                    log_format: state.log_format.clone(),
+                    log_buffer: state.log_buffer.clone(),
+                    stderr_buffer: state.stderr_buffer.clone(),
+                    stats: state.stats.clone(),
+                    ready_marker,
+                    ready_marker_seen: process.ready_marker_seen.clone(),
+                };
+                output::announce_session(app, &process, restart, started_config.console_per_restart);
+                output::spawn_readers(app, &mut process, options);
+            } else if output_redirect.is_none() {
+                info!("capture_output is off; the runner's output is not read");
+            }
+            process.output_redirect = output_redirect;
+            if let Err(e) = process.set_keep_awake(keep_awake) {
+                warn!("Failed to prevent system sleep: {}", e);
+            }
+            if let Some(cores) = &cpu_affinity {
+                match affinity::validate(cores).and_then(|()| affinity::set(pid, cores).map_err(|e| e.to_string())) {
+                    Ok(()) => info!("Pinned Python runner to cores {:?}", cores),
+                    Err(e) => warn!("Failed to set CPU affinity: {}", e),
+                }
+            }
+            supervisor::watch(app.clone(), process.run_id);
+            if let Some(interval) = metrics_interval {
+                metrics::start_sampler(app.clone(), process.run_id, interval);
+            }
+            readiness::watch(app.clone(), process.run_id, readiness_checks);
+            if launch_config.fingerprint_on_start {
+                fingerprint::check_at_start(app.clone(), launch_config.clone(), working_dir.clone());
+            }
+            if let Some(heartbeat) = heartbeat {
+                process.heartbeat_file = Some(heartbeat.path.clone());
+                heartbeat::watch(app.clone(), process.run_id, heartbeat);
+            }
+            if let Some(check) = idle::IdleCheck::from_config(&started_config) {
+                idle::watch(app.clone(), process.run_id, check);
+            }
+            if let Some(secs) = started_config.max_runtime_secs {
+                max_runtime::watch(app.clone(), process.run_id, Duration::from_secs(secs), kill_tree);
+            }
+            process.started_config = Some(started_config);
+            process.variant = overrides.map(|overrides| overrides.variant);
+            process.stderr_start = stderr_start;
+            *state.metrics.lock().unwrap() = None;
+            *state.health.lock().unwrap() = Health::Starting;
+            SessionStats::increment(&state.stats.starts);
+            survivor::remember(&process);
+            if crash_debug {
+                crash_debug::begin(state, &mut process);
+            }
+            *process_guard = Some(process);
             *running_guard = true;
+            *state.log_level.lock().unwrap() = None;
+            let context = StartContext {
+                command_line,
+                working_dir: working_dir.to_string_lossy().to_string(),
+                interpreter_version,
+            };
+            lifecycle::record_start(pid, context.clone());
+            drop(running_guard);
+            drop(process_guard);
+            if crash_debug {
+                crash_debug::raise_log_level(app.clone());
+            }
+            let _ = app.emit_all(
+                "runner-started",
+                RunnerStartedEvent {
+                    pid,
+                    command_line: context.command_line,
+                    working_dir: context.working_dir,
+                    interpreter_version: context.interpreter_version,
+                    session_label: lifecycle::session_label(),
+                },
+            );
+            // Health is `Starting` until the readiness watch resolves
+            let _ = app.emit_all("runner-status", runner_status(state));
             info!("Python runner started successfully");
             Ok("Python runner started".to_string())
         }
-        Err(e) => {
-            error!("Failed to start Python runner: {}", e);
-            Err(format!("Failed to start Python runner: {}", e))
+        Err(e) => Err(fail_start(
+            state,
+            format!("Failed to start Python runner: {}", e),
+            &process::command_line(&command),
+        )),
+    }
+}
+
+// Log and record a start that failed before a process was running; returns
+// `message` for the caller to hand back.
+fn fail_start(state: &RunnerState, message: String, command: &str) -> String {
+    error!("{}", message);
+    record_last_error(state, &message, command, None);
+    lifecycle::record(LifecycleKind::StartFailed, None, None, message.clone());
+    message
+}
+
+#[derive(Clone, Serialize)]
+struct RunnerStartedEvent {
+    pid: u32,
+    command_line: String,
+    working_dir: String,
+    interpreter_version: Option<String>,
+    session_label: Option<String>,
+}
+
+// Hold a start until the stop in progress, if any, has finished with the old
+// process, giving up once that takes longer than any stop should.
+async fn wait_for_stop(app: &tauri::AppHandle, state: &RunnerState) -> Result<(), String> {
+    {
+        let mut operations = state.operations.lock().unwrap();
+        if !operations.stopping {
+            return Ok(());
+        }
+        operations.queued = Some(QueuedOperation::Start);
+    }
+    info!("Stop in progress, starting the Python runner once it finishes");
+    let _ = app.emit_all("runner-status", runner_status(state));
+
+    let grace = Duration::from_secs(state.config.lock().unwrap().stop_grace_secs);
+    let deadline = Instant::now() + grace + STOP_QUEUE_MARGIN;
+    while state.operations.lock().unwrap().stopping {
+        if Instant::now() >= deadline {
+            state.operations.lock().unwrap().queued = None;
+            let _ = app.emit_all("runner-status", runner_status(state));
+            return Err("Timed out waiting for the previous stop to finish".to_string());
         }
+        tokio::time::sleep(STOP_QUEUE_POLL).await;
     }
+    state.operations.lock().unwrap().queued = None;
+    Ok(())
+}
+
+#[tauri::command]
+async fn stop_python_runner(app: tauri::AppHandle, state: tauri::State<'_, RunnerState>) -> Result<String, String> {
+    let kill_tree = state.config.lock().unwrap().kill_process_tree;
+    stop_runner(&app, &state, kill_tree).await
 }
 
+// Stop the runner and every process it spawned, regardless of `kill_process_tree`.
 #[tauri::command]
-async fn stop_python_runner(state: tauri::State<'_, RunnerState>) -> Result<String, String> {
+async fn stop_runner_tree(app: tauri::AppHandle, state: tauri::State<'_, RunnerState>) -> Result<String, String> {
+    stop_runner(&app, &state, true).await
+}
+
+#[derive(Clone, Serialize)]
+struct ForceKilledEvent {
+    grace_secs: u64,
+    // None when `force_kill_message` is empty
+    message: Option<String>,
+}
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum StopStage {
+    SignalSent,
+    // Repeated every STOP_PROGRESS_INTERVAL during the grace period
+    Waiting,
+    // The grace period ran out; force-killing
+    Escalating,
+    Stopped,
+}
+
+#[derive(Clone, Serialize)]
+struct StopProgressEvent {
+    stage: StopStage,
+    pid: u32,
+    elapsed_ms: u64,
+    grace_secs: u64,
+}
+
+const STOP_PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+
+// How a stop went, for callers that care beyond the message
+pub(crate) enum Stopped {
+    Nothing,
+    Reconnected(u32),
+    Process(StopOutcome),
+}
+
+pub(crate) struct StopOutcome {
+    // Exited within the grace period after the stop signal
+    pub graceful: bool,
+    // None if a force-killed process couldn't be reaped right away
+    pub status: Option<std::process::ExitStatus>,
+    pub elapsed: Duration,
+}
+
+async fn stop_runner(app: &tauri::AppHandle, state: &RunnerState, kill_tree: bool) -> Result<String, String> {
+    match stop_runner_with_outcome(app, state, kill_tree, true).await? {
+        Stopped::Nothing => Ok("No runner process to stop".to_string()),
+        Stopped::Reconnected(pid) => Ok(format!("Reconnected runner (PID {}) stopped", pid)),
+        Stopped::Process(_) => Ok("Python runner stopped".to_string()),
+    }
+}
+
+// With `graceful` false the process is killed right away, without the stop
+// signal and grace period; everything after that is the same.
+pub(crate) async fn stop_runner_with_outcome(
+    app: &tauri::AppHandle,
+    state: &RunnerState,
+    kill_tree: bool,
+    graceful: bool,
+) -> Result<Stopped, String> {
     info!("Stopping Python runner...");
-    
-    let mut process_guard = state.python_process.lock().unwrap();
-    let mut running_guard = state.is_running.lock().unwrap();
-    
-    if let Some(mut child) = process_guard.take() {
-        match child.kill() {
-            Ok(_) => {
-                let _ = child.wait();
-                *running_guard = false;
-                info!("Python runner stopped successfully");
-                Ok("Python runner stopped".to_string())
+    let stop_started = Instant::now();
+
+    let (stop_signal, grace, force_kill_message) = {
+        let config = state.config.lock().unwrap();
+        (config.stop_signal, Duration::from_secs(config.stop_grace_secs), config.force_kill_message.clone())
+    };
+
+    supervisor::reset_restarts(state);
+
+    // Take the process out of the state up front so the (possibly long) grace
+    // period doesn't hold the locks.
+    let process = {
+        let mut process_guard = state.python_process.lock().unwrap();
+        let mut running_guard = state.is_running.lock().unwrap();
+        if let Some(process) = process_guard.as_ref().filter(|_| !graceful) {
+            // Without a signal first, make sure the PID wasn't reused by something else
+            process.verify_owned_process().map_err(|e| format!("Refusing to kill: {}", e))?;
+        }
+        *running_guard = false;
+        *state.metrics.lock().unwrap() = None;
+        *state.health.lock().unwrap() = Health::Stopped;
+        let process = process_guard.take();
+        if process.is_some() {
+            state.operations.lock().unwrap().stopping = true;
+        }
+        process
+    };
+    let mut process = match process {
+        Some(process) => process,
+        None => {
+            if let Some(pid) = survivor::stop(state, kill_tree) {
+                let _ = app.emit_all("runner-status", runner_status(state));
+                return Ok(Stopped::Reconnected(pid));
             }
-            Err(e) => {
-                error!("Failed to stop Python runner: {}", e);
-                Err(format!("Failed to stop Python runner: {}", e))
+            warn!("No Python runner process to stop");
+            return Ok(Stopped::Nothing);
+        }
+    };
+    let _ = app.emit_all("runner-status", runner_status(state));
+
+    let pid = process.child.id();
+    let progress = |stage| {
+        let _ = app.emit_all(
+            "runner-stopping-progress",
+            StopProgressEvent {
+                stage,
+                pid,
+                elapsed_ms: stop_started.elapsed().as_millis() as u64,
+                grace_secs: grace.as_secs(),
+            },
+        );
+    };
+    let signal_sent = if graceful { Some(process.send_stop_signal(stop_signal, kill_tree)) } else { None };
+    let exited = match signal_sent {
+        None => None,
+        Some(Ok(())) => {
+            progress(StopStage::SignalSent);
+            let deadline = Instant::now() + grace;
+            loop {
+                progress(StopStage::Waiting);
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                let exited = process.wait_timeout(remaining.min(STOP_PROGRESS_INTERVAL)).await.unwrap_or(None);
+                if exited.is_some() || remaining <= STOP_PROGRESS_INTERVAL {
+                    break exited;
+                }
             }
         }
+        Some(Err(e)) => {
+            warn!("Failed to send {:?} to Python runner: {}", stop_signal, e);
+            None
+        }
+    };
+
+    let result = match exited {
+        Some(status) => {
+            info!("Python runner exited gracefully: {}", status);
+            if kill_tree {
+                // Clean up any subprocesses that outlived the parent
+                let _ = process.kill_tree();
+            }
+            lifecycle::record(LifecycleKind::Stop, Some(pid), status.code(), "Stopped gracefully");
+            Ok(Some(status))
+        }
+        None if !graceful => {
+            warn!("Force killing Python runner");
+            let result = process.kill(kill_tree).map(|()| process.child.try_wait().ok().flatten());
+            if result.is_ok() {
+                lifecycle::record(LifecycleKind::Stop, Some(pid), None, "Force-killed on request");
+            }
+            result
+        }
+        None => {
+            warn!("Python runner did not exit within {}s, force killing", grace.as_secs());
+            progress(StopStage::Escalating);
+            let result = process.kill(kill_tree).map(|()| process.child.try_wait().ok().flatten());
+            if result.is_ok() {
+                notify_force_killed(app, grace.as_secs(), &force_kill_message);
+                lifecycle::record(
+                    LifecycleKind::Stop,
+                    Some(pid),
+                    None,
+                    format!("Force-killed after {}s grace period", grace.as_secs()),
+                );
+            }
+            result
+        }
+    };
+
+    process.join_readers(process::READER_JOIN_TIMEOUT).await;
+    if let Some(path) = &process.heartbeat_file {
+        heartbeat::remove(path);
+    }
+    process.reap_in_background();
+    state.operations.lock().unwrap().stopping = false;
+    progress(StopStage::Stopped);
+    let _ = app.emit_all("runner-status", runner_status(state));
+
+    match result {
+        Ok(status) => {
+            SessionStats::increment(&state.stats.stops);
+            info!("Python runner stopped successfully");
+            Ok(Stopped::Process(StopOutcome {
+                graceful: exited.is_some(),
+                status,
+                elapsed: stop_started.elapsed(),
+            }))
+        }
+        Err(e) => {
+            error!("Failed to stop Python runner: {}", e);
+            Err(format!("Failed to stop Python runner: {}", e))
+        }
+    }
+}
+
+// Tell the user a stop had to escalate to a force kill, so a script that hangs
+// on shutdown doesn't go unnoticed.
+fn notify_force_killed(app: &tauri::AppHandle, grace_secs: u64, template: &str) {
+    let message = Some(template.trim())
+        .filter(|template| !template.is_empty())
+        .map(|template| template.replace("{grace}", &grace_secs.to_string()));
+
+    if let Some(message) = &message {
+        if let Err(e) = notify::alert(app, "Oriphim Runner: force-killed", message) {
+            warn!("{}", e);
+        }
+    }
+    let _ = app.emit_all("runner-force-killed", ForceKilledEvent { grace_secs, message });
+}
+
+#[derive(Clone, Serialize)]
+struct RestartFailedEvent {
+    error: String,
+}
+
+// Restart and wait for the new process to become ready. Unlike a plain
+// restart, a process that never becomes ready is reported as a failure and
+// announced with a `runner-restart-failed` event.
+#[tauri::command]
+async fn safe_restart(app: tauri::AppHandle, state: tauri::State<'_, RunnerState>) -> Result<String, String> {
+    info!("Safe restart requested");
+    let (kill_tree, ready_timeout) = {
+        let config = state.config.lock().unwrap();
+        (config.kill_process_tree, Duration::from_secs(config.ready_timeout_secs))
+    };
+
+    stop_runner(&app, &state, kill_tree).await?;
+    // Keeps a `launch_with_overrides` variant running as that variant
+    let result = match begin_start(&app, &state, true).await {
+        // The readiness watch gives up on its own after `ready_timeout`
+        Ok(_) => match readiness::wait_for_health(&state, ready_timeout + Duration::from_secs(1)).await {
+            Health::Ready => Ok(()),
+            Health::Starting => Err("Readiness check did not finish in time".to_string()),
+            Health::Stopped | Health::Failed => {
+                Err("Python runner did not become healthy after restart".to_string())
+            }
+        },
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(()) => {
+            info!("Safe restart completed");
+            Ok("Python runner restarted and ready".to_string())
+        }
+        Err(error) => {
+            error!("Safe restart failed: {}", error);
+            *state.health.lock().unwrap() = Health::Failed;
+            let _ = app.emit_all("runner-restart-failed", RestartFailedEvent { error: error.clone() });
+            let _ = app.emit_all("runner-status", runner_status(&state));
+            Err(error)
+        }
+    }
+}
+
+// Keep a pasteable report of a failure: what happened, what was run and,
+// once the process has produced output, the end of the stderr it printed
+// since `stderr_start`. Debug builds add the contended locks.
+fn record_last_error(state: &RunnerState, summary: &str, command_line: &str, stderr_start: Option<u64>) {
+    let mut report = format!(
+        "{}\nCommand: {}\nMode: {}",
+        summary,
+        command_line,
+        if paths::is_packaged() { "packaged" } else { "dev" }
+    );
+    if let Some(start) = stderr_start {
+        let stderr_buffer = state.stderr_buffer.lock().unwrap();
+        let tail = stderr_buffer.stderr_since(start, ERROR_REPORT_STDERR_LINES);
+        if !tail.is_empty() {
+            report.push_str(&format!("\n--- stderr (last {} lines) ---\n{}", tail.len(), tail.join("\n")));
+        }
+    }
+    if cfg!(debug_assertions) {
+        report.push('\n');
+        report.push_str(&lock_stats::report(state));
+    }
+    *state.last_error.lock().unwrap() = Some(report);
+}
+
+#[tauri::command]
+async fn copy_last_error(app: tauri::AppHandle, state: tauri::State<'_, RunnerState>) -> Result<String, String> {
+    let report = state.last_error.lock().unwrap().clone().ok_or("No error has been recorded")?;
+    app.clipboard_manager()
+        .write_text(report)
+        .map_err(|e| format!("Failed to copy error to clipboard: {}", e))?;
+    Ok("Last error copied to clipboard".to_string())
+}
+
+// The absolute directory the next start will run main.py from.
+#[tauri::command]
+async fn get_effective_working_dir(app: tauri::AppHandle, state: tauri::State<'_, RunnerState>) -> Result<String, String> {
+    let config = state.config.lock().unwrap().expanded()?;
+    Ok(paths::working_dir(&app, &config).display().to_string())
+}
+
+// True for an installed bundle, false when running from the source tree.
+#[tauri::command]
+async fn is_packaged() -> Result<bool, String> {
+    Ok(paths::is_packaged())
+}
+
+// Re-read config.toml. Settings used while spawning apply from the next start.
+// An invalid file is reported with `config-error` and the current settings are
+// kept, since they are already known to be good.
+#[tauri::command]
+async fn reload_config(app: tauri::AppHandle, state: tauri::State<'_, RunnerState>) -> Result<String, String> {
+    let config = RunnerConfig::load().map_err(|error| {
+        let message = error.to_string();
+        let _ = app.emit_all("config-error", error);
+        message
+    })?;
+    apply_config(&app, &state, config);
+    info!("Config reloaded");
+    Ok("Config reloaded".to_string())
+}
+
+#[derive(Clone, Serialize)]
+struct RequiresRestartEvent {
+    fields: Vec<&'static str>,
+}
+
+// Make `config` the current config, applying what can be applied to a running
+// process. Returns the changed fields that wait for a restart, which are also
+// announced with `config-requires-restart`.
+fn apply_config(app: &tauri::AppHandle, state: &RunnerState, config: RunnerConfig) -> Vec<&'static str> {
+    *state.watch_patterns.lock().unwrap() = WatchMatcher::compile_all(&config.watch_patterns);
+    *state.log_format.lock().unwrap() = LogFormat::compile(&config.log_format);
+    state.log_buffer.lock().unwrap().set_cap(config.log_buffer_lines);
+    if let Some(log_file) = state.log_file.lock().unwrap().as_mut() {
+        log_file.set_limits(config.log_max_bytes, config.log_max_files);
+    }
+    system_log::configure(&config);
+    let keep_awake = config.keep_awake;
+    *state.config.lock().unwrap() = config;
+    if let Err(e) = apply_keep_awake(state, keep_awake) {
+        warn!("{}", e);
+    }
+
+    let pending = pending_restart_fields(state);
+    if !pending.is_empty() {
+        info!("Config changes apply after a restart: {}", pending.join(", "));
+        let _ = app.emit_all("config-requires-restart", RequiresRestartEvent { fields: pending.clone() });
+    }
+    pending
+}
+
+// Restart-required settings changed since the running process started.
+fn pending_restart_fields(state: &RunnerState) -> Vec<&'static str> {
+    let process_guard = state.python_process.lock().unwrap();
+    match process_guard.as_ref().and_then(|process| process.started_config.as_ref()) {
+        Some(started) => started.restart_required_changes(&state.config.lock().unwrap()),
+        None => Vec::new(),
+    }
+}
+
+#[derive(Clone, Serialize)]
+struct ConfigView {
+    config: RunnerConfig,
+    // Fields that only take effect when the runner starts
+    restart_required_fields: &'static [&'static str],
+    // Of those, the ones changed since the running process started
+    pending_restart: Vec<&'static str>,
+}
+
+#[tauri::command]
+async fn get_config(state: tauri::State<'_, RunnerState>) -> Result<ConfigView, String> {
+    let pending_restart = pending_restart_fields(&state);
+    Ok(ConfigView {
+        config: state.config.lock().unwrap().clone(),
+        restart_required_fields: config::RESTART_REQUIRED_FIELDS,
+        pending_restart,
+    })
+}
+
+#[derive(Clone, Serialize)]
+struct ConfigResetEvent {
+    // Where the removed files were copied, if there were any
+    backup_dir: Option<String>,
+}
+
+// Stop the runner and go back to a fresh install: config.toml, state.json and
+// window.json are copied to ~/.oriphim/backups/reset-<time> and removed, and
+// the defaults are loaded. `confirm` must be true.
+#[tauri::command]
+async fn reset_to_defaults(
+    confirm: bool,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, RunnerState>,
+) -> Result<String, String> {
+    if !confirm {
+        return Err("Resetting deletes all settings and saved state; pass confirm to proceed".to_string());
+    }
+
+    if *state.is_running.lock().unwrap() {
+        let kill_tree = state.config.lock().unwrap().kill_process_tree;
+        stop_runner(&app, &state, kill_tree).await?;
+    }
+
+    let backup_dir = paths::oriphim_dir()
+        .ok_or("Could not find home directory")?
+        .join("backups")
+        .join(format!("reset-{}", chrono::Local::now().format("%Y%m%d-%H%M%S")));
+    let files = [paths::config_file(), paths::state_file(), paths::window_state_file()];
+    let mut backed_up = false;
+    for file in files.into_iter().flatten().filter(|file| file.is_file()) {
+        std::fs::create_dir_all(&backup_dir)
+            .map_err(|e| format!("Failed to create {}: {}", backup_dir.display(), e))?;
+        let name = file.file_name().unwrap_or_default();
+        std::fs::copy(&file, backup_dir.join(name))
+            .map_err(|e| format!("Failed to back up {}: {}", file.display(), e))?;
+        std::fs::remove_file(&file).map_err(|e| format!("Failed to remove {}: {}", file.display(), e))?;
+        backed_up = true;
+    }
+
+    apply_config(&app, &state, RunnerConfig::default());
+    {
+        let mut schedule = state.schedule.lock().unwrap();
+        *schedule = Schedule::default();
+        schedule::update_tooltip(&app, &schedule);
+    }
+
+    let backup_dir = Some(backup_dir.to_string_lossy().to_string()).filter(|_| backed_up);
+    match &backup_dir {
+        Some(dir) => info!("Reset to defaults, previous settings backed up to {}", dir),
+        None => info!("Reset to defaults, there were no settings to back up"),
+    }
+    let _ = app.emit_all("config-reset", ConfigResetEvent { backup_dir: backup_dir.clone() });
+    let _ = app.emit_all("runner-status", runner_status(&state));
+    Ok(match backup_dir {
+        Some(dir) => format!("Reset to defaults, backup saved to {}", dir),
+        None => "Reset to defaults".to_string(),
+    })
+}
+
+// Validate, save and apply a whole config from the settings UI. Changes to
+// restart-required fields are kept for the next start and reported with
+// `config-requires-restart`.
+#[tauri::command]
+async fn set_config(
+    config: RunnerConfig,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, RunnerState>,
+) -> Result<ConfigView, String> {
+    check_config(&config)?;
+    config.save()?;
+    let pending_restart = apply_config(&app, &state, config.clone());
+    info!("Config updated");
+    Ok(ConfigView {
+        config,
+        restart_required_fields: config::RESTART_REQUIRED_FIELDS,
+        pending_restart,
+    })
+}
+
+// Fail with every validation error in `config`; warnings don't count.
+fn check_config(config: &RunnerConfig) -> Result<(), String> {
+    let errors: Vec<String> = config
+        .validate()
+        .into_iter()
+        .filter(|issue| issue.severity == Severity::Error)
+        .map(|issue| format!("{}: {}", issue.field, issue.message))
+        .collect();
+    if !errors.is_empty() {
+        return Err(format!("Invalid config: {}", errors.join("; ")));
+    }
+    Ok(())
+}
+
+#[derive(Clone, Serialize)]
+struct ScriptSwitchedEvent {
+    previous: String,
+    script: String,
+}
+
+// Run another script from the same window: save `path` as `script`, then stop
+// the current process and start the new one. Its output carries on in the same
+// log and console after a separator line.
+#[tauri::command]
+async fn switch_script(path: String, app: tauri::AppHandle, state: tauri::State<'_, RunnerState>) -> Result<String, String> {
+    let path = path.trim().to_string();
+    let mut config = state.config.lock().unwrap().clone();
+    let previous = std::mem::replace(&mut config.script, path.clone());
+    check_config(&config)?;
+    let expanded = config.expanded()?;
+    let script = paths::working_dir(&app, &expanded).join(&expanded.script);
+    if !script.is_file() {
+        return Err(format!("Script {} does not exist", script.display()));
+    }
+
+    config.save()?;
+    let kill_tree = config.kill_process_tree;
+    apply_config(&app, &state, config);
+    let separator = format!("=== Switched script: {} -> {} ===", previous, path);
+    output::write_log_file(&app, &state.log_file, &separator);
+    info!("{}", separator);
+    let _ = app.emit_all("script-switched", ScriptSwitchedEvent { previous, script: path.clone() });
+
+    stop_runner(&app, &state, kill_tree).await?;
+    start_python_runner(app.clone(), state).await?;
+    Ok(format!("Switched to {}", path))
+}
+
+// Write the current config to `path` as TOML for sharing, with secrets redacted.
+#[tauri::command]
+async fn export_config(path: String, state: tauri::State<'_, RunnerState>) -> Result<String, String> {
+    let config = state.config.lock().unwrap().redacted();
+    let contents = toml::to_string_pretty(&config).map_err(|e| format!("Failed to serialize config: {}", e))?;
+    std::fs::write(&path, contents).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    let redacted = config.redacted_fields();
+    if redacted.is_empty() {
+        info!("Exported config to {}", path);
     } else {
-        warn!("No Python runner process to stop");
-        Ok("No runner process to stop".to_string())
+        info!("Exported config to {} (redacted secrets in {})", path, redacted.join(", "));
+    }
+    Ok(path)
+}
+
+// Load a config exported with `export_config` (or any config.toml), then
+// validate, save and apply it like `set_config`. Redacted values must be
+// filled in first.
+#[tauri::command]
+async fn import_config(
+    path: String,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, RunnerState>,
+) -> Result<ConfigView, String> {
+    let contents = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let config: RunnerConfig = toml::from_str(&contents).map_err(|e| format!("Invalid config in {}: {}", path, e))?;
+    let redacted = config.redacted_fields();
+    if !redacted.is_empty() {
+        return Err(format!(
+            "Replace the {} placeholders in {} before importing",
+            config::REDACTED,
+            redacted.join(", ")
+        ));
+    }
+    info!("Importing config from {}", path);
+    set_config(config, app, state).await
+}
+
+#[tauri::command]
+async fn get_runner_status(state: tauri::State<'_, RunnerState>) -> Result<RunnerStatus, String> {
+    let mut status = runner_status(&state);
+    if status.running {
+        let owned = state.python_process.lock().unwrap().as_ref().map(ManagedProcess::verify_owned_process);
+        if let Some(Err(e)) = owned {
+            warn!("Python runner PID is stale: {}", e);
+            status.running = false;
+        }
     }
+    Ok(status)
 }
 
+// Kill the runner immediately, skipping the stop signal and grace period, then
+// clean up like any stop. Refuses if the PID no longer belongs to our process.
 #[tauri::command]
-async fn get_runner_status(state: tauri::State<'_, RunnerState>) -> Result<bool, String> {
+async fn force_kill(app: tauri::AppHandle, state: tauri::State<'_, RunnerState>) -> Result<String, String> {
+    let kill_tree = state.config.lock().unwrap().kill_process_tree;
+    match stop_runner_with_outcome(&app, &state, kill_tree, false).await? {
+        Stopped::Nothing => Err("No runner process to kill".to_string()),
+        Stopped::Reconnected(pid) => Ok(format!("Reconnected runner (PID {}) killed", pid)),
+        Stopped::Process(_) => Ok("Python runner killed".to_string()),
+    }
+}
+
+fn runner_status(state: &RunnerState) -> RunnerStatus {
+    let process_guard = state.python_process.lock().unwrap();
     let running_guard = state.is_running.lock().unwrap();
-    Ok(*running_guard)
+    let operations = state.operations.lock().unwrap();
+    RunnerStatus {
+        running: *running_guard,
+        log_level: state.log_level.lock().unwrap().clone(),
+        process_group_id: process_guard.as_ref().and_then(ManagedProcess::process_group_id),
+        health: *state.health.lock().unwrap(),
+        session_label: lifecycle::session_label(),
+        stopping: operations.stopping,
+        queued: operations.queued,
+        started_at: process_guard
+            .as_ref()
+            .map(|process| process.recorded_start_time().unwrap_or(process.spawned_at)),
+        line_buffered: process_guard
+            .as_ref()
+            .and_then(|process| process.started_config.as_ref())
+            .map(|config| config.line_buffered),
+        circuit_breaker_tripped: state.circuit_breaker.lock().unwrap().is_tripped(),
+        variant: process_guard.as_ref().and_then(|process| process.variant.clone()),
+        notifications_snoozed_secs: notify::snooze_remaining().map(|remaining| remaining.as_secs()),
+        reconnected_pid: survivor::alive_pid(state),
+        crash_debug_armed: *state.crash_debug_armed.lock().unwrap(),
+        crash_debug: process_guard.as_ref().is_some_and(|process| process.crash_debug),
+    }
+}
+
+// How far the OS start time may be from our spawn time: sysinfo reports whole
+// seconds, and Linux derives it from the boot time, which drifts slightly.
+const START_TIME_TOLERANCE_SECS: i64 = 2;
+
+#[derive(Serialize)]
+struct ProcessStartTime {
+    pid: u32,
+    // Creation time the OS reports now
+    os_start_time: chrono::DateTime<chrono::Local>,
+    // When we spawned it
+    spawned_at: chrono::DateTime<chrono::Local>,
+    // False if the OS time moved since spawn or is far from our spawn time,
+    // meaning the PID may belong to another process
+    matches: bool,
 }
 
+// OS-reported creation time of the Python process, checked against our own records.
 #[tauri::command]
-async fn open_logs_folder() -> Result<String, String> {
-    let logs_path = dirs::home_dir()
-        .ok_or("Could not find home directory")?
-        .join(".oriphim")
-        .join("logs");
+async fn get_process_start_time(state: tauri::State<'_, RunnerState>) -> Result<ProcessStartTime, String> {
+    let process_guard = state.python_process.lock().unwrap();
+    let process = process_guard.as_ref().ok_or("Python runner is not running")?;
+    let pid = process.child.id();
+    let os_start_time = process
+        .os_start_time()
+        .ok_or_else(|| format!("Could not read the start time of process {}", pid))?;
+    let drift = (os_start_time - process.spawned_at).num_seconds().abs();
+    let changed = process.recorded_start_time().is_some_and(|recorded| recorded != os_start_time);
+    let matches = !changed && drift <= START_TIME_TOLERANCE_SECS;
+    if !matches {
+        warn!(
+            "Process {} started at {} by the OS but was spawned at {}; the PID may have been reused",
+            pid, os_start_time, process.spawned_at
+        );
+    }
+    Ok(ProcessStartTime {
+        pid,
+        os_start_time,
+        spawned_at: process.spawned_at,
+        matches,
+    })
+}
+
+#[derive(Serialize)]
+struct ProcessEnvironment {
+    pid: u32,
+    // Secret-looking values are replaced with `<redacted>`
+    variables: BTreeMap<String, String>,
+    redacted: Vec<String>,
+}
+
+// The environment the running process was spawned with, for "why can't my
+// script find X". An elevated process may have had its environment reset by
+// the elevation tool.
+#[tauri::command]
+async fn get_process_environment(state: tauri::State<'_, RunnerState>) -> Result<ProcessEnvironment, String> {
+    let process_guard = state.python_process.lock().unwrap();
+    let process = process_guard.as_ref().ok_or("Python runner is not running")?;
+    let mut redacted = Vec::new();
+    let variables = process
+        .environment
+        .iter()
+        .map(|(name, value)| {
+            if config::is_secret_name(name) {
+                redacted.push(name.clone());
+                (name.clone(), config::REDACTED.to_string())
+            } else {
+                (name.clone(), value.clone())
+            }
+        })
+        .collect();
+    Ok(ProcessEnvironment {
+        pid: process.child.id(),
+        variables,
+        redacted,
+    })
+}
+
+#[derive(Serialize)]
+struct ChildStateReport {
+    // None when no process is stored
+    pid: Option<u32>,
+    state: Option<process::ChildState>,
+}
+
+// Whether the stored child is running or a zombie. A zombie that persists past
+// the supervisor's next poll means a reaping bug.
+#[tauri::command]
+async fn get_child_state(state: tauri::State<'_, RunnerState>) -> Result<ChildStateReport, String> {
+    let process_guard = state.python_process.lock().unwrap();
+    let process = match process_guard.as_ref() {
+        Some(process) => process,
+        None => return Ok(ChildStateReport { pid: None, state: None }),
+    };
+    let child_state = process.child_state();
+    if child_state == process::ChildState::Zombie {
+        debug!("Python runner (PID {}) has exited and awaits reaping", process.child.id());
+    }
+    Ok(ChildStateReport {
+        pid: Some(process.child.id()),
+        state: Some(child_state),
+    })
+}
+
+#[tauri::command]
+async fn get_session_stats(state: tauri::State<'_, RunnerState>) -> Result<SessionStatsSnapshot, String> {
+    Ok(state.stats.snapshot())
+}
+
+// Latest resource usage of the Python process, or None while it isn't running.
+// Samples on demand when the background sampler is disabled or hasn't run yet.
+#[tauri::command]
+async fn get_process_metrics(state: tauri::State<'_, RunnerState>) -> Result<Option<ProcessMetrics>, String> {
+    let pid = match state.python_process.lock().unwrap().as_ref() {
+        Some(process) => process.child.id(),
+        None => return Ok(None),
+    };
+    if let Some(metrics) = state.metrics.lock().unwrap().clone() {
+        if metrics.pid == pid {
+            return Ok(Some(metrics));
+        }
+    }
+    Ok(metrics::sample(&mut sysinfo::System::new(), pid))
+}
+
+#[tauri::command]
+async fn set_runner_log_level(level: String, state: tauri::State<'_, RunnerState>) -> Result<String, String> {
+    let level = level.trim().to_uppercase();
+    if !LOG_LEVELS.contains(&level.as_str()) {
+        return Err(format!("Invalid log level '{}', expected one of: {}", level, LOG_LEVELS.join(", ")));
+    }
     
+    let command = format!("set-level {}\n", level).into_bytes();
+    write_stdin(&state, command.clone())
+        .await
+        .map_err(|e| format!("Failed to send log level to Python runner: {}", e))?;
+    stdin_recording::record(&state, &command);
+    
+    info!("Python runner log level set to {}", level);
+    *state.log_level.lock().unwrap() = Some(level.clone());
+    Ok(format!("Log level set to {}", level))
+}
+
+// Write raw bytes to the Python process's stdin without adding a newline, e.g.
+// to feed it a document in chunks. A full pipe blocks until the script reads,
+// so the write runs off the async runtime with stdin checked out of the process.
+#[tauri::command]
+async fn send_stdin_bytes(data: Vec<u8>, state: tauri::State<'_, RunnerState>) -> Result<String, String> {
+    write_stdin(&state, data.clone()).await?;
+    stdin_recording::record(&state, &data);
+    Ok(format!("Sent {} bytes", data.len()))
+}
+
+async fn write_stdin(state: &RunnerState, data: Vec<u8>) -> Result<(), String> {
+    let (run_id, mut stdin) = {
+        let mut process_guard = state.python_process.lock().unwrap();
+        let process = process_guard.as_mut().ok_or("Python runner is not running")?;
+        if process.stdin_closed {
+            return Err("Python runner stdin has been closed".to_string());
+        }
+        let stdin = process.child.stdin.take().ok_or("Python runner stdin is busy with another write")?;
+        (process.run_id, stdin)
+    };
+
+    let len = data.len();
+    let (stdin, result) = tauri::async_runtime::spawn_blocking(move || {
+        use std::io::Write;
+        let result = stdin.write_all(&data).and_then(|()| stdin.flush());
+        (stdin, result)
+    })
+    .await
+    .map_err(|e| format!("Failed to write to Python runner stdin: {}", e))?;
+
+    // Hand stdin back unless the pipe broke, the process was replaced, or
+    // close_stdin was called while the write was in progress
+    let broken = matches!(&result, Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe);
+    if let Some(process) = state.python_process.lock().unwrap().as_mut() {
+        if process.run_id == run_id && !process.stdin_closed && !broken {
+            process.child.stdin = Some(stdin);
+        }
+    }
+    result.map_err(|e| format!("Failed to write to Python runner stdin: {}", e))?;
+    debug!("Wrote {} bytes to Python runner stdin", len);
+    Ok(())
+}
+
+// Close the Python process's stdin so it reads EOF.
+#[tauri::command]
+async fn close_stdin(state: tauri::State<'_, RunnerState>) -> Result<String, String> {
+    let mut process_guard = state.python_process.lock().unwrap();
+    let process = process_guard.as_mut().ok_or("Python runner is not running")?;
+    if !process.close_stdin() {
+        return Ok("Python runner stdin was already closed".to_string());
+    }
+    info!("Closed Python runner stdin");
+    Ok("Python runner stdin closed".to_string())
+}
+
+// Choose the interpreter for future starts, or None to go back to `python` on PATH.
+// Saved to config.toml; a running process keeps its current interpreter.
+#[tauri::command]
+async fn set_python_path(path: Option<String>, state: tauri::State<'_, RunnerState>) -> Result<String, String> {
+    let path = path.map(|path| path.trim().to_string()).filter(|path| !path.is_empty());
+    if let Some(path) = &path {
+        let version = interpreters::python_version(Path::new(path))
+            .ok_or_else(|| format!("{} is not a working Python interpreter", path))?;
+        info!("Using Python {} at {}", version, path);
+    }
+
+    let mut config = state.config.lock().unwrap();
+    let previous = std::mem::replace(&mut config.python_path, path.clone());
+    if let Err(e) = config.save() {
+        config.python_path = previous;
+        return Err(e);
+    }
+    Ok(match path {
+        Some(path) => format!("Python interpreter set to {}", path),
+        None => "Python interpreter reset to default".to_string(),
+    })
+}
+
+// Pin the running process to `cores`, or let it use every core again with
+// None. Lasts until the process exits; `cpu_affinity` applies from each start.
+#[tauri::command]
+async fn set_cpu_affinity(cores: Option<Vec<usize>>, state: tauri::State<'_, RunnerState>) -> Result<String, String> {
+    let cores = match cores {
+        Some(cores) => {
+            affinity::validate(&cores)?;
+            cores
+        }
+        None => (0..affinity::core_count()).collect(),
+    };
+    let pid = {
+        let process_guard = state.python_process.lock().unwrap();
+        let process = process_guard.as_ref().ok_or("Python runner is not running")?;
+        process.verify_owned_process()?;
+        process.child.id()
+    };
+    affinity::set(pid, &cores).map_err(|e| format!("Failed to set CPU affinity: {}", e))?;
+    info!("Pinned Python runner to cores {:?}", cores);
+    Ok(format!("Python runner pinned to {} cores", cores.len()))
+}
+
+// Turn `keep_awake` on or off, saving it to config.toml and applying it to the
+// running process straight away.
+#[tauri::command]
+async fn set_keep_awake(enabled: bool, state: tauri::State<'_, RunnerState>) -> Result<String, String> {
+    {
+        let mut config = state.config.lock().unwrap();
+        let previous = std::mem::replace(&mut config.keep_awake, enabled);
+        if let Err(e) = config.save() {
+            config.keep_awake = previous;
+            return Err(e);
+        }
+    }
+    apply_keep_awake(&state, enabled)?;
+    Ok(if enabled { "Keeping the system awake while running" } else { "System sleep allowed" }.to_string())
+}
+
+// Start or stop the running process's sleep inhibitor. Its helper is spawned
+// or killed outside the process lock, which is only held to swap it in.
+fn apply_keep_awake(state: &RunnerState, enabled: bool) -> Result<(), String> {
+    let pid = match state.python_process.lock().unwrap().as_ref() {
+        Some(process) if process.keeps_awake() != enabled => process.child.id(),
+        _ => return Ok(()),
+    };
+    let inhibitor = if enabled {
+        Some(SleepInhibitor::acquire(pid).map_err(|e| format!("Failed to prevent system sleep: {}", e))?)
+    } else {
+        None
+    };
+    let mut process_guard = state.python_process.lock().unwrap();
+    let previous = match process_guard.as_mut() {
+        Some(process) if process.child.id() == pid => {
+            if enabled {
+                info!("Preventing system sleep while the Python runner is active");
+            } else {
+                info!("Allowing system sleep again");
+            }
+            process.replace_sleep_inhibitor(inhibitor)
+        }
+        // Replaced in the meantime; the new process took its own on start
+        _ => inhibitor,
+    };
+    drop(process_guard);
+    drop(previous);
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_process_group_id(state: tauri::State<'_, RunnerState>) -> Result<Option<u32>, String> {
+    let process_guard = state.python_process.lock().unwrap();
+    Ok(process_guard.as_ref().and_then(ManagedProcess::process_group_id))
+}
+
+#[tauri::command]
+async fn force_log_rotate(state: tauri::State<'_, RunnerState>) -> Result<String, String> {
+    let mut log_guard = state.log_file.lock().unwrap();
+    let log_file = log_guard.as_mut().ok_or("Runner log file is not open")?;
+    log_file
+        .rotate()
+        .map_err(|e| format!("Failed to rotate runner log: {}", e))?;
+    info!("Rotated {}", log_file.path().display());
+    Ok("Runner log rotated".to_string())
+}
+
+// Tag the current session, e.g. "reproduced the bug". The label is written to
+// the runner log, shown in the status and attached to lifecycle events until
+// the next manual start.
+#[tauri::command]
+async fn set_session_label(
+    label: Option<String>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, RunnerState>,
+) -> Result<String, String> {
+    let label = label.map(|label| label.trim().to_string()).filter(|label| !label.is_empty());
+    lifecycle::set_session_label(label.clone());
+
+    let header = match &label {
+        Some(label) => format!("=== Session label: {} ===", label),
+        None => "=== Session label cleared ===".to_string(),
+    };
+    output::write_log_file(&app, &state.log_file, &header);
+    info!("{}", header);
+    let _ = app.emit_all("runner-status", runner_status(&state));
+    Ok(match label {
+        Some(label) => format!("Session labelled '{}'", label),
+        None => "Session label cleared".to_string(),
+    })
+}
+
+// The most recent `lines` stderr lines (at most STDERR_BUFFER_LINES), oldest first.
+#[tauri::command]
+async fn get_recent_stderr(lines: usize, state: tauri::State<'_, RunnerState>) -> Result<Vec<LogLine>, String> {
+    Ok(state.stderr_buffer.lock().unwrap().tail(lines))
+}
+
+#[derive(Serialize)]
+struct RecentLogs {
+    // False when the current run doesn't capture output, so `lines` stays empty
+    capturing: bool,
+    // The file output goes to instead, with `output_redirect`
+    redirected_to: Option<String>,
+    lines: Vec<LogLine>,
+}
+
+// The most recent `lines` output lines from both streams, oldest first.
+#[tauri::command]
+async fn get_recent_logs(lines: usize, state: tauri::State<'_, RunnerState>) -> Result<RecentLogs, String> {
+    let (capturing, redirected_to) = {
+        let process_guard = state.python_process.lock().unwrap();
+        match process_guard.as_ref().and_then(|process| Some((process, process.started_config.as_ref()?))) {
+            Some((process, config)) => (config.captures_output(), process.output_redirect.clone()),
+            None => {
+                let config = state.config.lock().unwrap();
+                (config.captures_output(), config.output_redirect.clone())
+            }
+        }
+    };
+    let redirected_to = redirected_to.map(|path| path.display().to_string());
+    if !capturing {
+        return Ok(RecentLogs { capturing, redirected_to, lines: Vec::new() });
+    }
+    Ok(RecentLogs {
+        capturing,
+        redirected_to,
+        lines: state.log_buffer.lock().unwrap().tail(lines),
+    })
+}
+
+// Resize the in-memory output buffer until the config is next loaded,
+// dropping the oldest lines if it shrinks.
+#[tauri::command]
+async fn set_log_buffer_cap(lines: usize, state: tauri::State<'_, RunnerState>) -> Result<String, String> {
+    let (cap, dropped) = state.log_buffer.lock().unwrap().set_cap(lines);
+    info!("Log buffer cap set to {} lines ({} dropped)", cap, dropped);
+    Ok(format!("Log buffer now keeps {} lines", cap))
+}
+
+// How long flush_logs waits for the output readers to go quiet
+const READER_DRAIN_IDLE: Duration = Duration::from_millis(100);
+const READER_DRAIN_TIMEOUT: Duration = Duration::from_secs(1);
+
+// Let the reader threads finish handling lines they've already read off the pipes,
+// then flush and fsync the runner log. A runner that never stops printing is cut
+// off after READER_DRAIN_TIMEOUT.
+async fn flush_log_file(state: &RunnerState) -> Result<(), String> {
+    let handled = || {
+        state.stats.stdout_lines.load(Ordering::Relaxed) + state.stats.stderr_lines.load(Ordering::Relaxed)
+    };
+    if *state.is_running.lock().unwrap() {
+        let deadline = Instant::now() + READER_DRAIN_TIMEOUT;
+        let mut last = handled();
+        while Instant::now() < deadline {
+            tokio::time::sleep(READER_DRAIN_IDLE).await;
+            let now = handled();
+            if now == last {
+                break;
+            }
+            last = now;
+        }
+    }
+
+    if let Some(log_file) = state.log_file.lock().unwrap().as_mut() {
+        log_file
+            .sync()
+            .map_err(|e| format!("Failed to flush {}: {}", log_file.path().display(), e))?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn flush_logs(state: tauri::State<'_, RunnerState>) -> Result<String, String> {
+    flush_log_file(&state).await?;
+    info!("Flushed runner log");
+    Ok("Logs flushed to disk".to_string())
+}
+
+// Freeze a copy of the active runner log as logs/snapshots/<label>-<timestamp>.log,
+// safe from later rotation. Returns the snapshot's path.
+#[tauri::command]
+async fn snapshot_logs(label: Option<String>, state: tauri::State<'_, RunnerState>) -> Result<String, String> {
+    let label: String = label
+        .as_deref()
+        .unwrap_or("")
+        .trim()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    let label = if label.is_empty() { "snapshot".to_string() } else { label };
+    let file_name = format!("{}-{}.log", label, chrono::Local::now().format("%Y%m%d-%H%M%S"));
+    let dest = paths::logs_dir()
+        .ok_or("Could not find home directory")?
+        .join("snapshots")
+        .join(file_name);
+
+    flush_log_file(&state).await?;
+    let log_guard = state.log_file.lock().unwrap();
+    let log_file = log_guard.as_ref().ok_or("Runner log file is not open")?;
+    log_file
+        .snapshot(&dest)
+        .map_err(|e| format!("Failed to snapshot runner log: {}", e))?;
+    info!("Saved log snapshot to {}", dest.display());
+    Ok(dest.to_string_lossy().to_string())
+}
+
+// Open a file or folder with the OS default application.
+fn open_path(path: &Path) -> std::io::Result<()> {
     #[cfg(target_os = "windows")]
     {
         Command::new("explorer")
-            .arg(logs_path.to_string_lossy().to_string())
-            .spawn()
-            .map_err(|e| format!("Failed to open logs folder: {}", e))?;
+            .arg(path.to_string_lossy().to_string())
+            .spawn()?;
     }
     
     #[cfg(target_os = "macos")]
     {
-        Command::new("open")
-            .arg(logs_path.to_string_lossy().to_string())
-            .spawn()
-            .map_err(|e| format!("Failed to open logs folder: {}", e))?;
+        // -t picks the default text editor for files without a registered app
+        let mut command = Command::new("open");
+        if path.is_file() {
+            command.arg("-t");
+        }
+        command.arg(path.to_string_lossy().to_string()).spawn()?;
     }
     
     #[cfg(target_os = "linux")]
     {
         Command::new("xdg-open")
-            .arg(logs_path.to_string_lossy().to_string())
-            .spawn()
-            .map_err(|e| format!("Failed to open logs folder: {}", e))?;
+            .arg(path.to_string_lossy().to_string())
+            .spawn()?;
     }
     
+    Ok(())
+}
+
+#[tauri::command]
+async fn open_logs_folder() -> Result<String, String> {
+    let logs_path = paths::logs_dir().ok_or("Could not find home directory")?;
+    open_path(&logs_path).map_err(|e| format!("Failed to open logs folder: {}", e))?;
     Ok("Logs folder opened".to_string())
 }
 
+// Open config.toml in the default editor, writing the defaults first if it
+// doesn't exist. Edits apply on `reload_config`.
+#[tauri::command]
+async fn open_config_file() -> Result<String, String> {
+    let path = paths::config_file().ok_or("Could not find home directory")?;
+    if !path.exists() {
+        RunnerConfig::default().save()?;
+    }
+    open_path(&path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    info!("Opened {} for editing", path.display());
+    Ok(format!("Opened {}", path.display()))
+}
+
+// Keep the main window above other windows, e.g. to watch the live log while
+// working elsewhere. Saved in state.json and applied whenever the window is created.
+#[tauri::command]
+async fn set_always_on_top(enabled: bool, app: tauri::AppHandle) -> Result<String, String> {
+    SavedState::update(|state| state.always_on_top = enabled)?;
+    apply_always_on_top(&app, enabled);
+    let message = format!("Always on top {}", if enabled { "enabled" } else { "disabled" });
+    info!("{}", message);
+    Ok(message)
+}
+
+fn apply_always_on_top(app: &tauri::AppHandle, enabled: bool) {
+    if let Some(window) = app.get_window(MAIN_WINDOW_LABEL) {
+        if let Err(e) = window.set_always_on_top(enabled) {
+            warn!("Failed to set always on top: {}", e);
+        }
+    }
+    let _ = app.tray_handle().get_item("always_on_top").set_selected(enabled);
+}
+
 fn create_system_tray() -> SystemTray {
     let open = CustomMenuItem::new("open".to_string(), "Open Runner");
     let start = CustomMenuItem::new("start".to_string(), "Start Runner");
     let stop = CustomMenuItem::new("stop".to_string(), "Stop Runner");
     let logs = CustomMenuItem::new("logs".to_string(), "View Logs");
+    let edit_config = CustomMenuItem::new("edit_config".to_string(), "Edit Config");
+    let copy_error = CustomMenuItem::new("copy_error".to_string(), "Copy Last Error");
+    let support_bundle = CustomMenuItem::new("support_bundle".to_string(), "Create Support Bundle…");
+    let mut always_on_top = CustomMenuItem::new("always_on_top".to_string(), "Always on Top");
+    always_on_top.selected = SavedState::load().always_on_top;
     let quit = CustomMenuItem::new("quit".to_string(), "Quit");
     
     let tray_menu = SystemTrayMenu::new()
@@ -141,32 +1720,73 @@ fn create_system_tray() -> SystemTray {
         .add_item(stop)
         .add_native_item(SystemTrayMenuItem::Separator)
         .add_item(logs)
+        .add_item(edit_config)
+        .add_item(copy_error)
+        .add_item(support_bundle)
+        .add_native_item(SystemTrayMenuItem::Separator)
+        .add_item(always_on_top)
         .add_native_item(SystemTrayMenuItem::Separator)
         .add_item(quit);
     
     SystemTray::new().with_menu(tray_menu)
 }
 
+// Show and focus the main window. If it has been destroyed (closing it only
+// hides it, but devtools or a frontend bug can still destroy it), build a new
+// one from its entry in tauri.conf.json.
+fn show_main_window(app: &tauri::AppHandle) {
+    if let Some(window) = app.get_window(MAIN_WINDOW_LABEL) {
+        let _ = window.show();
+        let _ = window.set_focus();
+        return;
+    }
+
+    let window_config = app
+        .config()
+        .tauri
+        .windows
+        .iter()
+        .find(|window| window.label == MAIN_WINDOW_LABEL)
+        .cloned();
+    let window_config = match window_config {
+        Some(window_config) => window_config,
+        None => {
+            warn!("Main window is gone and has no config to recreate it from");
+            return;
+        }
+    };
+
+    // Building a window from an event handler on the main thread can deadlock
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        match tauri::WindowBuilder::from_config(&app, window_config).build() {
+            Ok(window) => {
+                info!("Recreated main window");
+                apply_always_on_top(&app, SavedState::load().always_on_top);
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+            Err(e) => error!("Failed to recreate main window: {}", e),
+        }
+    });
+}
+
 fn handle_system_tray_event(app: &tauri::AppHandle, event: SystemTrayEvent) {
     match event {
         SystemTrayEvent::LeftClick { .. } => {
             // Show main window on left click
-            let window = app.get_window("main").unwrap();
-            let _ = window.show();
-            let _ = window.set_focus();
+            show_main_window(app);
         }
         SystemTrayEvent::MenuItemClick { id, .. } => {
             match id.as_str() {
                 "open" => {
-                    let window = app.get_window("main").unwrap();
-                    let _ = window.show();
-                    let _ = window.set_focus();
+                    show_main_window(app);
                 }
                 "start" => {
                     let app_handle = app.clone();
                     tauri::async_runtime::spawn(async move {
                         let state = app_handle.state::<RunnerState>();
-                        if let Err(e) = start_python_runner(state).await {
+                        if let Err(e) = start_python_runner(app_handle.clone(), state).await {
                             error!("Failed to start runner from tray: {}", e);
                         }
                     });
@@ -175,7 +1795,7 @@ fn handle_system_tray_event(app: &tauri::AppHandle, event: SystemTrayEvent) {
                     let app_handle = app.clone();
                     tauri::async_runtime::spawn(async move {
                         let state = app_handle.state::<RunnerState>();
-                        if let Err(e) = stop_python_runner(state).await {
+                        if let Err(e) = stop_python_runner(app_handle.clone(), state).await {
                             error!("Failed to stop runner from tray: {}", e);
                         }
                     });
@@ -187,12 +1807,40 @@ fn handle_system_tray_event(app: &tauri::AppHandle, event: SystemTrayEvent) {
                         }
                     });
                 }
+                "edit_config" => {
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = open_config_file().await {
+                            error!("Failed to open config from tray: {}", e);
+                        }
+                    });
+                }
+                "copy_error" => {
+                    let app_handle = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        let state = app_handle.state::<RunnerState>();
+                        if let Err(e) = copy_last_error(app_handle.clone(), state).await {
+                            warn!("Failed to copy last error from tray: {}", e);
+                        }
+                    });
+                }
+                "support_bundle" => {
+                    support_bundle::save_from_tray(app);
+                }
+                "always_on_top" => {
+                    let enabled = !SavedState::load().always_on_top;
+                    let app_handle = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = set_always_on_top(enabled, app_handle).await {
+                            warn!("Failed to toggle always on top from tray: {}", e);
+                        }
+                    });
+                }
                 "quit" => {
                     // Stop Python runner before quitting
                     let app_handle = app.clone();
                     tauri::async_runtime::spawn(async move {
                         let state = app_handle.state::<RunnerState>();
-                        let _ = stop_python_runner(state).await;
+                        let _ = stop_python_runner(app_handle.clone(), state).await;
                         app_handle.exit(0);
                     });
                 }
@@ -205,36 +1853,180 @@ fn handle_system_tray_event(app: &tauri::AppHandle, event: SystemTrayEvent) {
 
 fn main() {
     env_logger::init();
+    if single_instance::signal_existing() {
+        info!("Oriphim Runner is already running; showing its window instead");
+        return;
+    }
     info!("Starting Oriphim Runner...");
     
-    let runner_state = RunnerState::new();
+    workspace::restore();
+    lifecycle::prune();
+    // An invalid config file must not stop the app from launching
+    let (config, config_error) = match RunnerConfig::load() {
+        Ok(config) => (config, None),
+        Err(error) => (RunnerConfig::default(), Some(error)),
+    };
+    system_log::configure(&config);
+    let runner_state = RunnerState::new(config);
     
     tauri::Builder::default()
         .manage(runner_state)
         .system_tray(create_system_tray())
         .on_system_tray_event(handle_system_tray_event)
-        .on_window_event(|event| match event.event() {
-            WindowEvent::CloseRequested { api, .. } => {
-                // Hide window instead of closing on X button
-                event.window().hide().unwrap();
-                api.prevent_close();
+        .on_window_event(|event| {
+            if let WindowEvent::CloseRequested { api, .. } = event.event() {
+                match event.window().label() {
+                    MAIN_WINDOW_LABEL => {
+                        // Hide window instead of closing on X button
+                        let _ = event.window().hide();
+                        api.prevent_close();
+                    }
+                    log_window::LOG_WINDOW_LABEL => log_window::save_geometry(event.window()),
+                    _ => {}
+                }
             }
-            _ => {}
         })
         .invoke_handler(tauri::generate_handler![
             start_python_runner,
             stop_python_runner,
+            stop_runner_tree,
+            force_kill,
+            safe_restart,
             get_runner_status,
-            open_logs_folder
+            copy_last_error,
+            get_process_group_id,
+            set_runner_log_level,
+            send_stdin_bytes,
+            close_stdin,
+            force_log_rotate,
+            lifecycle::get_lifecycle_events,
+            snapshot_logs,
+            flush_logs,
+            set_log_buffer_cap,
+            get_recent_stderr,
+            get_recent_logs,
+            get_process_start_time,
+            get_child_state,
+            get_process_environment,
+            fingerprint::get_environment_fingerprint,
+            circuit::get_circuit_breaker,
+            circuit::reset_circuit_breaker,
+            set_session_label,
+            get_session_stats,
+            get_process_metrics,
+            config::validate_config,
+            reload_config,
+            get_config,
+            set_config,
+            reset_to_defaults,
+            export_config,
+            import_config,
+            get_effective_working_dir,
+            is_packaged,
+            workspace::set_workspace,
+            workspace::get_workspace,
+            log_window::open_log_window,
+            schedule::schedule_start,
+            schedule::schedule_stop,
+            schedule::clear_schedule,
+            schedule::get_schedule,
+            interpreters::discover_interpreters,
+            conda::detect_conda,
+            script::check_script_update,
+            script::update_script,
+            set_python_path,
+            set_keep_awake,
+            set_cpu_affinity,
+            supervisor::get_restart_state,
+            supervisor::cancel_pending_restart,
+            crash_dump::get_last_crash,
+            latency::get_startup_latencies,
+            handles::get_process_handles,
+            notify::test_notification,
+            notify::set_notify_on_crash,
+            oneshot::run_python_oneshot,
+            open_logs_folder,
+            open_config_file,
+            set_always_on_top,
+            overrides::launch_with_overrides,
+            diagnose::diagnose_last_failure,
+            notify::snooze_notifications,
+            survivor::get_reconnected_process,
+            benchmark::benchmark_interpreter,
+            metrics_history::get_metrics_history,
+            idle::stop_if_idle,
+            crash_debug::arm_crash_debug,
+            artifacts::list_artifacts,
+            artifacts::delete_artifact,
+            switch_script,
+            shutdown_test::test_graceful_shutdown,
+            stdin_recording::start_recording_stdin,
+            stdin_recording::stop_recording_stdin,
+            stdin_recording::replay_stdin,
+            lock_stats::get_lock_stats,
+            support_bundle::create_support_bundle,
+            install_check::verify_install,
+            install_check::restore_from_bundle
         ])
         .setup(|app| {
+            single_instance::listen(app.handle());
+
+            // The window is created invisible (see tauri.conf.json) so booting
+            // straight to the tray never flashes it on screen
+            let start_hidden = app.state::<RunnerState>().config.lock().unwrap().start_hidden;
+            if let Some(window) = app.get_window(MAIN_WINDOW_LABEL) {
+                if start_hidden {
+                    info!("Starting hidden in the system tray");
+                    let _ = window.hide();
+                } else {
+                    let _ = window.show();
+                }
+            }
+
+            if SavedState::load().always_on_top {
+                apply_always_on_top(&app.handle(), true);
+            }
+
+            let schedule = app.state::<RunnerState>().schedule.lock().unwrap().clone();
+            schedule::update_tooltip(&app.handle(), &schedule);
+            schedule::start_scheduler(app.handle());
+            let (control_socket, metrics_port, metrics_bind) = {
+                let config = app.state::<RunnerState>().config.lock().unwrap().clone();
+                (config.control_socket, config.metrics_port, config.metrics_bind)
+            };
+            if control_socket {
+                control::start(app.handle());
+            }
+            if let Some(port) = metrics_port {
+                match metrics_bind.parse() {
+                    Ok(bind) => prometheus::start(app.handle(), bind, port),
+                    Err(_) => error!("metrics_bind '{}' is not an IP address, metrics not served", metrics_bind),
+                }
+            }
+
+            let reconnected = survivor::detect(&app.state::<RunnerState>());
+            if reconnected.is_some() {
+                survivor::watch(app.handle());
+            }
+
             // Auto-start Python runner on app startup
             let app_handle = app.handle();
-            let runner_state = app_handle.state::<RunnerState>();
             
             tauri::async_runtime::spawn(async move {
                 tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-                if let Err(e) = start_python_runner(runner_state).await {
+                // Reported once the UI has had time to start listening
+                if let Some(error) = config_error {
+                    let _ = app_handle.emit_all("config-error", error);
+                }
+                script::notify_if_outdated(&app_handle);
+                if let Some(process) = reconnected {
+                    // Still running, so leave it be rather than start a second one
+                    let _ = app_handle.emit_all("reconnected-to-runner", process);
+                    let _ = app_handle.emit_all("runner-status", runner_status(&app_handle.state::<RunnerState>()));
+                    return;
+                }
+                let runner_state = app_handle.state::<RunnerState>();
+                if let Err(e) = start_python_runner(app_handle.clone(), runner_state).await {
                     error!("Failed to auto-start Python runner: {}", e);
                 }
             });
@@ -0,0 +1,43 @@
+use serde::Serialize;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use log::{debug, error, info};
+
+use crate::lifecycle::{self, LifecycleKind};
+use crate::RunnerState;
+
+#[derive(Clone, Serialize)]
+struct MaxRuntimeEvent {
+    max_runtime_secs: u64,
+}
+
+// Gracefully stop the process started as `run_id` once it has run for `limit`,
+// emitting `runner-max-runtime-reached`. A stop, not a crash, so nothing
+// restarts it. Ends early if the process exits or is replaced first.
+pub fn watch(app: AppHandle, run_id: u64, limit: Duration, kill_tree: bool) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(limit).await;
+        let state = app.state::<RunnerState>();
+        let pid = match state.python_process.lock().unwrap().as_ref() {
+            Some(process) if process.run_id == run_id => process.child.id(),
+            _ => {
+                debug!("Max runtime watch for run {} stopped", run_id);
+                return;
+            }
+        };
+
+        let reason = format!("Reached max_runtime_secs ({}s)", limit.as_secs());
+        info!("{}, stopping Python runner", reason);
+        lifecycle::record(LifecycleKind::Stop, Some(pid), None, reason);
+        if let Err(e) = crate::stop_runner(&app, &state, kill_tree).await {
+            error!("Max runtime stop failed: {}", e);
+            return;
+        }
+        let _ = app.emit_all(
+            "runner-max-runtime-reached",
+            MaxRuntimeEvent {
+                max_runtime_secs: limit.as_secs(),
+            },
+        );
+    });
+}
@@ -0,0 +1,253 @@
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, System};
+use tauri::{AppHandle, Manager};
+use log::{debug, error, warn};
+
+use crate::circuit;
+use crate::lifecycle::{self, LifecycleKind};
+use crate::metrics_history;
+use crate::RunnerState;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessMetrics {
+    pub pid: u32,
+    pub memory_bytes: u64,
+    // Percent of a single core, so a busy multi-threaded process can exceed 100
+    pub cpu_percent: f32,
+    // None if the platform wouldn't say
+    pub thread_count: Option<usize>,
+    // Open file descriptors on Unix, kernel handles on Windows
+    pub handle_count: Option<usize>,
+}
+
+// Read the current figures for `pid` from `system`. CPU usage is measured
+// between refreshes, so the first sample from a new `System` always reports 0.
+pub fn sample(system: &mut System, pid: u32) -> Option<ProcessMetrics> {
+    let sys_pid = Pid::from_u32(pid);
+    if !system.refresh_process(sys_pid) {
+        return None;
+    }
+    let process = system.process(sys_pid)?;
+    Some(ProcessMetrics {
+        pid,
+        memory_bytes: process.memory(),
+        cpu_percent: process.cpu_usage(),
+        thread_count: thread_count(process),
+        handle_count: handle_count(pid),
+    })
+}
+
+// sysinfo only lists threads on Linux; elsewhere ask the OS directly.
+#[cfg(target_os = "linux")]
+fn thread_count(process: &sysinfo::Process) -> Option<usize> {
+    process.tasks().map(|tasks| tasks.len())
+}
+
+#[cfg(windows)]
+fn thread_count(process: &sysinfo::Process) -> Option<usize> {
+    use windows_sys::Win32::Foundation::{CloseHandle, INVALID_HANDLE_VALUE};
+    use windows_sys::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Thread32First, Thread32Next, TH32CS_SNAPTHREAD, THREADENTRY32,
+    };
+
+    let pid = process.pid().as_u32();
+    unsafe {
+        // The snapshot holds every thread on the system, not just this process's
+        let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPTHREAD, 0);
+        if snapshot == INVALID_HANDLE_VALUE {
+            return None;
+        }
+        let mut entry: THREADENTRY32 = std::mem::zeroed();
+        entry.dwSize = std::mem::size_of::<THREADENTRY32>() as u32;
+        let mut count = 0;
+        let mut more = Thread32First(snapshot, &mut entry) != 0;
+        while more {
+            if entry.th32OwnerProcessID == pid {
+                count += 1;
+            }
+            more = Thread32Next(snapshot, &mut entry) != 0;
+        }
+        CloseHandle(snapshot);
+        Some(count)
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn thread_count(process: &sysinfo::Process) -> Option<usize> {
+    let mut info: libc::proc_taskinfo = unsafe { std::mem::zeroed() };
+    let size = std::mem::size_of::<libc::proc_taskinfo>() as libc::c_int;
+    let pid = process.pid().as_u32() as libc::c_int;
+    let read = unsafe {
+        libc::proc_pidinfo(pid, libc::PROC_PIDTASKINFO, 0, &mut info as *mut _ as *mut libc::c_void, size)
+    };
+    (read == size).then_some(info.pti_threadnum as usize)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn thread_count(_process: &sysinfo::Process) -> Option<usize> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn handle_count(pid: u32) -> Option<usize> {
+    std::fs::read_dir(format!("/proc/{}/fd", pid)).ok().map(|entries| entries.count())
+}
+
+#[cfg(windows)]
+fn handle_count(pid: u32) -> Option<usize> {
+    use windows_sys::Win32::Foundation::CloseHandle;
+    use windows_sys::Win32::System::Threading::{GetProcessHandleCount, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION};
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if handle == 0 {
+            return None;
+        }
+        let mut count = 0u32;
+        let ok = GetProcessHandleCount(handle, &mut count) != 0;
+        CloseHandle(handle);
+        ok.then_some(count as usize)
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn handle_count(pid: u32) -> Option<usize> {
+    // With no buffer, proc_pidinfo returns the size the descriptor list needs
+    let size = unsafe { libc::proc_pidinfo(pid as libc::c_int, libc::PROC_PIDLISTFDS, 0, std::ptr::null_mut(), 0) };
+    (size > 0).then(|| size as usize / std::mem::size_of::<libc::proc_fdinfo>())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn handle_count(_pid: u32) -> Option<usize> {
+    None
+}
+
+#[derive(Clone, Serialize)]
+struct ThreadWarningEvent {
+    thread_count: usize,
+    limit: usize,
+}
+
+#[derive(Clone, Serialize)]
+struct MemoryRestartEvent {
+    memory_bytes: u64,
+    limit_bytes: u64,
+    over_secs: u64,
+}
+
+// Emit `runner-metrics` every `interval` for the process started as `run_id`,
+// stopping once it exits or is replaced. Each start spawns a fresh sampler.
+// Also enforces `memory_limit_mb`.
+pub fn start_sampler(app: AppHandle, run_id: u64, interval: Duration) {
+    tauri::async_runtime::spawn(async move {
+        let mut system = System::new();
+        // When memory first went over the limit, reset whenever it drops back
+        let mut over_since: Option<Instant> = None;
+        // Warned about thread_count_warn, until the count drops back under it
+        let mut threads_warned = false;
+        // When the last sample went to metrics.jsonl
+        let mut history_written: Option<Instant> = None;
+
+        loop {
+            tokio::time::sleep(interval).await;
+            let state = app.state::<RunnerState>();
+
+            let pid = {
+                let process_guard = state.python_process.lock().unwrap();
+                match process_guard.as_ref() {
+                    Some(process) if process.run_id == run_id => process.child.id(),
+                    _ => break,
+                }
+            };
+
+            if let Some(metrics) = sample(&mut system, pid) {
+                state.stats.record_memory(metrics.memory_bytes);
+                *state.metrics.lock().unwrap() = Some(metrics.clone());
+                let memory_bytes = metrics.memory_bytes;
+                let thread_count = metrics.thread_count;
+                let (limit_mb, sustain, thread_limit, history) = {
+                    let config = state.config.lock().unwrap();
+                    let history = config.metrics_history.then(|| {
+                        (Duration::from_secs(config.metrics_history_interval_secs), config.metrics_history_max_bytes)
+                    });
+                    (config.memory_limit_mb, Duration::from_secs(config.memory_limit_secs), config.thread_count_warn, history)
+                };
+                if let Some((every, max_bytes)) = history {
+                    if history_written.map_or(true, |written| written.elapsed() >= every) {
+                        history_written = Some(Instant::now());
+                        metrics_history::append(&metrics, max_bytes);
+                    }
+                }
+                let _ = app.emit_all("runner-metrics", metrics);
+
+                match (thread_count, thread_limit) {
+                    (Some(thread_count), Some(limit)) if thread_count > limit => {
+                        if !threads_warned {
+                            threads_warned = true;
+                            warn!("Python runner has {} threads, over the {} thread limit", thread_count, limit);
+                            let _ = app.emit_all("runner-thread-warning", ThreadWarningEvent { thread_count, limit });
+                        }
+                    }
+                    _ => threads_warned = false,
+                }
+                let limit_bytes = match limit_mb {
+                    Some(limit_mb) => limit_mb.saturating_mul(1024 * 1024),
+                    None => {
+                        over_since = None;
+                        continue;
+                    }
+                };
+                if memory_bytes <= limit_bytes {
+                    over_since = None;
+                    continue;
+                }
+
+                let since = *over_since.get_or_insert_with(|| {
+                    warn!(
+                        "Python runner memory {} MB is over the {} MB limit",
+                        memory_bytes / (1024 * 1024),
+                        limit_bytes / (1024 * 1024)
+                    );
+                    Instant::now()
+                });
+                if since.elapsed() >= sustain {
+                    restart_for_memory(&app, memory_bytes, limit_bytes, since.elapsed());
+                    break;
+                }
+            }
+        }
+
+        debug!("Metrics sampler for run {} stopped", run_id);
+    });
+}
+
+// The restart starts a fresh sampler, so the caller stops after this.
+fn restart_for_memory(app: &AppHandle, memory_bytes: u64, limit_bytes: u64, over: Duration) {
+    if !circuit::allow_restart(app, &app.state::<RunnerState>(), "memory limit") {
+        return;
+    }
+    let reason = format!(
+        "Memory {} MB stayed over the {} MB limit for {}s",
+        memory_bytes / (1024 * 1024),
+        limit_bytes / (1024 * 1024),
+        over.as_secs()
+    );
+    warn!("{}, restarting Python runner", reason);
+    lifecycle::record(LifecycleKind::Restart, None, None, reason);
+    let _ = app.emit_all(
+        "runner-memory-restart",
+        MemoryRestartEvent {
+            memory_bytes,
+            limit_bytes,
+            over_secs: over.as_secs(),
+        },
+    );
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = crate::safe_restart(app.clone(), app.state::<RunnerState>()).await {
+            error!("Memory limit restart failed: {}", e);
+        }
+    });
+}
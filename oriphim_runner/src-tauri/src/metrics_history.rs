@@ -0,0 +1,100 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::sync::Mutex;
+use log::warn;
+
+use crate::logs::{self, LogFile};
+use crate::metrics::ProcessMetrics;
+use crate::paths;
+
+// metrics.jsonl plus one rotated file, metrics.1.jsonl
+const ROTATED_FILES: usize = 1;
+
+// Opened by the first sample of the session
+static HISTORY: Mutex<Option<LogFile>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricsRecord {
+    pub timestamp: DateTime<Local>,
+    #[serde(flatten)]
+    pub metrics: ProcessMetrics,
+}
+
+// Append a sample to ~/.oriphim/metrics.jsonl, rotating it past `max_bytes`.
+pub fn append(metrics: &ProcessMetrics, max_bytes: u64) {
+    if let Err(e) = write(metrics, max_bytes) {
+        warn!("Failed to record metrics history: {}", e);
+    }
+}
+
+fn write(metrics: &ProcessMetrics, max_bytes: u64) -> Result<(), String> {
+    let record = MetricsRecord {
+        timestamp: Local::now(),
+        metrics: metrics.clone(),
+    };
+    let line = serde_json::to_string(&record).map_err(|e| format!("Failed to serialize metrics: {}", e))?;
+    let path = paths::metrics_history_file().ok_or("Could not find home directory")?;
+    let mut history = HISTORY.lock().unwrap();
+    // Switching workspaces moves the file
+    let file = match history.as_mut() {
+        Some(file) if file.path() == path => file,
+        _ => {
+            let file = LogFile::open(path.clone(), max_bytes, ROTATED_FILES)
+                .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+            history.insert(file)
+        }
+    };
+    file.set_limits(max_bytes, ROTATED_FILES);
+    file.write_line(&line).map_err(|e| format!("Failed to write {}: {}", file.path().display(), e))
+}
+
+fn read_records(path: &Path, range: &MetricsRange, records: &mut Vec<MetricsRecord>) {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(_) => return,
+    };
+    let lines = BufReader::new(file).lines().map_while(Result::ok).filter(|line| !line.trim().is_empty());
+    for line in lines {
+        match serde_json::from_str::<MetricsRecord>(&line) {
+            Ok(record) if range.contains(record.timestamp) => records.push(record),
+            Ok(_) => {}
+            Err(e) => warn!("Skipping invalid metrics history entry: {}", e),
+        }
+    }
+}
+
+// Either end may be left open.
+#[derive(Debug, Default, Deserialize)]
+pub struct MetricsRange {
+    pub since: Option<DateTime<Local>>,
+    pub until: Option<DateTime<Local>>,
+}
+
+impl MetricsRange {
+    fn contains(&self, timestamp: DateTime<Local>) -> bool {
+        self.since.map_or(true, |since| timestamp >= since) && self.until.map_or(true, |until| timestamp <= until)
+    }
+}
+
+// Samples recorded by `metrics_history` within `range` (everything kept when
+// unset), oldest first.
+#[tauri::command]
+pub async fn get_metrics_history(range: Option<MetricsRange>) -> Result<Vec<MetricsRecord>, String> {
+    let path = paths::metrics_history_file().ok_or("Could not find home directory")?;
+    let range = range.unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || {
+        // Hold off writers so the latest line is always whole
+        let _history = HISTORY.lock().unwrap();
+        let mut records = Vec::new();
+        for index in (1..=ROTATED_FILES).rev() {
+            read_records(&logs::rotated_path(&path, index), &range, &mut records);
+        }
+        read_records(&path, &range, &mut records);
+        records
+    })
+    .await
+    .map_err(|e| format!("Failed to read metrics history: {}", e))
+}
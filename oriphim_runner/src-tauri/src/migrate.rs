@@ -0,0 +1,182 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+use log::{info, warn};
+
+// Schema version written to config.toml. Files without `config_version` are
+// version 1. Bump it together with a new entry in MIGRATIONS.
+pub const CONFIG_VERSION: u32 = 2;
+
+// Upgrades a table from the version before it to the version after.
+struct Migration {
+    from: u32,
+    description: &'static str,
+    apply: fn(&mut Table),
+}
+
+const MIGRATIONS: &[Migration] = &[Migration {
+    from: 1,
+    description: "moved ready_marker into readiness_probe",
+    apply: ready_marker_to_probe,
+}];
+
+// readiness_probe took over ready_marker; the old key still works, but both
+// together only ever meant the probe. With health_port alongside there is no
+// single probe for both, so that combination is kept.
+fn ready_marker_to_probe(table: &mut Table) {
+    if table.contains_key("readiness_probe") || table.contains_key("health_port") {
+        return;
+    }
+    if let Some(Value::String(marker)) = table.remove("ready_marker") {
+        let mut probe = Table::new();
+        probe.insert("type".to_string(), Value::String("stdout-marker".to_string()));
+        probe.insert("marker".to_string(), Value::String(marker));
+        table.insert("readiness_probe".to_string(), Value::Table(probe));
+    }
+}
+
+fn version_of(table: &Table) -> u32 {
+    match table.get("config_version") {
+        Some(Value::Integer(version)) => u32::try_from(*version).unwrap_or(1),
+        _ => 1,
+    }
+}
+
+// Bring `table` up to CONFIG_VERSION. Returns the migrations applied, in order;
+// nothing for a current file, or one from a newer app, which is left alone.
+fn migrate(table: &mut Table) -> Vec<&'static str> {
+    let mut version = version_of(table);
+    if version > CONFIG_VERSION {
+        warn!(
+            "config.toml is version {}, newer than this app understands ({}); loading it as is",
+            version, CONFIG_VERSION
+        );
+        return Vec::new();
+    }
+    let mut applied = Vec::new();
+    while version < CONFIG_VERSION {
+        if let Some(migration) = MIGRATIONS.iter().find(|migration| migration.from == version) {
+            (migration.apply)(table);
+            applied.push(migration.description);
+        }
+        version += 1;
+    }
+    if !applied.is_empty() || !table.contains_key("config_version") {
+        table.insert("config_version".to_string(), Value::Integer(CONFIG_VERSION.into()));
+    }
+    applied
+}
+
+// `config.toml.v1.bak` for a version 1 file, kept alongside any other backups
+fn backup_path(path: &Path, version: u32) -> PathBuf {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(format!(".v{}.bak", version));
+    PathBuf::from(backup)
+}
+
+// Upgrade the contents of the config file at `path` to the current schema,
+// writing the result back after copying the original aside. Anything that
+// doesn't parse is returned unchanged so loading reports the error as usual.
+pub fn upgrade(path: &Path, contents: String) -> String {
+    let mut table: Table = match toml::from_str(&contents) {
+        Ok(table) => table,
+        Err(_) => return contents,
+    };
+    let version = version_of(&table);
+    if version >= CONFIG_VERSION {
+        return contents;
+    }
+    let applied = migrate(&mut table);
+    for description in &applied {
+        info!("Migrated {} from version {}: {}", path.display(), version, description);
+    }
+    let migrated = match toml::to_string_pretty(&table) {
+        Ok(migrated) => migrated,
+        Err(e) => {
+            warn!("Failed to serialize migrated config, loading the original: {}", e);
+            return contents;
+        }
+    };
+
+    let backup = backup_path(path, version);
+    if let Err(e) = fs::copy(path, &backup) {
+        // Still use the upgrade, just don't overwrite the only copy of the old file
+        warn!("Not rewriting {}: backup to {} failed: {}", path.display(), backup.display(), e);
+        return migrated;
+    }
+    match fs::write(path, &migrated) {
+        Ok(()) => info!(
+            "Upgraded {} to config version {} (original saved as {})",
+            path.display(),
+            CONFIG_VERSION,
+            backup.display()
+        ),
+        Err(e) => warn!("Failed to write migrated {}: {}", path.display(), e),
+    }
+    migrated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ReadinessProbe, RunnerConfig};
+
+    // A config.toml from before config_version existed
+    const V1_CONFIG: &str = r#"
+python_path = "/usr/bin/python3"
+ready_marker = "Runner ready"
+ready_timeout_secs = 45
+auto_restart = false
+watch_patterns = ["FATAL", { regex = "dead\\s*lock" }]
+"#;
+
+    #[test]
+    fn v1_config_migrates_to_current() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(&path, V1_CONFIG).unwrap();
+
+        let migrated = upgrade(&path, V1_CONFIG.to_string());
+        let config: RunnerConfig = toml::from_str(&migrated).unwrap();
+
+        assert_eq!(config.config_version, CONFIG_VERSION);
+        assert!(config.ready_marker.is_none());
+        assert!(matches!(
+            &config.readiness_probe,
+            Some(ReadinessProbe::StdoutMarker { marker }) if marker == "Runner ready"
+        ));
+        assert_eq!(config.ready_marker().as_deref(), Some("Runner ready"));
+        // Everything else carries over
+        assert_eq!(config.python_path.as_deref(), Some("/usr/bin/python3"));
+        assert_eq!(config.ready_timeout_secs, 45);
+        assert!(!config.auto_restart);
+        assert_eq!(config.watch_patterns.len(), 2);
+
+        // The file is rewritten, with the original kept beside it
+        assert_eq!(fs::read_to_string(&path).unwrap(), migrated);
+        assert_eq!(fs::read_to_string(backup_path(&path, 1)).unwrap(), V1_CONFIG);
+    }
+
+    #[test]
+    fn ready_marker_with_health_port_is_kept() {
+        let mut table: Table = toml::from_str("ready_marker = \"up\"\nhealth_port = 8080\n").unwrap();
+        assert_eq!(migrate(&mut table).len(), 1);
+        assert_eq!(table.get("ready_marker").and_then(Value::as_str), Some("up"));
+        assert!(!table.contains_key("readiness_probe"));
+        assert_eq!(version_of(&table), CONFIG_VERSION);
+    }
+
+    #[test]
+    fn current_and_newer_configs_are_untouched() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let current = format!("config_version = {}\nready_marker = \"up\"\n", CONFIG_VERSION);
+        assert_eq!(upgrade(&path, current.clone()), current);
+        assert!(!path.exists());
+
+        let mut newer: Table = toml::from_str("config_version = 99\nready_marker = \"up\"\n").unwrap();
+        assert!(migrate(&mut newer).is_empty());
+        assert_eq!(version_of(&newer), 99);
+        assert!(newer.contains_key("ready_marker"));
+    }
+}
@@ -0,0 +1,157 @@
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::api::notification::Notification;
+use tauri::{AppHandle, Manager};
+use log::{debug, info, warn};
+
+use crate::saved_state::SavedState;
+use crate::RunnerState;
+
+// Longest snooze, so a typo can't silence notifications for good
+const MAX_SNOOZE: Duration = Duration::from_secs(24 * 60 * 60);
+
+// When alerts resume, and the snooze it belongs to so an older timer can't
+// end a newer snooze
+struct Snooze {
+    until: Instant,
+    token: u64,
+}
+
+static SNOOZE: Mutex<Option<Snooze>> = Mutex::new(None);
+static NEXT_SNOOZE_TOKEN: AtomicU64 = AtomicU64::new(1);
+
+// Time left on `snooze_notifications`, or None when not snoozed.
+pub fn snooze_remaining() -> Option<Duration> {
+    SNOOZE
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|snooze| snooze.until.saturating_duration_since(Instant::now()))
+        .filter(|remaining| !remaining.is_zero())
+}
+
+// Show a notification about the runner (a crash, a forced kill, a pattern
+// match) unless they are snoozed.
+pub fn alert(app: &AppHandle, title: &str, body: &str) -> Result<(), String> {
+    if snooze_remaining().is_some() {
+        debug!("Notification snoozed: {}", title);
+        return Ok(());
+    }
+    show(app, title, body)
+}
+
+#[derive(Clone, Serialize)]
+struct SnoozeEvent {
+    // Remaining seconds, 0 once notifications are back on
+    remaining_secs: u64,
+}
+
+// Hold back runner notifications for `duration_secs` (at most a day), then
+// turn them back on and emit `notifications-resumed`. 0 ends a snooze early.
+#[tauri::command]
+pub async fn snooze_notifications(duration_secs: u64, app: AppHandle) -> Result<String, String> {
+    let duration = Duration::from_secs(duration_secs).min(MAX_SNOOZE);
+    if duration.is_zero() {
+        let was_snoozed = SNOOZE.lock().unwrap().take().is_some();
+        if was_snoozed {
+            info!("Notifications resumed");
+            let _ = app.emit_all("notifications-resumed", SnoozeEvent { remaining_secs: 0 });
+            let _ = app.emit_all("runner-status", crate::runner_status(&app.state::<RunnerState>()));
+        }
+        return Ok("Notifications are on".to_string());
+    }
+
+    let token = NEXT_SNOOZE_TOKEN.fetch_add(1, Ordering::Relaxed);
+    *SNOOZE.lock().unwrap() = Some(Snooze {
+        until: Instant::now() + duration,
+        token,
+    });
+    info!("Notifications snoozed for {}s", duration.as_secs());
+    let _ = app.emit_all("notifications-snoozed", SnoozeEvent { remaining_secs: duration.as_secs() });
+
+    let timer_app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(duration).await;
+        let ended = {
+            let mut snooze = SNOOZE.lock().unwrap();
+            let current = snooze.as_ref().is_some_and(|snooze| snooze.token == token);
+            if current {
+                *snooze = None;
+            }
+            current
+        };
+        if ended {
+            info!("Notification snooze ended");
+            let _ = timer_app.emit_all("notifications-resumed", SnoozeEvent { remaining_secs: 0 });
+            let state = timer_app.state::<RunnerState>();
+            let _ = timer_app.emit_all("runner-status", crate::runner_status(&state));
+        }
+    });
+    let _ = app.emit_all("runner-status", crate::runner_status(&app.state::<RunnerState>()));
+    Ok(format!("Notifications snoozed for {}s", duration.as_secs()))
+}
+
+// Show a desktop notification from the app.
+pub fn show(app: &AppHandle, title: &str, body: &str) -> Result<(), String> {
+    Notification::new(&app.config().tauri.bundle.identifier)
+        .title(title)
+        .body(body)
+        .show()
+        .map_err(|e| format!("Failed to show notification: {}", e))
+}
+
+// Whether a sample notification could be handed to the OS. None of the
+// platforms report whether the user then allowed it, so `shown` can be true
+// for a notification that never appears; the frontend can check the webview's
+// `isPermissionGranted` for that.
+#[derive(Debug, Clone, Serialize)]
+pub struct NotificationTest {
+    pub shown: bool,
+    pub error: Option<String>,
+}
+
+fn send_test(app: &AppHandle) -> NotificationTest {
+    match show(app, "Oriphim Runner", "Notifications are working") {
+        Ok(()) => NotificationTest { shown: true, error: None },
+        Err(error) => {
+            warn!("{}", error);
+            NotificationTest { shown: false, error: Some(error) }
+        }
+    }
+}
+
+// Fire a sample notification. On macOS the first one also triggers the
+// system's permission prompt.
+#[tauri::command]
+pub async fn test_notification(app: AppHandle) -> Result<NotificationTest, String> {
+    let result = send_test(&app);
+    SavedState::update(|saved| saved.notifications_tested = true)?;
+    Ok(result)
+}
+
+// Turn `notify_on_crash` on or off, saving it to config.toml. The first time it
+// is enabled a sample notification is sent so the OS asks for permission now,
+// not on the first crash.
+#[tauri::command]
+pub async fn set_notify_on_crash(
+    enabled: bool,
+    app: AppHandle,
+    state: tauri::State<'_, RunnerState>,
+) -> Result<Option<NotificationTest>, String> {
+    {
+        let mut config = state.config.lock().unwrap();
+        let previous = std::mem::replace(&mut config.notify_on_crash, enabled);
+        if let Err(e) = config.save() {
+            config.notify_on_crash = previous;
+            return Err(e);
+        }
+    }
+    info!("Crash notifications {}", if enabled { "enabled" } else { "disabled" });
+
+    if !enabled || SavedState::load().notifications_tested {
+        return Ok(None);
+    }
+    test_notification(app).await.map(Some)
+}
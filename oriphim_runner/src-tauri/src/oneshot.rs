@@ -0,0 +1,67 @@
+use serde::Serialize;
+use std::process::Stdio;
+use std::time::Duration;
+use log::{info, warn};
+
+use crate::interpreters;
+use crate::paths;
+use crate::RunnerState;
+
+const DEFAULT_TIMEOUT_SECS: u64 = 10;
+const MAX_TIMEOUT_SECS: u64 = 120;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OneshotOutput {
+    pub stdout: String,
+    pub stderr: String,
+    pub exit_code: Option<i32>,
+}
+
+// Run `<python> -c <code>` with the runner's interpreter and working directory,
+// separate from the managed process. Only available with `advanced_mode`
+// since it executes whatever it's given.
+#[tauri::command]
+pub async fn run_python_oneshot(
+    code: String,
+    timeout_secs: Option<u64>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, RunnerState>,
+) -> Result<OneshotOutput, String> {
+    let (advanced_mode, config, working_dir) = {
+        let config = state.config.lock().unwrap().expanded()?;
+        let working_dir = paths::working_dir(&app, &config);
+        (config.advanced_mode, config, working_dir)
+    };
+    if !advanced_mode {
+        return Err("One-shot Python commands require advanced_mode in config.toml".to_string());
+    }
+    let interpreter = interpreters::resolve_interpreter(&config, &working_dir)?.to_string_lossy().to_string();
+    let timeout = Duration::from_secs(timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS).clamp(1, MAX_TIMEOUT_SECS));
+
+    info!("Running one-shot Python command with {}", interpreter);
+    let child = tokio::process::Command::new(&interpreter)
+        .arg("-c")
+        .arg(&code)
+        .current_dir(&working_dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        // Dropping the output future on timeout kills the process
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", interpreter, e))?;
+
+    let output = match tokio::time::timeout(timeout, child.wait_with_output()).await {
+        Ok(output) => output.map_err(|e| format!("Failed to read one-shot output: {}", e))?,
+        Err(_) => {
+            warn!("One-shot Python command timed out after {}s", timeout.as_secs());
+            return Err(format!("Command timed out after {}s", timeout.as_secs()));
+        }
+    };
+
+    Ok(OneshotOutput {
+        stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+        stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        exit_code: output.status.code(),
+    })
+}
@@ -0,0 +1,752 @@
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, Weak};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use log::{debug, error, info, warn};
+
+use crate::buffer::{LogBuffer, LogLine};
+use crate::config::{CarriageReturnMode, WatchPattern};
+use crate::logs::{self, LogFile, LogFormat, LoggingChange};
+use crate::lock_stats::TrackedMutex;
+use crate::notify;
+use crate::system_log::{self, Level};
+use crate::process::ManagedProcess;
+use crate::stats::SessionStats;
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+impl Stream {
+    fn name(self) -> &'static str {
+        match self {
+            Stream::Stdout => "stdout",
+            Stream::Stderr => "stderr",
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+struct OutputLine<'a> {
+    stream: Stream,
+    line: &'a str,
+    // The line laid out by a non-default `log_format`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    formatted: Option<String>,
+}
+
+// A piece of output from a reader: a whole line, or with
+// `carriage_return = "overwrite"` the text a bare `\r` is about to redraw.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Segment {
+    Line,
+    Overwrite,
+}
+
+#[derive(Clone, Serialize)]
+struct PatternMatch<'a> {
+    pattern: &'a str,
+    stream: Stream,
+    line: &'a str,
+}
+
+// A `watch_patterns` entry, compiled once when the config is loaded.
+#[derive(Clone)]
+pub enum WatchMatcher {
+    Substring(String),
+    Regex(Regex),
+}
+
+impl WatchMatcher {
+    pub fn compile(pattern: &WatchPattern) -> Result<Self, regex::Error> {
+        match pattern {
+            WatchPattern::Substring(text) => Ok(WatchMatcher::Substring(text.clone())),
+            WatchPattern::Regex { regex } => Regex::new(regex).map(WatchMatcher::Regex),
+        }
+    }
+
+    pub fn compile_all(patterns: &[WatchPattern]) -> Vec<Self> {
+        patterns
+            .iter()
+            .filter_map(|pattern| match Self::compile(pattern) {
+                Ok(matcher) => Some(matcher),
+                Err(e) => {
+                    error!("Ignoring invalid watch pattern: {}", e);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    fn pattern(&self) -> &str {
+        match self {
+            WatchMatcher::Substring(text) => text,
+            WatchMatcher::Regex(regex) => regex.as_str(),
+        }
+    }
+
+    fn is_match(&self, line: &str) -> bool {
+        match self {
+            WatchMatcher::Substring(text) => line.contains(text.as_str()),
+            WatchMatcher::Regex(regex) => regex.is_match(line),
+        }
+    }
+}
+
+// At most one desktop notification per watch pattern in this long; matches in
+// between are counted and mentioned in the next one.
+const NOTIFY_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Default)]
+pub struct NotifyLimiter {
+    // Per pattern: when it last notified and the matches suppressed since
+    patterns: HashMap<String, (Instant, u64)>,
+}
+
+impl NotifyLimiter {
+    // The matches suppressed since the last notification if one may go out now.
+    fn admit(&mut self, pattern: &str) -> Option<u64> {
+        match self.patterns.get_mut(pattern) {
+            Some((last, suppressed)) if last.elapsed() < NOTIFY_INTERVAL => {
+                *suppressed += 1;
+                None
+            }
+            Some((last, suppressed)) => {
+                *last = Instant::now();
+                Some(std::mem::take(suppressed))
+            }
+            None => {
+                self.patterns.insert(pattern.to_string(), (Instant::now(), 0));
+                Some(0)
+            }
+        }
+    }
+}
+
+// Read buffer per pipe: small with `line_buffered` so each line is handed over
+// as it arrives, large otherwise to cut per-read overhead on heavy output.
+const LINE_READ_BUFFER: usize = 8 * 1024;
+const BLOCK_READ_BUFFER: usize = 256 * 1024;
+
+const THROTTLE_WINDOW: Duration = Duration::from_secs(1);
+// How often the flush thread checks for drops of a window no line has closed.
+const THROTTLE_FLUSH_INTERVAL: Duration = Duration::from_millis(250);
+
+// Fixed one-second windows of at most `limit` lines. Drops are reported once
+// per window: when the next line starts a new one, or from the flush thread
+// once the window has passed without one, and at EOF.
+pub struct OutputThrottle {
+    limit: u32,
+    window_start: Instant,
+    lines: u32,
+    dropped: u64,
+}
+
+#[derive(Clone, Serialize)]
+struct ThrottledEvent {
+    dropped: u64,
+    limit_per_sec: u32,
+}
+
+impl OutputThrottle {
+    pub fn new(limit: u32) -> Self {
+        Self {
+            limit,
+            window_start: Instant::now(),
+            lines: 0,
+            dropped: 0,
+        }
+    }
+
+    // Whether this line may go out; also returns the drops of a window that just ended.
+    fn admit(&mut self) -> (bool, u64) {
+        let mut ended_drops = 0;
+        if self.window_start.elapsed() >= THROTTLE_WINDOW {
+            ended_drops = std::mem::take(&mut self.dropped);
+            self.window_start = Instant::now();
+            self.lines = 0;
+        }
+        if self.lines < self.limit {
+            self.lines += 1;
+            (true, ended_drops)
+        } else {
+            self.dropped += 1;
+            (false, ended_drops)
+        }
+    }
+
+    // The drops of the current window if it has ended, or of any window with `finished`.
+    fn take_drops(&mut self, finished: bool) -> u64 {
+        if !finished && self.window_start.elapsed() < THROTTLE_WINDOW {
+            return 0;
+        }
+        std::mem::take(&mut self.dropped)
+    }
+}
+
+fn report_drops(app: &AppHandle, dropped: u64, limit: u32) {
+    if dropped == 0 {
+        return;
+    }
+    warn!("Dropped {} Python runner output lines over the {}/s limit", dropped, limit);
+    let _ = app.emit_all("runner-output-throttled", ThrottledEvent { dropped, limit_per_sec: limit });
+}
+
+pub struct OutputOptions {
+    pub merge_output: bool,
+    pub line_buffered: bool,
+    pub throttle: Option<Arc<Mutex<OutputThrottle>>>,
+    pub carriage_return: CarriageReturnMode,
+    pub watch_patterns: Vec<WatchMatcher>,
+    pub notify_on_pattern: bool,
+    pub notify_limiter: Mutex<NotifyLimiter>,
+    pub log_file: Arc<TrackedMutex<Option<LogFile>>>,
+    pub log_stdout_to_file: bool,
+    pub log_stderr_to_file: bool,
+    pub log_format: Arc<TrackedMutex<LogFormat>>,
+    pub log_buffer: Arc<TrackedMutex<LogBuffer>>,
+    pub stderr_buffer: Arc<TrackedMutex<LogBuffer>>,
+    pub stats: Arc<SessionStats>,
+    pub ready_marker: Option<String>,
+    pub ready_marker_seen: Arc<AtomicBool>,
+}
+
+#[derive(Clone, Serialize)]
+struct LoggingDisabledEvent {
+    path: String,
+    reason: String,
+    retry_secs: u64,
+}
+
+// Append `line` to the runner log. If the file can't be written (read-only
+// directory, full disk) lines are dropped, with a `logging-disabled` event,
+// and writing is retried every logs::RETRY_INTERVAL; `logging-enabled` follows
+// once it works again. Output still reaches the UI and buffers meanwhile.
+pub fn write_log_file(app: &AppHandle, log_file: &TrackedMutex<Option<LogFile>>, line: &str) {
+    let (path, change) = {
+        let mut log_file = log_file.lock().unwrap();
+        let log_file = match log_file.as_mut() {
+            Some(log_file) => log_file,
+            None => return,
+        };
+        match log_file.log_line(line) {
+            Some(change) => (log_file.path().display().to_string(), change),
+            None => return,
+        }
+    };
+    match change {
+        LoggingChange::Disabled(reason) => {
+            warn!("Failed to write {}, file logging is off until it can be written again: {}", path, reason);
+            let retry_secs = logs::RETRY_INTERVAL.as_secs();
+            let _ = app.emit_all("logging-disabled", LoggingDisabledEvent { path, reason, retry_secs });
+        }
+        LoggingChange::Enabled => {
+            info!("Writing {} again", path);
+            let _ = app.emit_all("logging-enabled", path);
+        }
+    }
+}
+
+// Open `path` for appending as the runner's stdout and stderr. Both share one
+// handle, so the file keeps the order the process wrote in.
+pub fn redirect(path: &Path) -> Result<(Stdio, Stdio), String> {
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open output_redirect {}: {}", path.display(), e))?;
+    let stderr = file
+        .try_clone()
+        .map_err(|e| format!("Failed to open output_redirect {}: {}", path.display(), e))?;
+    Ok((Stdio::from(file), Stdio::from(stderr)))
+}
+
+type MergedLine = (Stream, Segment, usize, String);
+type LineHandler = Box<dyn FnMut(Segment, usize, &str) + Send>;
+
+#[derive(Clone, Serialize)]
+struct ConsoleSessionEvent {
+    run_id: u64,
+    pid: u32,
+    // Set on `console-continued`
+    separator: Option<String>,
+}
+
+// Tell the UI where a restarted process's output goes: a fresh console with
+// `console_per_restart`, otherwise the current one after a separator line.
+// A start that isn't a restart sends neither.
+pub fn announce_session(app: &AppHandle, process: &ManagedProcess, restart: bool, new_console: bool) {
+    if !restart {
+        return;
+    }
+    let pid = process.child.id();
+    if new_console {
+        let _ = app.emit_all("console-new-session", ConsoleSessionEvent { run_id: process.run_id, pid, separator: None });
+    } else {
+        let separator = format!("=== Restarted (PID {}) ===", pid);
+        let event = ConsoleSessionEvent { run_id: process.run_id, pid, separator: Some(separator) };
+        let _ = app.emit_all("console-continued", event);
+    }
+}
+
+// Start one thread per output pipe. Each line is forwarded to the UI as a
+// `runner-stdout`/`runner-stderr` event and checked against the watch patterns.
+// With `merge_output` both pipes feed a single thread instead, which handles
+// lines in arrival order and emits them as `runner-output`.
+// The threads are kept on the process so they can be joined once it exits.
+pub fn spawn_readers(app: &AppHandle, process: &mut ManagedProcess, options: OutputOptions) {
+    let options = Arc::new(options);
+    if let Some(throttle) = &options.throttle {
+        spawn_throttle_flush(app.clone(), throttle.clone(), Arc::downgrade(&options), process);
+    }
+
+    let merged = if options.merge_output {
+        let (tx, rx) = mpsc::channel();
+        spawn_merger(app.clone(), rx, options.clone(), process);
+        Some(tx)
+    } else {
+        None
+    };
+
+    let read_buffer = if options.line_buffered { LINE_READ_BUFFER } else { BLOCK_READ_BUFFER };
+    let reader = ReaderOptions { carriage_return: options.carriage_return, read_buffer };
+    if let Some(stdout) = process.child.stdout.take() {
+        let handler = line_handler(app, Stream::Stdout, &options, merged.clone());
+        spawn_output_reader(Stream::Stdout, stdout, reader, handler, process);
+    }
+    if let Some(stderr) = process.child.stderr.take() {
+        let handler = line_handler(app, Stream::Stderr, &options, merged);
+        spawn_output_reader(Stream::Stderr, stderr, reader, handler, process);
+    }
+}
+
+fn line_handler(
+    app: &AppHandle,
+    stream: Stream,
+    options: &Arc<OutputOptions>,
+    merged: Option<Sender<MergedLine>>,
+) -> LineHandler {
+    match merged {
+        Some(tx) => Box::new(move |segment, read, line| {
+            let _ = tx.send((stream, segment, read, line.to_string()));
+        }),
+        None => {
+            let app = app.clone();
+            let options = options.clone();
+            Box::new(move |segment, read, line| process_segment(&app, stream, segment, read, line, &options))
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct ReaderOptions {
+    carriage_return: CarriageReturnMode,
+    read_buffer: usize,
+}
+
+fn spawn_output_reader<R, F>(
+    stream: Stream,
+    pipe: R,
+    options: ReaderOptions,
+    on_segment: F,
+    process: &mut ManagedProcess,
+) where
+    R: Read + Send + 'static,
+    F: FnMut(Segment, usize, &str) + Send + 'static,
+{
+    match spawn_reader(stream, pipe, options, on_segment) {
+        Ok(handle) => process.readers.push(handle),
+        Err(e) => error!("Failed to start {} reader thread: {}", stream.name(), e),
+    }
+}
+
+// Reports drops that no later line would, until the readers (the only other
+// holders of `options`) are gone, then reports the last window's as at EOF.
+fn spawn_throttle_flush(
+    app: AppHandle,
+    throttle: Arc<Mutex<OutputThrottle>>,
+    options: Weak<OutputOptions>,
+    process: &mut ManagedProcess,
+) {
+    let result = thread::Builder::new()
+        .name("runner-output-throttle".to_string())
+        .spawn(move || loop {
+            thread::sleep(THROTTLE_FLUSH_INTERVAL);
+            let finished = options.strong_count() == 0;
+            let (dropped, limit) = {
+                let mut throttle = throttle.lock().unwrap();
+                (throttle.take_drops(finished), throttle.limit)
+            };
+            report_drops(&app, dropped, limit);
+            if finished {
+                break;
+            }
+        });
+    match result {
+        Ok(handle) => process.readers.push(handle),
+        Err(e) => error!("Failed to start output throttle thread: {}", e),
+    }
+}
+
+// Runs until both readers have finished and dropped their senders.
+fn spawn_merger(app: AppHandle, rx: Receiver<MergedLine>, options: Arc<OutputOptions>, process: &mut ManagedProcess) {
+    let result = thread::Builder::new()
+        .name("runner-output".to_string())
+        .spawn(move || {
+            for (stream, segment, read, line) in rx {
+                process_segment(&app, stream, segment, read, &line, &options);
+            }
+        });
+    match result {
+        Ok(handle) => process.readers.push(handle),
+        Err(e) => error!("Failed to start merged output thread: {}", e),
+    }
+}
+
+fn process_segment(app: &AppHandle, stream: Stream, segment: Segment, read: usize, line: &str, options: &OutputOptions) {
+    let (bytes, lines) = match stream {
+        Stream::Stdout => (&options.stats.stdout_bytes, &options.stats.stdout_lines),
+        Stream::Stderr => (&options.stats.stderr_bytes, &options.stats.stderr_lines),
+    };
+    bytes.fetch_add(read as u64, Ordering::Relaxed);
+    match segment {
+        Segment::Line => {
+            match stream {
+                Stream::Stdout => options.stats.record_stdout_line(),
+                Stream::Stderr => SessionStats::increment(lines),
+            }
+            handle_line(app, stream, line, options);
+        }
+        // Only the UI sees redraws; the log and buffer get the finished line
+        Segment::Overwrite => {
+            let _ = app.emit_all("runner-overwrite", OutputLine { stream, line, formatted: None });
+        }
+    }
+}
+
+// Splits raw output into segments. `\n` and `\r\n` end a line; what a bare
+// `\r` does depends on the mode.
+struct LineSplitter {
+    mode: CarriageReturnMode,
+    buf: Vec<u8>,
+    // Bytes consumed since the last segment was emitted
+    read: usize,
+    // The last chunk ended in `\r`, so it's not yet known whether `\n` follows
+    pending_cr: bool,
+}
+
+impl LineSplitter {
+    fn new(mode: CarriageReturnMode) -> Self {
+        Self {
+            mode,
+            buf: Vec::new(),
+            read: 0,
+            pending_cr: false,
+        }
+    }
+
+    fn feed(&mut self, chunk: &[u8], emit: &mut impl FnMut(Segment, usize, &str)) {
+        for (i, &byte) in chunk.iter().enumerate() {
+            self.read += 1;
+            if self.pending_cr {
+                self.pending_cr = false;
+                if byte != b'\n' {
+                    // The overwrite (if any) went out when the chunk ended
+                    self.bare_cr(emit, false);
+                }
+            }
+            match byte {
+                b'\n' => self.emit(Segment::Line, emit),
+                b'\r' => match chunk.get(i + 1) {
+                    // `\r\n`; the `\n` ends the line
+                    Some(b'\n') => {}
+                    Some(_) => self.bare_cr(emit, true),
+                    None => {
+                        self.pending_cr = true;
+                        // Don't hold a progress bar back until more output arrives.
+                        // If `\n` follows after all, the line replaces this in the UI.
+                        if self.mode == CarriageReturnMode::Overwrite {
+                            self.emit_overwrite(emit);
+                        }
+                    }
+                },
+                _ => self.buf.push(byte),
+            }
+        }
+    }
+
+    // The pipe closed; emit any unterminated last line.
+    fn finish(&mut self, emit: &mut impl FnMut(Segment, usize, &str)) {
+        if !self.buf.is_empty() || self.read > 0 {
+            self.emit(Segment::Line, emit);
+        }
+    }
+
+    fn bare_cr(&mut self, emit: &mut impl FnMut(Segment, usize, &str), overwrite: bool) {
+        match self.mode {
+            CarriageReturnMode::Keep => self.buf.push(b'\r'),
+            CarriageReturnMode::Last => self.buf.clear(),
+            CarriageReturnMode::Overwrite => {
+                if overwrite {
+                    self.emit_overwrite(emit);
+                }
+                self.buf.clear();
+            }
+        }
+    }
+
+    fn emit_overwrite(&mut self, emit: &mut impl FnMut(Segment, usize, &str)) {
+        if !self.buf.is_empty() {
+            emit(Segment::Overwrite, self.read, &String::from_utf8_lossy(&self.buf));
+            self.read = 0;
+        }
+    }
+
+    fn emit(&mut self, segment: Segment, emit: &mut impl FnMut(Segment, usize, &str)) {
+        emit(segment, self.read, &String::from_utf8_lossy(&self.buf));
+        self.buf.clear();
+        self.read = 0;
+    }
+}
+
+// Read `pipe` until it closes, calling `on_segment` with each line (without its
+// terminator) or redraw and the raw byte count. The pipe closing (EOF, or a
+// broken pipe once the process is gone) is the normal way this ends.
+fn spawn_reader<R, F>(
+    stream: Stream,
+    pipe: R,
+    options: ReaderOptions,
+    mut on_segment: F,
+) -> io::Result<JoinHandle<()>>
+where
+    R: Read + Send + 'static,
+    F: FnMut(Segment, usize, &str) + Send + 'static,
+{
+    thread::Builder::new()
+        .name(format!("runner-{}", stream.name()))
+        .spawn(move || {
+            let mut reader = BufReader::with_capacity(options.read_buffer, pipe);
+            let mut splitter = LineSplitter::new(options.carriage_return);
+            loop {
+                match reader.fill_buf() {
+                    Ok([]) => {
+                        splitter.finish(&mut on_segment);
+                        debug!("Python runner {} closed", stream.name());
+                        break;
+                    }
+                    Ok(chunk) => {
+                        let len = chunk.len();
+                        splitter.feed(chunk, &mut on_segment);
+                        reader.consume(len);
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(e) if e.kind() == io::ErrorKind::BrokenPipe => {
+                        debug!("Python runner {} pipe broke: {}", stream.name(), e);
+                        break;
+                    }
+                    Err(e) => {
+                        warn!("Error reading Python runner {}: {}", stream.name(), e);
+                        break;
+                    }
+                }
+            }
+        })
+}
+
+fn handle_line(app: &AppHandle, stream: Stream, line: &str, options: &OutputOptions) {
+    if let (Stream::Stdout, Some(marker)) = (stream, &options.ready_marker) {
+        if line.contains(marker.as_str()) && !options.ready_marker_seen.swap(true, Ordering::Relaxed) {
+            debug!("Python runner printed ready marker '{}'", marker);
+        }
+    }
+
+    let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f").to_string();
+    let admitted = match &options.throttle {
+        Some(throttle) => {
+            let (admitted, ended_drops, limit) = {
+                let mut throttle = throttle.lock().unwrap();
+                let (admitted, ended_drops) = throttle.admit();
+                (admitted, ended_drops, throttle.limit)
+            };
+            report_drops(app, ended_drops, limit);
+            admitted
+        }
+        None => true,
+    };
+    if !admitted {
+        // Dropped from events and the log file, but the buffers keep the latest
+        // lines and watch patterns still see them
+        SessionStats::increment(&options.stats.dropped_lines);
+        let log_line = LogLine {
+            timestamp,
+            stream,
+            line: line.to_string(),
+        };
+        if matches!(stream, Stream::Stderr) {
+            options.stderr_buffer.lock().unwrap().push(log_line.clone());
+        }
+        options.log_buffer.lock().unwrap().push(log_line);
+        match_watch_patterns(app, stream, line, options);
+        return;
+    }
+
+    let (formatted, custom_format) = {
+        let log_format = options.log_format.lock().unwrap();
+        let formatted = log_format.format(&timestamp, line_level(stream, line), stream.name(), line);
+        (formatted, !log_format.is_default())
+    };
+
+    let event = if options.merge_output {
+        "runner-output".to_string()
+    } else {
+        format!("runner-{}", stream.name())
+    };
+    let _ = app.emit_all(
+        &event,
+        OutputLine {
+            stream,
+            line,
+            formatted: Some(formatted.clone()).filter(|_| custom_format),
+        },
+    );
+    if matches!(stream, Stream::Stderr) && system_log::forward_stderr() {
+        system_log::write(Level::Warning, line);
+    }
+
+    let log_line = LogLine {
+        timestamp: timestamp.clone(),
+        stream,
+        line: line.to_string(),
+    };
+    if matches!(stream, Stream::Stderr) {
+        options.stderr_buffer.lock().unwrap().push(log_line.clone());
+    }
+    options.log_buffer.lock().unwrap().push(log_line);
+
+    let to_file = match stream {
+        Stream::Stdout => options.log_stdout_to_file,
+        Stream::Stderr => options.log_stderr_to_file,
+    };
+    if to_file {
+        write_log_file(app, &options.log_file, &formatted);
+    }
+
+    match_watch_patterns(app, stream, line, options);
+}
+
+fn match_watch_patterns(app: &AppHandle, stream: Stream, line: &str, options: &OutputOptions) {
+    for matcher in &options.watch_patterns {
+        if !matcher.is_match(line) {
+            continue;
+        }
+        let pattern = matcher.pattern();
+        warn!("Python runner output matched watch pattern '{}': {}", pattern, line);
+        let _ = app.emit_all("pattern-matched", PatternMatch { pattern, stream, line });
+
+        if options.notify_on_pattern {
+            let suppressed = options.notify_limiter.lock().unwrap().admit(pattern);
+            if let Some(suppressed) = suppressed {
+                let body = match suppressed {
+                    0 => line.to_string(),
+                    n => format!("{}\n({} more matches since the last notification)", line, n),
+                };
+                if let Err(e) = notify::alert(app, &format!("Oriphim Runner: matched '{}'", pattern), &body) {
+                    warn!("{}", e);
+                }
+            }
+        }
+    }
+}
+
+// The level a Python logging line was printed at, from an uppercase level name
+// among its first few words; otherwise by stream.
+fn line_level(stream: Stream, line: &str) -> &'static str {
+    const LEVELS: &[&str] = &["DEBUG", "INFO", "WARNING", "ERROR", "CRITICAL"];
+    let detected = line
+        .split(|c: char| !c.is_ascii_alphabetic())
+        .filter(|word| !word.is_empty())
+        .take(8)
+        .find_map(|word| match word {
+            "WARN" => Some("WARNING"),
+            "FATAL" => Some("CRITICAL"),
+            word => LEVELS.iter().find(|&&level| level == word).copied(),
+        });
+    detected.unwrap_or(match stream {
+        Stream::Stdout => "INFO",
+        Stream::Stderr => "WARNING",
+    })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::process::{Command, Stdio};
+    use std::sync::mpsc;
+    use std::time::{Duration, Instant};
+
+    use crate::process;
+
+    #[test]
+    fn readers_terminate_when_process_is_killed() {
+        let mut process = process::spawn(
+            Command::new("sh")
+                .arg("-c")
+                .arg("echo ready; echo oops >&2; exec sleep 30")
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped()),
+        )
+        .unwrap();
+
+        let (tx, rx) = mpsc::channel();
+        let stdout = process.child.stdout.take().unwrap();
+        let stderr = process.child.stderr.take().unwrap();
+        let stdout_tx = tx.clone();
+        let options = ReaderOptions {
+            carriage_return: CarriageReturnMode::Keep,
+            read_buffer: LINE_READ_BUFFER,
+        };
+        process.readers.push(
+            spawn_reader(Stream::Stdout, stdout, options, move |_, _, line| {
+                stdout_tx.send(line.to_string()).unwrap()
+            })
+            .unwrap(),
+        );
+        process.readers.push(
+            spawn_reader(Stream::Stderr, stderr, options, move |_, _, line| {
+                tx.send(line.to_string()).unwrap()
+            })
+            .unwrap(),
+        );
+
+        let mut lines = vec![
+            rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+            rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+        ];
+        lines.sort();
+        assert_eq!(lines, ["oops", "ready"]);
+
+        process.kill(true).unwrap();
+        let started = Instant::now();
+        let joined = tauri::async_runtime::block_on(process.join_readers(Duration::from_secs(2)));
+        assert!(joined, "reader threads still running after the process was killed");
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert!(process.readers.is_empty());
+        assert!(process.child.stdin.is_none());
+    }
+}
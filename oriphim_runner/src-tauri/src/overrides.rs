@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::AppHandle;
+use log::info;
+
+use crate::RunnerState;
+
+// A temporary variant of the config for one launch, e.g. to compare two
+// settings back to back. Never saved; the next normal start drops it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConfigOverrides {
+    // Shown in the status as the active variant, e.g. "B"
+    pub variant: String,
+    // Replaces `script_args` when set
+    pub script_args: Option<Vec<String>>,
+    // Added to the process environment, over the env_bootstrap variables
+    pub env: BTreeMap<String, String>,
+}
+
+impl ConfigOverrides {
+    fn validate(&self) -> Result<(), String> {
+        if self.variant.trim().is_empty() {
+            return Err("Overrides need a variant name".to_string());
+        }
+        if let Some(name) = self.env.keys().find(|name| name.is_empty() || name.contains('=') || name.contains('\0')) {
+            return Err(format!("Invalid environment variable name '{}'", name));
+        }
+        Ok(())
+    }
+}
+
+// Start the runner with `overrides` on top of the saved config, replacing any
+// running process. Automatic restarts keep the variant; the next normal start
+// goes back to the saved config.
+#[tauri::command]
+pub async fn launch_with_overrides(
+    overrides: ConfigOverrides,
+    app: AppHandle,
+    state: tauri::State<'_, RunnerState>,
+) -> Result<String, String> {
+    overrides.validate()?;
+    info!("Launching Python runner as variant '{}'", overrides.variant);
+    *state.overrides.lock().unwrap() = Some(overrides);
+    crate::begin_start(&app, &state, false).await
+}
@@ -0,0 +1,157 @@
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+use crate::config::RunnerConfig;
+
+// The bundled Python sources, relative to the app's working or resource directory
+const PYTHON_SRC_DIR: &str = "src";
+
+// Project directory selected with `set_workspace`, if any
+static WORKSPACE: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+pub fn workspace() -> Option<PathBuf> {
+    WORKSPACE.lock().unwrap().clone()
+}
+
+pub fn set_workspace(dir: Option<PathBuf>) {
+    *WORKSPACE.lock().unwrap() = dir;
+}
+
+// Everything the runner persists lives under ~/.oriphim, or under
+// `<workspace>/.oriphim` for the files scoped to the active workspace.
+pub fn oriphim_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".oriphim"))
+}
+
+fn data_dir() -> Option<PathBuf> {
+    match workspace() {
+        Some(workspace) => Some(workspace.join(".oriphim")),
+        None => oriphim_dir(),
+    }
+}
+
+// The workspace's config.toml if it has one, else the global one.
+pub fn config_file() -> Option<PathBuf> {
+    if let Some(workspace) = workspace() {
+        let file = workspace.join(".oriphim").join("config.toml");
+        if file.is_file() {
+            return Some(file);
+        }
+    }
+    oriphim_dir().map(|dir| dir.join("config.toml"))
+}
+
+pub fn state_file() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("state.json"))
+}
+
+// Remembers the active workspace between launches
+pub fn workspace_file() -> Option<PathBuf> {
+    oriphim_dir().map(|dir| dir.join("workspace"))
+}
+
+// Local control interface, one per user regardless of workspace
+#[cfg(unix)]
+pub fn control_socket() -> Option<PathBuf> {
+    oriphim_dir().map(|dir| dir.join("control.sock"))
+}
+
+// Where a second launch finds the running instance, one per user
+#[cfg(unix)]
+pub fn instance_socket() -> Option<PathBuf> {
+    oriphim_dir().map(|dir| dir.join("instance.sock"))
+}
+
+pub fn window_state_file() -> Option<PathBuf> {
+    oriphim_dir().map(|dir| dir.join("window.json"))
+}
+
+// Append-only record of starts, stops and crashes
+pub fn lifecycle_file() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("lifecycle.jsonl"))
+}
+
+// Sampled runner metrics kept by `metrics_history`
+pub fn metrics_history_file() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("metrics.jsonl"))
+}
+
+// Output of `crash_dump_command`, one directory per crash
+pub fn crashes_dir() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("crashes"))
+}
+
+// Saved `start_recording_stdin` sessions for `replay_stdin`
+pub fn recordings_dir() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("recordings"))
+}
+
+pub fn logs_dir() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("logs"))
+}
+
+// Script arguments too long for the command line, one per line
+pub fn args_file() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("script-args.txt"))
+}
+
+// Whether this is an installed bundle rather than `cargo tauri dev` or another
+// build run from the source tree: a release build whose executable isn't under
+// a cargo `target` directory.
+pub fn is_packaged() -> bool {
+    if cfg!(debug_assertions) {
+        return false;
+    }
+    match env::current_exe() {
+        Ok(exe) => !exe.ancestors().any(|dir| dir.ends_with("target")),
+        Err(_) => true,
+    }
+}
+
+// Directory the Python process runs in, as an absolute path:
+// `working_dir` from the config (relative paths are taken from the workspace,
+// or the app's current directory without one), else the bundled `src` resource
+// when packaged or `src` in the current directory when running from a checkout,
+// falling back to the other if it doesn't exist.
+pub fn working_dir(app: &AppHandle, config: &RunnerConfig) -> PathBuf {
+    let cwd = env::current_dir().unwrap_or_default();
+    if let Some(dir) = &config.working_dir {
+        return absolute(&workspace().unwrap_or_else(|| cwd.clone()).join(dir));
+    }
+
+    let dev_dir = cwd.join(PYTHON_SRC_DIR);
+    let resource_dir = app
+        .path_resolver()
+        .resolve_resource(PYTHON_SRC_DIR)
+        .filter(|dir| dir.is_dir());
+    if is_packaged() {
+        if let Some(resource_dir) = &resource_dir {
+            return absolute(resource_dir);
+        }
+    }
+    if dev_dir.is_dir() {
+        return absolute(&dev_dir);
+    }
+    match resource_dir {
+        Some(resource_dir) => absolute(&resource_dir),
+        None => dev_dir,
+    }
+}
+
+// The Python sources shipped with the app: the `src` resource, or `src` in the
+// current directory when running from a checkout without one.
+pub fn bundled_src_dir(app: &AppHandle) -> Option<PathBuf> {
+    let resource_dir = app.path_resolver().resolve_resource(PYTHON_SRC_DIR).filter(|dir| dir.is_dir());
+    if resource_dir.is_some() || is_packaged() {
+        return resource_dir.map(|dir| absolute(&dir));
+    }
+    let dev_dir = env::current_dir().ok()?.join(PYTHON_SRC_DIR);
+    dev_dir.is_dir().then(|| absolute(&dev_dir))
+}
+
+// Resolve `..` and symlinks when the path exists
+fn absolute(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
@@ -0,0 +1,583 @@
+use chrono::{DateTime, Local, TimeZone};
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::io;
+use std::path::PathBuf;
+use std::process::{Child, Command, ExitStatus};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use sysinfo::{Pid, ProcessStatus, System};
+use log::{debug, error, info, warn};
+
+use crate::config::{self, RunnerConfig, StopSignal};
+use crate::keep_awake::SleepInhibitor;
+
+// Printed between the bootstrap command and the environment dump so anything the
+// bootstrap itself echoes (conda banners, etc.) is never parsed as a variable.
+const ENV_MARKER: &str = "__ORIPHIM_ENV__";
+
+// Run the configured bootstrap command in a shell (`sh` on Unix, so `. file`
+// rather than bash's `source file`; `cmd` on Windows) and return the variables
+// it exported that differ from our own environment.
+pub fn bootstrap_env(bootstrap: &str) -> Result<HashMap<String, String>, String> {
+    info!("Running env bootstrap: {}", bootstrap);
+
+    #[cfg(windows)]
+    let output = Command::new("cmd")
+        .arg("/C")
+        .arg(format!("{} && echo {} && set", bootstrap, ENV_MARKER))
+        .output();
+
+    #[cfg(not(windows))]
+    let output = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} && echo {} && env", bootstrap, ENV_MARKER))
+        .output();
+
+    let output = output.map_err(|e| format!("Failed to run env bootstrap: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Env bootstrap exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let dump = match stdout.split_once(ENV_MARKER) {
+        Some((_, dump)) => dump,
+        None => return Err("Env bootstrap produced no environment output".to_string()),
+    };
+
+    let imported: HashMap<String, String> = parse_env_dump(dump)
+        .into_iter()
+        .filter(|(key, value)| env::var(key).ok().as_deref() != Some(value.as_str()))
+        .collect();
+
+    let mut names: Vec<&str> = imported.keys().map(String::as_str).collect();
+    names.sort_unstable();
+    info!("Env bootstrap imported {} variables: {}", names.len(), names.join(", "));
+    for (key, value) in &imported {
+        let value = if config::is_secret_name(key) { config::REDACTED } else { value.as_str() };
+        debug!("  {}={}", key, value);
+    }
+
+    Ok(imported)
+}
+
+// Parse `env`/`set` output. Values may span several lines, so a line that doesn't
+// start with a valid variable name is treated as a continuation of the previous value.
+fn parse_env_dump(dump: &str) -> HashMap<String, String> {
+    let mut vars = HashMap::new();
+    let mut current: Option<(String, String)> = None;
+
+    for line in dump.lines() {
+        let line = line.trim_end_matches('\r');
+        let assignment = line.split_once('=').filter(|(key, _)| is_var_name(key));
+        match assignment {
+            Some((key, value)) => {
+                if let Some((key, value)) = current.take() {
+                    vars.insert(key, value);
+                }
+                current = Some((key.to_string(), value.to_string()));
+            }
+            None => {
+                if let Some((_, value)) = current.as_mut() {
+                    value.push('\n');
+                    value.push_str(line);
+                }
+            }
+        }
+    }
+    if let Some((key, value)) = current {
+        vars.insert(key, value);
+    }
+
+    vars
+}
+
+fn is_var_name(name: &str) -> bool {
+    // Windows allows names like `ProgramFiles(x86)`, so be lenient beyond the first char.
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '(' | ')'))
+}
+
+// A spawned Python process plus whatever we need to take down its descendants.
+// On Unix the child leads its own process group; on Windows it is placed in a
+// Job Object, which `kill_tree` terminates. Neither outlives a stop on its own
+// account: the runner keeps running if the app exits without stopping it.
+pub struct ManagedProcess {
+    pub child: Child,
+    // Unique per spawn, unlike PIDs which the OS may reuse
+    pub run_id: u64,
+    pub started_at: Instant,
+    // The same moment by the wall clock, to compare with the OS-reported start time
+    pub spawned_at: DateTime<Local>,
+    // What was run, for error reports
+    pub command_line: String,
+    // The environment it was given: ours plus the command's overrides
+    pub environment: BTreeMap<String, String>,
+    // What the OS reported for our PID right after spawning
+    identity: Option<ProcessIdentity>,
+    // Held while `keep_awake` is on; released when the process is dropped
+    sleep_inhibitor: Option<SleepInhibitor>,
+    // stdout/stderr reader threads, which finish once the pipes close
+    pub readers: Vec<JoinHandle<()>>,
+    // Set by the stdout reader once `ready_marker` shows up
+    pub ready_marker_seen: Arc<AtomicBool>,
+    // Set when `heartbeat_file` is configured
+    pub heartbeat_file: Option<PathBuf>,
+    // Where stdout/stderr go when `output_redirect` is configured
+    pub output_redirect: Option<PathBuf>,
+    // Stderr buffer sequence number of this process's first stderr line
+    pub stderr_start: u64,
+    // close_stdin was called; stdin stays closed even if a write had it checked out
+    pub stdin_closed: bool,
+    // The config this process was started with, to tell which later changes need a restart
+    pub started_config: Option<RunnerConfig>,
+    // Name of the `launch_with_overrides` variant this process runs, if any
+    pub variant: Option<String>,
+    // Started armed by `arm_crash_debug`, with extra diagnostics
+    pub crash_debug: bool,
+    #[cfg(windows)]
+    job: Option<windows_job::Job>,
+}
+
+// How long to wait for the output readers to drain once the process is gone
+pub const READER_JOIN_TIMEOUT: Duration = Duration::from_secs(2);
+
+static NEXT_RUN_ID: AtomicU64 = AtomicU64::new(1);
+
+pub fn spawn(command: &mut Command) -> io::Result<ManagedProcess> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    #[cfg(windows)]
+    {
+        // Its own console process group, so CTRL_BREAK can target just the runner
+        use std::os::windows::process::CommandExt;
+        use windows_sys::Win32::System::Threading::CREATE_NEW_PROCESS_GROUP;
+        command.creation_flags(CREATE_NEW_PROCESS_GROUP);
+    }
+
+    let child = command.spawn()?;
+    let spawned_at = Local::now();
+    let identity = ProcessIdentity::of(child.id());
+    if identity.is_none() {
+        warn!("Could not record identity of Python runner (PID {})", child.id());
+    }
+
+    #[cfg(windows)]
+    let job = match windows_job::Job::assign(&child) {
+        Ok(job) => Some(job),
+        Err(e) => {
+            warn!("Could not attach Python runner to a job object, only the parent will be stopped: {}", e);
+            None
+        }
+    };
+
+    Ok(ManagedProcess {
+        child,
+        run_id: NEXT_RUN_ID.fetch_add(1, Ordering::Relaxed),
+        started_at: Instant::now(),
+        spawned_at,
+        command_line: command_line(command),
+        environment: environment(command),
+        identity,
+        sleep_inhibitor: None,
+        readers: Vec::new(),
+        ready_marker_seen: Arc::new(AtomicBool::new(false)),
+        heartbeat_file: None,
+        output_redirect: None,
+        started_config: None,
+        variant: None,
+        crash_debug: false,
+        stderr_start: 0,
+        stdin_closed: false,
+        #[cfg(windows)]
+        job,
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChildState {
+    Running,
+    // Exited but not yet reaped with wait()
+    Zombie,
+    // Gone from the OS process table
+    Exited,
+}
+
+// Start time and executable of a PID, used to tell our process apart from an
+// unrelated one that was later given the same PID.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProcessIdentity {
+    // Seconds since the epoch
+    start_time: u64,
+    exe: Option<PathBuf>,
+}
+
+impl ProcessIdentity {
+    fn of(pid: u32) -> Option<Self> {
+        let mut system = System::new();
+        let pid = Pid::from_u32(pid);
+        if !system.refresh_process(pid) {
+            return None;
+        }
+        system.process(pid).map(|process| Self {
+            start_time: process.start_time(),
+            exe: process.exe().map(|exe| exe.to_path_buf()),
+        })
+    }
+
+    // Whether `pid` is still the process this identity was recorded from.
+    pub fn is_alive(&self, pid: u32) -> bool {
+        Self::of(pid).is_some_and(|current| {
+            current.start_time == self.start_time
+                && (self.exe.is_none() || current.exe.is_none() || current.exe == self.exe)
+        })
+    }
+
+    pub fn start_time(&self) -> Option<DateTime<Local>> {
+        to_local(self.start_time)
+    }
+}
+
+fn to_local(epoch_secs: u64) -> Option<DateTime<Local>> {
+    Local.timestamp_opt(epoch_secs as i64, 0).single()
+}
+
+// What a child spawned from `command` inherits, with the command's own
+// variables applied on top.
+pub fn environment(command: &Command) -> BTreeMap<String, String> {
+    let mut vars: BTreeMap<String, String> = env::vars_os()
+        .map(|(name, value)| (name.to_string_lossy().to_string(), value.to_string_lossy().to_string()))
+        .collect();
+    for (name, value) in command.get_envs() {
+        let name = name.to_string_lossy().to_string();
+        match value {
+            Some(value) => vars.insert(name, value.to_string_lossy().to_string()),
+            None => vars.remove(&name),
+        };
+    }
+    vars
+}
+
+// `program arg1 arg2 (in dir)`. Built by hand because Command's Debug output
+// includes the environment, which may hold secrets from the bootstrap.
+pub fn command_line(command: &Command) -> String {
+    let mut line = command.get_program().to_string_lossy().to_string();
+    for arg in command.get_args() {
+        line.push(' ');
+        line.push_str(&arg.to_string_lossy());
+    }
+    if let Some(dir) = command.get_current_dir() {
+        line.push_str(&format!(" (in {})", dir.display()));
+    }
+    line
+}
+
+impl ManagedProcess {
+    // Hold or release the sleep inhibitor for this process.
+    pub fn set_keep_awake(&mut self, enabled: bool) -> io::Result<()> {
+        if !enabled {
+            if self.sleep_inhibitor.take().is_some() {
+                info!("Allowing system sleep again");
+            }
+        } else if self.sleep_inhibitor.is_none() {
+            self.sleep_inhibitor = Some(SleepInhibitor::acquire(self.child.id())?);
+            info!("Preventing system sleep while the Python runner is active");
+        }
+        Ok(())
+    }
+
+    pub fn keeps_awake(&self) -> bool {
+        self.sleep_inhibitor.is_some()
+    }
+
+    // Swap in `inhibitor` and hand back the old one, so the caller can drop it
+    // (which kills and waits for its helper) outside the process lock.
+    pub fn replace_sleep_inhibitor(&mut self, inhibitor: Option<SleepInhibitor>) -> Option<SleepInhibitor> {
+        std::mem::replace(&mut self.sleep_inhibitor, inhibitor)
+    }
+
+    // Check that our PID still refers to the process we spawned before acting
+    // on it: it must exist with the start time and executable recorded at
+    // spawn. Processes whose identity couldn't be recorded are trusted.
+    pub fn verify_owned_process(&self) -> Result<(), String> {
+        let recorded = match &self.identity {
+            Some(identity) => identity,
+            None => return Ok(()),
+        };
+        let pid = self.child.id();
+        let current = ProcessIdentity::of(pid).ok_or_else(|| format!("Process {} no longer exists", pid))?;
+        if current.start_time != recorded.start_time {
+            return Err(format!("PID {} now belongs to a different process (start time changed)", pid));
+        }
+        if recorded.exe.is_some() && current.exe.is_some() && current.exe != recorded.exe {
+            return Err(format!("PID {} now belongs to a different program", pid));
+        }
+        Ok(())
+    }
+
+    // Start time the OS reported for our PID right after spawning.
+    pub fn recorded_start_time(&self) -> Option<DateTime<Local>> {
+        self.identity.as_ref().and_then(ProcessIdentity::start_time)
+    }
+
+    pub fn identity(&self) -> Option<&ProcessIdentity> {
+        self.identity.as_ref()
+    }
+
+    // Start time the OS reports for our PID now; differs from the recorded one
+    // if the PID was reused.
+    pub fn os_start_time(&self) -> Option<DateTime<Local>> {
+        ProcessIdentity::of(self.child.id()).and_then(|identity| to_local(identity.start_time))
+    }
+
+    // Group ID shared by the child and everything it spawns. Windows has no
+    // equivalent; descendants are tracked by the job object instead.
+    pub fn process_group_id(&self) -> Option<u32> {
+        #[cfg(unix)]
+        {
+            Some(self.child.id())
+        }
+        #[cfg(not(unix))]
+        {
+            None
+        }
+    }
+
+    // Close stdin so the process reads EOF. Returns false if it was already closed.
+    pub fn close_stdin(&mut self) -> bool {
+        let was_open = !self.stdin_closed;
+        self.stdin_closed = true;
+        self.child.stdin.take();
+        was_open
+    }
+
+    // Ask the process to shut down. With `tree` set on Unix the signal goes to
+    // the whole process group. Windows has no signals; CTRL_BREAK delivered to
+    // the runner's console process group is the closest equivalent.
+    #[cfg(unix)]
+    pub fn send_stop_signal(&self, signal: StopSignal, tree: bool) -> io::Result<()> {
+        let signal = match signal {
+            StopSignal::Term => libc::SIGTERM,
+            StopSignal::Int => libc::SIGINT,
+            StopSignal::Quit => libc::SIGQUIT,
+        };
+        let pid = self.child.id() as libc::pid_t;
+        let result = unsafe {
+            if tree {
+                libc::killpg(pid, signal)
+            } else {
+                libc::kill(pid, signal)
+            }
+        };
+        if result == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+
+    // The event only reaches processes on the caller's console, and the app has
+    // none of its own when built as a GUI app, so it attaches to the runner's
+    // console for the duration. A debug build already has a console, which the
+    // runner shares, so it sends directly.
+    #[cfg(windows)]
+    pub fn send_stop_signal(&self, _signal: StopSignal, _tree: bool) -> io::Result<()> {
+        use std::sync::Mutex;
+        use windows_sys::Win32::Foundation::ERROR_ACCESS_DENIED;
+        use windows_sys::Win32::System::Console::{AttachConsole, FreeConsole, GenerateConsoleCtrlEvent, CTRL_BREAK_EVENT};
+
+        // Console attachment is per process, so one stop at a time
+        static CONSOLE: Mutex<()> = Mutex::new(());
+        let _console = CONSOLE.lock().unwrap();
+        let pid = self.child.id();
+        let attached = unsafe { AttachConsole(pid) } != 0;
+        if !attached {
+            let error = io::Error::last_os_error();
+            if error.raw_os_error() != Some(ERROR_ACCESS_DENIED as i32) {
+                warn!("Graceful stop is unavailable: could not attach to the runner's console: {}", error);
+                return Err(io::Error::new(io::ErrorKind::Unsupported, format!("no console to send CTRL_BREAK to: {}", error)));
+            }
+            // ERROR_ACCESS_DENIED: already on a console, which the runner inherited
+        }
+        let result = if unsafe { GenerateConsoleCtrlEvent(CTRL_BREAK_EVENT, pid) } == 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        };
+        if attached {
+            unsafe { FreeConsole() };
+        }
+        result
+    }
+
+    // Poll for exit until `timeout` elapses, reaping the process if it exits.
+    pub async fn wait_timeout(&mut self, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(status) = self.child.try_wait()? {
+                return Ok(Some(status));
+            }
+            if Instant::now() >= deadline {
+                return Ok(None);
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    }
+
+    // Kill the process (and, if `tree` is set, all of its descendants), then reap it.
+    pub fn kill(&mut self, tree: bool) -> io::Result<()> {
+        if tree {
+            if let Err(e) = self.kill_tree() {
+                warn!("Failed to kill process tree, falling back to parent only: {}", e);
+                self.child.kill()?;
+            }
+        } else {
+            self.child.kill()?;
+        }
+        self.child.wait()?;
+        Ok(())
+    }
+
+    // Close stdin and wait for the reader threads to drain the remaining output,
+    // giving up after `timeout`. A descendant that inherited the pipes can keep
+    // them open after the runner exits; those threads are detached with a warning.
+    // Returns whether every thread finished.
+    pub async fn join_readers(&mut self, timeout: Duration) -> bool {
+        self.child.stdin.take();
+
+        let deadline = Instant::now() + timeout;
+        while self.readers.iter().any(|reader| !reader.is_finished()) && Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+
+        let mut all_finished = true;
+        for reader in self.readers.drain(..) {
+            if reader.is_finished() {
+                let _ = reader.join();
+            } else {
+                warn!("Output reader thread still running {}s after the runner exited", timeout.as_secs());
+                all_finished = false;
+            }
+        }
+        all_finished
+    }
+
+    // Whether the child is alive, or dead and waiting to be reaped, without
+    // reaping it; the supervisor does that on its next poll.
+    pub fn child_state(&self) -> ChildState {
+        let mut system = System::new();
+        let pid = Pid::from_u32(self.child.id());
+        if !system.refresh_process(pid) {
+            return ChildState::Exited;
+        }
+        match system.process(pid).map(|process| process.status()) {
+            Some(ProcessStatus::Zombie) => ChildState::Zombie,
+            Some(_) => ChildState::Running,
+            None => ChildState::Exited,
+        }
+    }
+
+    // Make sure a process we're letting go of is reaped. A failed kill (an
+    // elevated child we may not signal, say) leaves it running, so wait for it
+    // on a thread rather than leaving a zombie behind once it does exit.
+    pub fn reap_in_background(mut self) {
+        match self.child.try_wait() {
+            Ok(Some(_)) => {}
+            Ok(None) => {
+                let pid = self.child.id();
+                warn!("Python runner (PID {}) is still running; reaping it once it exits", pid);
+                let result = thread::Builder::new().name("runner-reaper".to_string()).spawn(move || {
+                    match self.child.wait() {
+                        Ok(status) => debug!("Reaped Python runner (PID {}): {}", pid, status),
+                        Err(e) => warn!("Failed to reap Python runner (PID {}): {}", pid, e),
+                    }
+                });
+                if let Err(e) = result {
+                    error!("Failed to start reaper thread for PID {}: {}", pid, e);
+                }
+            }
+            Err(e) => warn!("Failed to check whether the Python runner exited: {}", e),
+        }
+    }
+
+    #[cfg(unix)]
+    pub fn kill_tree(&mut self) -> io::Result<()> {
+        let pgid = self.child.id() as libc::pid_t;
+        if unsafe { libc::killpg(pgid, libc::SIGKILL) } == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+
+    #[cfg(windows)]
+    pub fn kill_tree(&mut self) -> io::Result<()> {
+        match &self.job {
+            Some(job) => job.terminate(),
+            None => self.child.kill(),
+        }
+    }
+}
+
+#[cfg(windows)]
+mod windows_job {
+    use std::io;
+    use std::os::windows::io::AsRawHandle;
+    use std::process::Child;
+    use windows_sys::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows_sys::Win32::System::JobObjects::{
+        AssignProcessToJobObject, CreateJobObjectW, TerminateJobObject,
+    };
+
+    pub struct Job(HANDLE);
+
+    // The job handle is only ever used behind the RunnerState mutex.
+    unsafe impl Send for Job {}
+
+    impl Job {
+        pub fn assign(child: &Child) -> io::Result<Self> {
+            unsafe {
+                let handle = CreateJobObjectW(std::ptr::null(), std::ptr::null());
+                if handle == 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                let job = Job(handle);
+                // No JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE: closing the handle leaves the
+                // tree running, so kill_process_tree = false only ends the parent and
+                // a runner survives an app crash to be reconnected to, as on Unix
+                if AssignProcessToJobObject(job.0, child.as_raw_handle() as HANDLE) == 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(job)
+            }
+        }
+
+        pub fn terminate(&self) -> io::Result<()> {
+            if unsafe { TerminateJobObject(self.0, 1) } == 0 {
+                Err(io::Error::last_os_error())
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    impl Drop for Job {
+        fn drop(&mut self) {
+            unsafe {
+                CloseHandle(self.0);
+            }
+        }
+    }
+}
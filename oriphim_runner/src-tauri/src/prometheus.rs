@@ -0,0 +1,123 @@
+use std::fmt::Write as _;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use log::{debug, error, info, warn};
+
+use crate::readiness::Health;
+use crate::RunnerState;
+
+// Scrapers send a short GET; anything longer or slower is dropped
+const MAX_REQUEST_BYTES: usize = 8192;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+fn metric(out: &mut String, name: &str, kind: &str, help: &str, value: impl std::fmt::Display) {
+    let _ = writeln!(out, "# HELP oriphim_runner_{} {}", name, help);
+    let _ = writeln!(out, "# TYPE oriphim_runner_{} {}", name, kind);
+    let _ = writeln!(out, "oriphim_runner_{} {}", name, value);
+}
+
+// The runner's state in the Prometheus text exposition format. Counters are
+// for this app session, like `get_session_stats`.
+fn render(state: &RunnerState) -> String {
+    let (up, uptime) = {
+        let process_guard = state.python_process.lock().unwrap();
+        let uptime = process_guard.as_ref().map(|process| process.started_at.elapsed().as_secs_f64());
+        (process_guard.is_some(), uptime)
+    };
+    let healthy = *state.health.lock().unwrap() == Health::Ready;
+    let metrics = state.metrics.lock().unwrap().clone();
+    let stats = state.stats.snapshot();
+
+    let mut out = String::new();
+    metric(&mut out, "up", "gauge", "Whether the Python runner process is running.", u8::from(up));
+    metric(&mut out, "ready", "gauge", "Whether the runner passed its readiness check.", u8::from(up && healthy));
+    metric(&mut out, "uptime_seconds", "gauge", "Seconds since the running process started.", uptime.unwrap_or(0.0));
+    metric(&mut out, "starts_total", "counter", "Processes started.", stats.starts);
+    metric(&mut out, "restarts_total", "counter", "Restarts, automatic or replacing a running process.", stats.restarts);
+    metric(&mut out, "crashes_total", "counter", "Exits with a failure status.", stats.crashes);
+    metric(&mut out, "stops_total", "counter", "Stops requested from the app.", stats.stops);
+    if let Some(metrics) = metrics.filter(|_| up) {
+        metric(&mut out, "memory_bytes", "gauge", "Resident memory of the runner process.", metrics.memory_bytes);
+        metric(&mut out, "cpu_percent", "gauge", "CPU usage, as a percent of one core.", metrics.cpu_percent);
+        if let Some(threads) = metrics.thread_count {
+            metric(&mut out, "threads", "gauge", "Threads in the runner process.", threads);
+        }
+        if let Some(handles) = metrics.handle_count {
+            metric(&mut out, "open_handles", "gauge", "Open file descriptors or handles.", handles);
+        }
+    }
+    metric(&mut out, "peak_memory_bytes", "gauge", "Highest memory seen this session.", stats.peak_memory_bytes);
+    let _ = writeln!(out, "# HELP oriphim_runner_output_lines_total Output lines read, by stream.");
+    let _ = writeln!(out, "# TYPE oriphim_runner_output_lines_total counter");
+    let _ = writeln!(out, "oriphim_runner_output_lines_total{{stream=\"stdout\"}} {}", stats.stdout_lines);
+    let _ = writeln!(out, "oriphim_runner_output_lines_total{{stream=\"stderr\"}} {}", stats.stderr_lines);
+    metric(&mut out, "dropped_lines_total", "counter", "Output lines dropped by output_max_lines_per_sec.", stats.dropped_lines);
+    out
+}
+
+// Read the request head and answer it. Only `GET /metrics` is served.
+async fn serve(app: AppHandle, mut stream: TcpStream) {
+    let mut request = Vec::new();
+    let mut chunk = [0u8; 1024];
+    let read = tokio::time::timeout(REQUEST_TIMEOUT, async {
+        while !request.windows(4).any(|window| window == b"\r\n\r\n") && request.len() < MAX_REQUEST_BYTES {
+            match stream.read(&mut chunk).await {
+                Ok(0) | Err(_) => return false,
+                Ok(n) => request.extend_from_slice(&chunk[..n]),
+            }
+        }
+        true
+    })
+    .await;
+    if read != Ok(true) {
+        debug!("Dropped an incomplete metrics request");
+        return;
+    }
+
+    let head = String::from_utf8_lossy(&request);
+    let mut parts = head.lines().next().unwrap_or_default().split_whitespace();
+    let (method, path) = (parts.next().unwrap_or_default(), parts.next().unwrap_or_default());
+    let path = path.split('?').next().unwrap_or_default();
+    let (status, content_type, body) = match (method, path) {
+        ("GET", "/metrics") => ("200 OK", "text/plain; version=0.0.4", render(&app.state::<RunnerState>())),
+        ("GET", _) => ("404 Not Found", "text/plain", "Not found; metrics are at /metrics\n".to_string()),
+        _ => ("405 Method Not Allowed", "text/plain", "Only GET is supported\n".to_string()),
+    };
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    if let Err(e) = stream.write_all(response.as_bytes()).await {
+        debug!("Failed to answer metrics request: {}", e);
+    }
+}
+
+// Serve `GET /metrics` on `bind`:`port` for Prometheus to scrape.
+pub fn start(app: AppHandle, bind: IpAddr, port: u16) {
+    tauri::async_runtime::spawn(async move {
+        let address = SocketAddr::new(bind, port);
+        let listener = match TcpListener::bind(address).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                error!("Failed to serve metrics on {}: {}", address, e);
+                return;
+            }
+        };
+        info!("Serving Prometheus metrics on http://{}/metrics", address);
+
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tauri::async_runtime::spawn(serve(app.clone(), stream));
+                }
+                Err(e) => warn!("Metrics accept failed: {}", e),
+            }
+        }
+    });
+}
@@ -0,0 +1,390 @@
+use serde::Serialize;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use log::{info, warn};
+
+use crate::config::{ReadinessProbe, RunnerConfig};
+use crate::RunnerState;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(200);
+// With no marker or port configured, a process that survives this long counts as ready
+const SETTLE_TIME: Duration = Duration::from_secs(2);
+// Lets the readers catch up on the last stderr lines of a process that exited early
+const STDERR_DRAIN_TIME: Duration = Duration::from_millis(250);
+// stderr lines included when the process exits before becoming ready
+const STDERR_TAIL_LINES: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Health {
+    Stopped,
+    // Running, readiness not yet confirmed
+    Starting,
+    Ready,
+    // Crashed, or never became ready
+    Failed,
+}
+
+#[derive(Clone, Serialize)]
+struct StartFailedEvent {
+    error: String,
+    // Set when the process exited during the wait
+    exit_code: Option<i32>,
+    stderr_tail: Vec<String>,
+}
+
+// What the process was doing at one readiness poll
+enum Observation {
+    Running { marker_seen: bool },
+    Exited(ExitStatus),
+    // Stopped or replaced; whoever did that owns the health now
+    Gone,
+}
+
+#[derive(Debug)]
+enum NotReady {
+    Gone,
+    Exited(ExitStatus),
+    TimedOut(String),
+}
+
+// The checks a process must pass before it counts as ready, copied from the
+// config at start so edits don't affect a wait in progress.
+#[derive(Clone)]
+pub struct ReadinessChecks {
+    ready_marker: bool,
+    health_port: Option<u16>,
+    // Replaces the two checks above when set
+    probe: Option<Probe>,
+    timeout: Duration,
+}
+
+// `readiness_probe` with the file path resolved against the working directory
+#[derive(Clone)]
+enum Probe {
+    StdoutMarker,
+    TcpPort { host: String, port: u16 },
+    HttpUrl { url: String },
+    FileExists { path: PathBuf },
+}
+
+impl Probe {
+    fn describe(&self) -> String {
+        match self {
+            Probe::StdoutMarker => "the stdout marker".to_string(),
+            Probe::TcpPort { host, port } => format!("a TCP connection to {}:{}", host, port),
+            Probe::HttpUrl { url } => format!("a 2xx answer from {}", url),
+            Probe::FileExists { path } => format!("{} to exist", path.display()),
+        }
+    }
+
+    fn passed(&self, marker_seen: bool) -> bool {
+        match self {
+            Probe::StdoutMarker => marker_seen,
+            Probe::TcpPort { host, port } => tcp_open(host, *port),
+            Probe::HttpUrl { url } => http_ok(url),
+            Probe::FileExists { path } => path.exists(),
+        }
+    }
+}
+
+impl ReadinessChecks {
+    pub fn from_config(config: &RunnerConfig, working_dir: &Path) -> Self {
+        let probe = config.readiness_probe.as_ref().map(|probe| match probe {
+            ReadinessProbe::StdoutMarker { .. } => Probe::StdoutMarker,
+            ReadinessProbe::TcpPort { host, port } => Probe::TcpPort { host: host.clone(), port: *port },
+            ReadinessProbe::HttpUrl { url } => Probe::HttpUrl { url: url.clone() },
+            ReadinessProbe::FileExists { path } => Probe::FileExists { path: working_dir.join(path) },
+        });
+        Self {
+            ready_marker: config.ready_marker.is_some(),
+            health_port: config.health_port,
+            probe,
+            timeout: Duration::from_secs(config.ready_timeout_secs),
+        }
+    }
+}
+
+// Resolve the health of the process started as `run_id` in the background:
+// `Ready` once every configured check passes, `Failed` on timeout or early exit.
+// Starting returns as soon as the process is spawned, so this is where the UI
+// learns the outcome, from `runner-ready` or `runner-start-failed`.
+pub fn watch(app: AppHandle, run_id: u64, checks: ReadinessChecks) {
+    tauri::async_runtime::spawn(async move {
+        let result = wait_until_ready(&app.state::<RunnerState>(), run_id, &checks).await;
+        let state = app.state::<RunnerState>();
+
+        if let Err(NotReady::Exited(status)) = result {
+            report_early_exit(&app, &state, run_id, status).await;
+            return;
+        }
+
+        let (command_line, started_at, stderr_start) = {
+            let process_guard = state.python_process.lock().unwrap();
+            let process = match process_guard.as_ref() {
+                Some(process) if process.run_id == run_id => process,
+                // Stopped or replaced; whoever did that owns the health now
+                _ => return,
+            };
+            let mut health = state.health.lock().unwrap();
+            if *health != Health::Starting {
+                return;
+            }
+            *health = if result.is_ok() { Health::Ready } else { Health::Failed };
+            (process.command_line.clone(), process.started_at, process.stderr_start)
+        };
+
+        match result {
+            Ok(()) => {
+                let latency = started_at.elapsed();
+                info!("Python runner is ready after {}ms", latency.as_millis());
+                crate::latency::record(&app, &state, latency);
+                let _ = app.emit_all("runner-ready", ());
+            }
+            Err(NotReady::TimedOut(error)) => {
+                warn!("{}", error);
+                crate::record_last_error(&state, &error, &command_line, Some(stderr_start));
+                let _ = app.emit_all(
+                    "runner-start-failed",
+                    StartFailedEvent { error, exit_code: None, stderr_tail: Vec::new() },
+                );
+            }
+            Err(NotReady::Gone | NotReady::Exited(_)) => return,
+        }
+        let _ = app.emit_all("runner-status", crate::runner_status(&state));
+    });
+}
+
+// Fail the start of a process that exited before becoming ready, with its exit
+// code and last stderr lines. The supervisor still handles the exit itself.
+async fn report_early_exit(app: &AppHandle, state: &RunnerState, run_id: u64, status: ExitStatus) {
+    tokio::time::sleep(STDERR_DRAIN_TIME).await;
+    let (command_line, stderr_start) = {
+        let process_guard = state.python_process.lock().unwrap();
+        match process_guard.as_ref() {
+            Some(process) if process.run_id == run_id => {
+                let mut health = state.health.lock().unwrap();
+                if *health == Health::Starting {
+                    *health = Health::Failed;
+                }
+                (process.command_line.clone(), Some(process.stderr_start))
+            }
+            // Already taken by the supervisor, which reports the exit
+            _ => (String::new(), None),
+        }
+    };
+    let stderr_tail: Vec<String> = match stderr_start {
+        Some(start) => state
+            .stderr_buffer
+            .lock()
+            .unwrap()
+            .stderr_since(start, STDERR_TAIL_LINES)
+            .into_iter()
+            .map(str::to_string)
+            .collect(),
+        None => Vec::new(),
+    };
+    let error = early_exit_message(status, &stderr_tail);
+    warn!("{}", error);
+    if stderr_start.is_some() {
+        crate::record_last_error(state, &error, &command_line, stderr_start);
+    }
+    let _ = app.emit_all(
+        "runner-start-failed",
+        StartFailedEvent { error, exit_code: status.code(), stderr_tail },
+    );
+    let _ = app.emit_all("runner-status", crate::runner_status(state));
+}
+
+fn early_exit_message(status: ExitStatus, stderr_tail: &[String]) -> String {
+    match stderr_tail.last() {
+        Some(last) => format!("Python runner exited before becoming ready ({}): {}", status, last),
+        None => format!("Python runner exited before becoming ready ({})", status),
+    }
+}
+
+async fn wait_until_ready(state: &RunnerState, run_id: u64, checks: &ReadinessChecks) -> Result<(), NotReady> {
+    poll_until_ready(checks, || {
+        let mut process_guard = state.python_process.lock().unwrap();
+        match process_guard.as_mut() {
+            Some(process) if process.run_id == run_id => match process.child.try_wait() {
+                // The status is kept on the child, so the supervisor still sees the exit
+                Ok(Some(status)) => Observation::Exited(status),
+                _ => Observation::Running { marker_seen: process.ready_marker_seen.load(Ordering::Relaxed) },
+            },
+            _ => Observation::Gone,
+        }
+    })
+    .await
+}
+
+// Poll `observe` until the checks pass, the process exits or the timeout runs out.
+async fn poll_until_ready(checks: &ReadinessChecks, mut observe: impl FnMut() -> Observation) -> Result<(), NotReady> {
+    let started = Instant::now();
+    let deadline = started + checks.timeout;
+
+    loop {
+        let marker_seen = match observe() {
+            Observation::Running { marker_seen } => marker_seen,
+            Observation::Exited(status) => return Err(NotReady::Exited(status)),
+            Observation::Gone => return Err(NotReady::Gone),
+        };
+
+        let ready = if let Some(probe) = &checks.probe {
+            probe.passed(marker_seen)
+        } else if !checks.ready_marker && checks.health_port.is_none() {
+            started.elapsed() >= SETTLE_TIME
+        } else {
+            (!checks.ready_marker || marker_seen) && checks.health_port.map_or(true, port_open)
+        };
+        if ready {
+            return Ok(());
+        }
+
+        if Instant::now() >= deadline {
+            return Err(NotReady::TimedOut(match &checks.probe {
+                Some(probe) => format!(
+                    "Readiness timeout: waited {}s for {}",
+                    checks.timeout.as_secs(),
+                    probe.describe()
+                ),
+                None => format!(
+                    "Readiness timeout: Python runner did not become ready within {}s",
+                    checks.timeout.as_secs()
+                ),
+            }));
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+fn port_open(port: u16) -> bool {
+    TcpStream::connect_timeout(&SocketAddr::from(([127, 0, 0, 1], port)), CONNECT_TIMEOUT).is_ok()
+}
+
+fn tcp_open(host: &str, port: u16) -> bool {
+    match (host, port).to_socket_addrs() {
+        Ok(mut addrs) => addrs.any(|addr| TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT).is_ok()),
+        Err(_) => false,
+    }
+}
+
+// Split `http://host[:port][/path]` into its parts. Only plain http is
+// supported; the probe speaks HTTP/1.0 by hand.
+pub fn parse_http_url(url: &str) -> Result<(String, u16, String), String> {
+    let rest = url
+        .strip_prefix("http://")
+        .ok_or_else(|| format!("{} is not an http:// URL", url))?;
+    let (authority, path) = match rest.find('/') {
+        Some(index) => (&rest[..index], &rest[index..]),
+        None => (rest, "/"),
+    };
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) => (host, port.parse().map_err(|_| format!("Invalid port in {}", url))?),
+        None => (authority, 80),
+    };
+    if host.is_empty() {
+        return Err(format!("{} has no host", url));
+    }
+    Ok((host.to_string(), port, path.to_string()))
+}
+
+fn http_ok(url: &str) -> bool {
+    let (host, port, path) = match parse_http_url(url) {
+        Ok(parts) => parts,
+        Err(_) => return false,
+    };
+    let query = || -> std::io::Result<String> {
+        let addr = (host.as_str(), port)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "no address"))?;
+        let mut stream = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)?;
+        stream.set_read_timeout(Some(CONNECT_TIMEOUT))?;
+        stream.set_write_timeout(Some(CONNECT_TIMEOUT))?;
+        write!(stream, "GET {} HTTP/1.0\r\nHost: {}:{}\r\n\r\n", path, host, port)?;
+        let mut status_line = [0u8; 32];
+        let read = stream.read(&mut status_line)?;
+        Ok(String::from_utf8_lossy(&status_line[..read]).to_string())
+    };
+    match query() {
+        Ok(response) => response.split_whitespace().nth(1).is_some_and(|code| code.starts_with('2')),
+        Err(_) => false,
+    }
+}
+
+// Wait for the background readiness watch of the current process to resolve.
+pub async fn wait_for_health(state: &RunnerState, timeout: Duration) -> Health {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let health = *state.health.lock().unwrap();
+        if health != Health::Starting || Instant::now() >= deadline {
+            return health;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    fn marker_checks(timeout: Duration) -> ReadinessChecks {
+        ReadinessChecks {
+            ready_marker: true,
+            health_port: None,
+            probe: None,
+            timeout,
+        }
+    }
+
+    #[cfg(unix)]
+    fn exit_immediately(code: i32) -> Command {
+        let mut command = Command::new("sh");
+        command.args(["-c", &format!("exit {}", code)]);
+        command
+    }
+
+    #[cfg(windows)]
+    fn exit_immediately(code: i32) -> Command {
+        let mut command = Command::new("cmd");
+        command.args(["/C", &format!("exit {}", code)]);
+        command
+    }
+
+    #[tokio::test]
+    async fn exit_during_wait_fails_before_timeout() {
+        let mut child = exit_immediately(3).spawn().unwrap();
+        let started = Instant::now();
+        let result = poll_until_ready(&marker_checks(Duration::from_secs(30)), || match child.try_wait() {
+            Ok(Some(status)) => Observation::Exited(status),
+            _ => Observation::Running { marker_seen: false },
+        })
+        .await;
+
+        let status = match result {
+            Err(NotReady::Exited(status)) => status,
+            other => panic!("expected an early exit, got {:?}", other),
+        };
+        assert_eq!(status.code(), Some(3));
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        let tail = vec!["Traceback (most recent call last):".to_string(), "ValueError: bad config".to_string()];
+        let message = early_exit_message(status, &tail);
+        assert!(message.starts_with("Python runner exited before becoming ready"));
+        assert!(message.ends_with("ValueError: bad config"));
+    }
+
+    #[tokio::test]
+    async fn running_process_times_out() {
+        let result = poll_until_ready(&marker_checks(Duration::ZERO), || Observation::Running { marker_seen: false }).await;
+        assert!(matches!(result, Err(NotReady::TimedOut(error)) if error.starts_with("Readiness timeout:")));
+    }
+}
@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::sync::Mutex;
+use log::warn;
+
+use crate::fingerprint::EnvironmentFingerprint;
+use crate::paths;
+use crate::schedule::Schedule;
+use crate::survivor::RunnerRecord;
+
+// Serializes each update's load, change and write, and keeps loads from
+// reading a file halfway through being written
+static FILE_LOCK: Mutex<()> = Mutex::new(());
+
+// Runtime state that survives restarts, stored as JSON in ~/.oriphim/state.json.
+// Unlike config.toml it is written by the app, not edited by hand.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SavedState {
+    pub schedule: Schedule,
+    // A sample notification has been sent, so the OS has asked for permission
+    pub notifications_tested: bool,
+    // Keep the main window above other windows
+    pub always_on_top: bool,
+    // Recorded at the last start, to spot environment drift
+    pub environment_fingerprint: Option<EnvironmentFingerprint>,
+    // The runner last started, to find it again if the app exits without stopping it
+    pub last_runner: Option<RunnerRecord>,
+}
+
+impl SavedState {
+    pub fn load() -> Self {
+        let _lock = FILE_LOCK.lock().unwrap();
+        Self::read()
+    }
+
+    fn read() -> Self {
+        let path = match paths::state_file() {
+            Some(path) => path,
+            None => return Self::default(),
+        };
+        match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                warn!("Ignoring invalid state file {}: {}", path.display(), e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    // Read-modify-write so each caller only touches its own part of the file.
+    pub fn update(change: impl FnOnce(&mut SavedState)) -> Result<(), String> {
+        let path = paths::state_file().ok_or("Could not find home directory")?;
+        let _lock = FILE_LOCK.lock().unwrap();
+        let mut state = Self::read();
+        change(&mut state);
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let contents = serde_json::to_string_pretty(&state).map_err(|e| format!("Failed to serialize state: {}", e))?;
+        fs::write(&path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+}
@@ -0,0 +1,231 @@
+use chrono::{Local, NaiveDateTime, NaiveTime, Timelike};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use log::{error, info, warn};
+
+use crate::paths;
+use crate::saved_state::SavedState;
+use crate::RunnerState;
+
+// An action this late (e.g. the machine was asleep) is skipped rather than run
+const MISSED_GRACE_SECS: i64 = 120;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScheduleAction {
+    Start,
+    Stop,
+}
+
+impl ScheduleAction {
+    fn name(self) -> &'static str {
+        match self {
+            ScheduleAction::Start => "start",
+            ScheduleAction::Stop => "stop",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledAction {
+    // Local time of day
+    pub time: NaiveTime,
+    // Repeat every day instead of running once
+    pub daily: bool,
+    // When it fires next, in local time
+    pub next: NaiveDateTime,
+}
+
+impl ScheduledAction {
+    fn new(time: NaiveTime, daily: bool, now: NaiveDateTime) -> Self {
+        Self {
+            time,
+            daily,
+            next: next_occurrence(time, now),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Schedule {
+    pub start: Option<ScheduledAction>,
+    pub stop: Option<ScheduledAction>,
+}
+
+impl Schedule {
+    fn slot(&mut self, action: ScheduleAction) -> &mut Option<ScheduledAction> {
+        match action {
+            ScheduleAction::Start => &mut self.start,
+            ScheduleAction::Stop => &mut self.stop,
+        }
+    }
+
+    // The earliest upcoming action.
+    pub fn next_action(&self) -> Option<(ScheduleAction, &ScheduledAction)> {
+        let start = self.start.as_ref().map(|scheduled| (ScheduleAction::Start, scheduled));
+        let stop = self.stop.as_ref().map(|scheduled| (ScheduleAction::Stop, scheduled));
+        start.into_iter().chain(stop).min_by_key(|(_, scheduled)| scheduled.next)
+    }
+
+    // Remove the actions due at `now`, in the order they were due, and move
+    // daily ones on to their next occurrence. Returns the due actions and
+    // whether the schedule changed.
+    fn take_due(&mut self, now: NaiveDateTime) -> (Vec<ScheduleAction>, bool) {
+        let mut due = Vec::new();
+        let mut changed = false;
+
+        for action in [ScheduleAction::Start, ScheduleAction::Stop] {
+            let slot = self.slot(action);
+            let scheduled = match slot.as_mut() {
+                Some(scheduled) if scheduled.next <= now => scheduled,
+                _ => continue,
+            };
+            changed = true;
+
+            if (now - scheduled.next).num_seconds() > MISSED_GRACE_SECS {
+                warn!("Skipping scheduled {} missed at {}", action.name(), scheduled.next.format("%Y-%m-%d %H:%M"));
+            } else {
+                due.push((scheduled.next, action));
+            }
+
+            if scheduled.daily {
+                scheduled.next = next_occurrence(scheduled.time, now);
+            } else {
+                *slot = None;
+            }
+        }
+
+        due.sort_by_key(|(next, _)| *next);
+        (due.into_iter().map(|(_, action)| action).collect(), changed)
+    }
+}
+
+fn next_occurrence(time: NaiveTime, after: NaiveDateTime) -> NaiveDateTime {
+    let today = after.date().and_time(time);
+    if today > after {
+        today
+    } else {
+        today + chrono::Duration::days(1)
+    }
+}
+
+fn parse_time(time: &str) -> Result<NaiveTime, String> {
+    let time = time.trim();
+    NaiveTime::parse_from_str(time, "%H:%M")
+        .or_else(|_| NaiveTime::parse_from_str(time, "%H:%M:%S"))
+        .map_err(|_| format!("Invalid time '{}', expected HH:MM", time))
+}
+
+fn save(schedule: &Schedule) -> Result<(), String> {
+    SavedState::update(|saved| saved.schedule = schedule.clone())
+}
+
+// The tray tooltip shows the active workspace and the next scheduled action.
+pub fn update_tooltip(app: &AppHandle, schedule: &Schedule) {
+    let mut tooltip = "Oriphim Runner".to_string();
+    if let Some(name) = paths::workspace().as_deref().and_then(|dir| dir.file_name()) {
+        tooltip.push_str(&format!(" [{}]", name.to_string_lossy()));
+    }
+    if let Some((action, scheduled)) = schedule.next_action() {
+        tooltip.push_str(&format!(
+            "\nNext: {} at {}{}",
+            action.name(),
+            scheduled.next.format("%a %H:%M"),
+            if scheduled.daily { " (daily)" } else { "" }
+        ));
+    }
+    let _ = app.tray_handle().set_tooltip(&tooltip);
+}
+
+// Check the schedule at the top of every minute and run whatever is due.
+pub fn start_scheduler(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let seconds = 60 - u64::from(Local::now().second()).min(59);
+            tokio::time::sleep(Duration::from_secs(seconds)).await;
+            let state = app.state::<RunnerState>();
+
+            let (due, schedule) = {
+                let mut schedule = state.schedule.lock().unwrap();
+                let (due, changed) = schedule.take_due(Local::now().naive_local());
+                if !changed {
+                    continue;
+                }
+                if let Err(e) = save(&schedule) {
+                    error!("Failed to save schedule: {}", e);
+                }
+                (due, schedule.clone())
+            };
+            update_tooltip(&app, &schedule);
+
+            for action in due {
+                info!("Running scheduled {}", action.name());
+                let result = match action {
+                    ScheduleAction::Start => crate::start_python_runner(app.clone(), app.state::<RunnerState>()).await,
+                    ScheduleAction::Stop => crate::stop_python_runner(app.clone(), app.state::<RunnerState>()).await,
+                };
+                if let Err(e) = result {
+                    error!("Scheduled {} failed: {}", action.name(), e);
+                }
+            }
+        }
+    });
+}
+
+fn set_action(
+    app: &AppHandle,
+    state: &RunnerState,
+    action: ScheduleAction,
+    scheduled: Option<ScheduledAction>,
+) -> Result<Schedule, String> {
+    let mut schedule = state.schedule.lock().unwrap();
+    let mut updated = schedule.clone();
+    *updated.slot(action) = scheduled;
+    save(&updated)?;
+    *schedule = updated.clone();
+    update_tooltip(app, &schedule);
+    Ok(updated)
+}
+
+// Start the runner at `time` (HH:MM, local), once or every day.
+#[tauri::command]
+pub async fn schedule_start(
+    time: String,
+    daily: Option<bool>,
+    app: AppHandle,
+    state: tauri::State<'_, RunnerState>,
+) -> Result<Schedule, String> {
+    let scheduled = ScheduledAction::new(parse_time(&time)?, daily.unwrap_or(false), Local::now().naive_local());
+    info!("Scheduled start at {}", scheduled.next.format("%Y-%m-%d %H:%M"));
+    set_action(&app, &state, ScheduleAction::Start, Some(scheduled))
+}
+
+// Stop the runner at `time` (HH:MM, local), once or every day.
+#[tauri::command]
+pub async fn schedule_stop(
+    time: String,
+    daily: Option<bool>,
+    app: AppHandle,
+    state: tauri::State<'_, RunnerState>,
+) -> Result<Schedule, String> {
+    let scheduled = ScheduledAction::new(parse_time(&time)?, daily.unwrap_or(false), Local::now().naive_local());
+    info!("Scheduled stop at {}", scheduled.next.format("%Y-%m-%d %H:%M"));
+    set_action(&app, &state, ScheduleAction::Stop, Some(scheduled))
+}
+
+#[tauri::command]
+pub async fn clear_schedule(app: AppHandle, state: tauri::State<'_, RunnerState>) -> Result<Schedule, String> {
+    let mut schedule = state.schedule.lock().unwrap();
+    save(&Schedule::default())?;
+    *schedule = Schedule::default();
+    update_tooltip(&app, &schedule);
+    info!("Cleared schedule");
+    Ok(schedule.clone())
+}
+
+#[tauri::command]
+pub async fn get_schedule(state: tauri::State<'_, RunnerState>) -> Result<Schedule, String> {
+    Ok(state.schedule.lock().unwrap().clone())
+}
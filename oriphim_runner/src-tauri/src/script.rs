@@ -0,0 +1,124 @@
+use regex::Regex;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tauri::{AppHandle, Manager};
+use log::{info, warn};
+
+use crate::paths;
+use crate::RunnerState;
+
+const SCRIPT_NAME: &str = "main.py";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScriptStatus {
+    // The copy shipped with the app
+    pub bundled: String,
+    // The copy the runner starts
+    pub installed: String,
+    pub bundled_version: Option<String>,
+    pub installed_version: Option<String>,
+    // SHA-256 of each file's contents
+    pub bundled_hash: String,
+    pub installed_hash: Option<String>,
+    pub update_available: bool,
+}
+
+include!("content_hash.rs");
+
+// `__version__ = "1.2.3"` in the script, if it declares one
+fn script_version(contents: &str) -> Option<String> {
+    static VERSION: OnceLock<Regex> = OnceLock::new();
+    let version = VERSION.get_or_init(|| Regex::new(r#"(?m)^__version__\s*=\s*['"]([^'"]+)['"]"#).unwrap());
+    version.captures(contents).map(|captures| captures[1].to_string())
+}
+
+// Compare dotted versions numerically, e.g. 1.10 > 1.9. Non-numeric parts count as 0.
+fn is_newer(candidate: &str, current: &str) -> bool {
+    let parts = |version: &str| -> Vec<u64> { version.split('.').map(|part| part.parse().unwrap_or(0)).collect() };
+    parts(candidate) > parts(current)
+}
+
+// Compare the bundled main.py with the one in the working directory. None when
+// there's no bundled copy, or the runner already runs the bundled copy.
+pub fn check(app: &AppHandle) -> Option<ScriptStatus> {
+    let bundled: PathBuf = app.path_resolver().resolve_resource(Path::new("src").join(SCRIPT_NAME))?;
+    let config = app.state::<RunnerState>().config.lock().unwrap().expanded().ok()?;
+    let installed = paths::working_dir(app, &config).join(SCRIPT_NAME);
+    if fs::canonicalize(&bundled).ok() == fs::canonicalize(&installed).ok() {
+        return None;
+    }
+
+    let bundled_contents = fs::read(&bundled).ok()?;
+    let installed_contents = fs::read(&installed).ok();
+    let bundled_version = script_version(&String::from_utf8_lossy(&bundled_contents));
+    let installed_version = installed_contents
+        .as_deref()
+        .and_then(|contents| script_version(&String::from_utf8_lossy(contents)));
+    let bundled_hash = content_hash(&bundled_contents);
+    let installed_hash = installed_contents.as_deref().map(content_hash);
+
+    // Differing contents are an update unless the versions say the installed copy is newer
+    let update_available = installed_hash.as_deref() != Some(bundled_hash.as_str())
+        && match (&bundled_version, &installed_version) {
+            (Some(bundled), Some(installed)) => is_newer(bundled, installed),
+            _ => true,
+        };
+    Some(ScriptStatus {
+        bundled: bundled.display().to_string(),
+        installed: installed.display().to_string(),
+        bundled_version,
+        installed_version,
+        bundled_hash,
+        installed_hash,
+        update_available,
+    })
+}
+
+// Emit `script-update-available` if the app ships a newer main.py than the one in use.
+pub fn notify_if_outdated(app: &AppHandle) {
+    if let Some(status) = check(app).filter(|status| status.update_available) {
+        info!("A newer {} is bundled: {} -> {}", SCRIPT_NAME, status.bundled, status.installed);
+        let _ = app.emit_all("script-update-available", status);
+    }
+}
+
+#[tauri::command]
+pub async fn check_script_update(app: AppHandle) -> Result<Option<ScriptStatus>, String> {
+    Ok(check(&app))
+}
+
+// Copy the bundled main.py over the one in use (kept as main.py.bak), then
+// restart the runner if it was running. `confirm` must be true.
+#[tauri::command]
+pub async fn update_script(confirm: bool, app: AppHandle, state: tauri::State<'_, RunnerState>) -> Result<String, String> {
+    if !confirm {
+        return Err(format!("Updating replaces the {} in use; pass confirm to proceed", SCRIPT_NAME));
+    }
+    let status = check(&app).ok_or("No separate bundled script to update from")?;
+    if !status.update_available {
+        return Err(format!("{} is already up to date", status.installed));
+    }
+
+    let installed = Path::new(&status.installed);
+    if installed.exists() {
+        let backup = installed.with_extension("py.bak");
+        fs::copy(installed, &backup).map_err(|e| format!("Failed to back up {}: {}", installed.display(), e))?;
+    }
+    fs::copy(&status.bundled, installed).map_err(|e| format!("Failed to update {}: {}", installed.display(), e))?;
+    info!(
+        "Updated {} to the bundled version {}",
+        installed.display(),
+        status.bundled_version.as_deref().unwrap_or(&status.bundled_hash)
+    );
+
+    if *state.is_running.lock().unwrap() {
+        if let Err(e) = crate::safe_restart(app.clone(), state).await {
+            warn!("Restart after script update failed: {}", e);
+            return Err(format!("Script updated, but the restart failed: {}", e));
+        }
+        return Ok("Script updated and runner restarted".to_string());
+    }
+    Ok("Script updated".to_string())
+}
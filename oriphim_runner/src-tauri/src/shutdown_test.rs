@@ -0,0 +1,73 @@
+use serde::Serialize;
+use std::time::Duration;
+use log::{info, warn};
+
+use crate::config::StopSignal;
+use crate::readiness::{self, Health};
+use crate::{RunnerState, Stopped};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ShutdownTestResult {
+    // Sent on Unix; Windows always sends CTRL_BREAK
+    pub signal: StopSignal,
+    pub grace_secs: u64,
+    // Exited within the grace period, i.e. the script handled the signal
+    pub handled: bool,
+    pub exit_code: Option<i32>,
+    pub status: Option<String>,
+    pub elapsed_ms: u64,
+}
+
+// Check that the script shuts down cleanly: start it, wait until it is ready,
+// stop it the way stop_python_runner would and report whether it exited on
+// its own or had to be force-killed. Leaves the runner stopped.
+#[tauri::command]
+pub async fn test_graceful_shutdown(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, RunnerState>,
+) -> Result<ShutdownTestResult, String> {
+    if *state.is_running.lock().unwrap() {
+        return Err("Stop the Python runner before testing shutdown".to_string());
+    }
+    let (signal, grace_secs, kill_tree, ready_timeout) = {
+        let config = state.config.lock().unwrap();
+        (
+            config.stop_signal,
+            config.stop_grace_secs,
+            config.kill_process_tree,
+            Duration::from_secs(config.ready_timeout_secs),
+        )
+    };
+
+    info!("Testing graceful shutdown with {:?} and a {}s grace period", signal, grace_secs);
+    crate::start_runner(&app, &state, false).await?;
+    // The readiness watch gives up on its own after `ready_timeout`
+    let health = readiness::wait_for_health(&state, ready_timeout + Duration::from_secs(1)).await;
+    if health != Health::Ready {
+        warn!("Shutdown test aborted: the Python runner did not become ready");
+        crate::stop_runner(&app, &state, kill_tree).await?;
+        return Err("Python runner did not become ready; shutdown was not tested".to_string());
+    }
+
+    let outcome = match crate::stop_runner_with_outcome(&app, &state, kill_tree, true).await? {
+        Stopped::Process(outcome) => outcome,
+        // Exited or was stopped by someone else after becoming ready
+        Stopped::Nothing | Stopped::Reconnected(_) => {
+            return Err("Python runner exited before the stop signal was sent".to_string())
+        }
+    };
+    let result = ShutdownTestResult {
+        signal,
+        grace_secs,
+        handled: outcome.graceful,
+        exit_code: outcome.status.and_then(|status| status.code()),
+        status: outcome.status.map(|status| status.to_string()),
+        elapsed_ms: outcome.elapsed.as_millis() as u64,
+    };
+    if result.handled {
+        info!("Shutdown test passed: exited after {}ms ({:?})", result.elapsed_ms, result.status);
+    } else {
+        warn!("Shutdown test failed: force-killed after the {}s grace period", grace_secs);
+    }
+    Ok(result)
+}
@@ -0,0 +1,137 @@
+use tauri::AppHandle;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use log::{debug, error, info, warn};
+
+// Only one instance runs per user. A second launch asks the first to show its
+// window, over a local socket (a named pipe on Windows) that is always served,
+// unlike the opt-in control socket, and then exits.
+const SHOW: &str = "show";
+
+#[cfg(windows)]
+fn pipe_name() -> String {
+    let user = std::env::var("USERNAME").unwrap_or_else(|_| "default".to_string());
+    format!(r"\\.\pipe\oriphim-runner-instance-{}", user)
+}
+
+// Ask an instance that's already running to show its window. Returns whether
+// one took the request, in which case this launch should exit.
+#[cfg(unix)]
+pub fn signal_existing() -> bool {
+    use std::io::Write;
+    use std::os::unix::net::UnixStream;
+
+    let path = match crate::paths::instance_socket() {
+        Some(path) => path,
+        None => return false,
+    };
+    match UnixStream::connect(&path) {
+        Ok(mut stream) => stream.write_all(format!("{}\n", SHOW).as_bytes()).is_ok(),
+        Err(_) => false,
+    }
+}
+
+#[cfg(windows)]
+pub fn signal_existing() -> bool {
+    use std::io::Write;
+
+    match std::fs::OpenOptions::new().write(true).open(pipe_name()) {
+        Ok(mut pipe) => pipe.write_all(format!("{}\n", SHOW).as_bytes()).is_ok(),
+        Err(_) => false,
+    }
+}
+
+// Show the main window whenever a later launch asks to.
+async fn serve<S: AsyncRead + Unpin>(app: AppHandle, stream: S) {
+    let mut lines = BufReader::new(stream).lines();
+    loop {
+        match lines.next_line().await {
+            Ok(Some(line)) if line.trim() == SHOW => {
+                info!("Another launch of the app asked to show the window");
+                crate::show_main_window(&app);
+            }
+            Ok(Some(line)) => debug!("Ignoring instance request '{}'", line.trim()),
+            Ok(None) => break,
+            Err(e) => {
+                debug!("Instance connection closed: {}", e);
+                break;
+            }
+        }
+    }
+}
+
+// Listen on ~/.oriphim/instance.sock, readable and writable only by the
+// current user. Called once `signal_existing` found no instance, so a socket
+// already there was left by one that crashed and is replaced.
+#[cfg(unix)]
+pub fn listen(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let path = match crate::paths::instance_socket() {
+            Some(path) => path,
+            None => {
+                warn!("Could not find home directory, single-instance socket not started");
+                return;
+            }
+        };
+        if let Some(dir) = path.parent() {
+            if let Err(e) = std::fs::create_dir_all(dir) {
+                error!("Failed to create {}: {}", dir.display(), e);
+                return;
+            }
+        }
+        let listener = match crate::control::bind_private(&path) {
+            Ok(listener) => listener,
+            Err(e) => {
+                error!("Failed to listen on {}: {}", path.display(), e);
+                return;
+            }
+        };
+        debug!("Single-instance socket listening on {}", path.display());
+
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tauri::async_runtime::spawn(serve(app.clone(), stream));
+                }
+                Err(e) => warn!("Single-instance socket accept failed: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(windows)]
+pub fn listen(app: AppHandle) {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    tauri::async_runtime::spawn(async move {
+        let name = pipe_name();
+        let options = || {
+            let mut options = ServerOptions::new();
+            options.reject_remote_clients(true);
+            options
+        };
+        let mut server = match options().first_pipe_instance(true).create(&name) {
+            Ok(server) => server,
+            Err(e) => {
+                error!("Failed to create single-instance pipe {}: {}", name, e);
+                return;
+            }
+        };
+        debug!("Single-instance pipe listening on {}", name);
+
+        loop {
+            if let Err(e) = server.connect().await {
+                warn!("Single-instance pipe connect failed: {}", e);
+                continue;
+            }
+            let next = match options().create(&name) {
+                Ok(next) => next,
+                Err(e) => {
+                    error!("Failed to create single-instance pipe {}: {}", name, e);
+                    return;
+                }
+            };
+            let connected = std::mem::replace(&mut server, next);
+            tauri::async_runtime::spawn(serve(app.clone(), connected));
+        }
+    });
+}
@@ -0,0 +1,79 @@
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// Aggregate counters for the current app session. They start at zero every
+// time the app launches and are never persisted.
+#[derive(Default)]
+pub struct SessionStats {
+    pub stdout_bytes: AtomicU64,
+    pub stderr_bytes: AtomicU64,
+    pub stdout_lines: AtomicU64,
+    pub stderr_lines: AtomicU64,
+    pub starts: AtomicU64,
+    pub stops: AtomicU64,
+    pub restarts: AtomicU64,
+    pub crashes: AtomicU64,
+    pub peak_memory_bytes: AtomicU64,
+    // Lines dropped by the output rate limit
+    pub dropped_lines: AtomicU64,
+    // Milliseconds since the epoch of the latest stdout line, 0 before the first
+    last_stdout_ms: AtomicU64,
+}
+
+#[derive(Serialize)]
+pub struct SessionStatsSnapshot {
+    pub stdout_bytes: u64,
+    pub stderr_bytes: u64,
+    pub stdout_lines: u64,
+    pub stderr_lines: u64,
+    pub starts: u64,
+    pub stops: u64,
+    pub restarts: u64,
+    pub crashes: u64,
+    pub peak_memory_bytes: u64,
+    pub dropped_lines: u64,
+}
+
+impl SessionStats {
+    pub fn increment(counter: &AtomicU64) {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_stdout_line(&self) {
+        Self::increment(&self.stdout_lines);
+        self.last_stdout_ms.store(epoch_millis(), Ordering::Relaxed);
+    }
+
+    // Time since the latest stdout line, or None if nothing was ever printed.
+    pub fn stdout_silent_for(&self) -> Option<Duration> {
+        match self.last_stdout_ms.load(Ordering::Relaxed) {
+            0 => None,
+            last => Some(Duration::from_millis(epoch_millis().saturating_sub(last))),
+        }
+    }
+
+    pub fn record_memory(&self, bytes: u64) {
+        self.peak_memory_bytes.fetch_max(bytes, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> SessionStatsSnapshot {
+        let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+        SessionStatsSnapshot {
+            stdout_bytes: load(&self.stdout_bytes),
+            stderr_bytes: load(&self.stderr_bytes),
+            stdout_lines: load(&self.stdout_lines),
+            stderr_lines: load(&self.stderr_lines),
+            starts: load(&self.starts),
+            stops: load(&self.stops),
+            restarts: load(&self.restarts),
+            crashes: load(&self.crashes),
+            peak_memory_bytes: load(&self.peak_memory_bytes),
+            dropped_lines: load(&self.dropped_lines),
+        }
+    }
+}
+
+fn epoch_millis() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
@@ -0,0 +1,160 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, Instant};
+use log::{debug, info, warn};
+
+use crate::paths;
+use crate::RunnerState;
+
+// What was written to stdin: text when it was valid UTF-8, raw bytes otherwise
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum StdinData {
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+impl StdinData {
+    fn into_bytes(self) -> Vec<u8> {
+        match self {
+            StdinData::Text(text) => text.into_bytes(),
+            StdinData::Bytes(bytes) => bytes,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedCommand {
+    // Since the recording started
+    pub offset_ms: u64,
+    pub data: StdinData,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StdinRecording {
+    pub name: String,
+    pub started_at: DateTime<Local>,
+    pub commands: Vec<RecordedCommand>,
+}
+
+// A recording in progress
+pub struct Recorder {
+    recording: StdinRecording,
+    started: Instant,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SavedRecording {
+    pub name: String,
+    pub path: String,
+    pub commands: usize,
+    pub duration_ms: u64,
+}
+
+// Note a write to the runner's stdin if a recording is in progress.
+pub fn record(state: &RunnerState, data: &[u8]) {
+    if let Some(recorder) = state.stdin_recording.lock().unwrap().as_mut() {
+        let data = match std::str::from_utf8(data) {
+            Ok(text) => StdinData::Text(text.to_string()),
+            Err(_) => StdinData::Bytes(data.to_vec()),
+        };
+        recorder.recording.commands.push(RecordedCommand {
+            offset_ms: recorder.started.elapsed().as_millis() as u64,
+            data,
+        });
+    }
+}
+
+// Recordings are saved as `<name>.json`, so a name is a plain file stem.
+fn recording_path(name: &str) -> Result<PathBuf, String> {
+    let valid = !name.is_empty()
+        && name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_' || b == b'.')
+        && matches!(Path::new(name).components().collect::<Vec<_>>()[..], [Component::Normal(_)]);
+    if !valid {
+        return Err(format!("Invalid recording name '{}': use letters, digits, '-', '_' and '.'", name));
+    }
+    let dir = paths::recordings_dir().ok_or("Could not determine the recordings directory")?;
+    Ok(dir.join(format!("{}.json", name)))
+}
+
+// Start noting every command sent to the runner's stdin, with its time relative
+// to now. `name` defaults to one based on the current time.
+#[tauri::command]
+pub async fn start_recording_stdin(name: Option<String>, state: tauri::State<'_, RunnerState>) -> Result<String, String> {
+    let name = match name.map(|name| name.trim().to_string()).filter(|name| !name.is_empty()) {
+        Some(name) => name,
+        None => Local::now().format("stdin-%Y%m%d-%H%M%S").to_string(),
+    };
+    recording_path(&name)?;
+
+    let mut recorder = state.stdin_recording.lock().unwrap();
+    if let Some(recorder) = recorder.as_ref() {
+        return Err(format!("Already recording stdin as '{}'", recorder.recording.name));
+    }
+    *recorder = Some(Recorder {
+        recording: StdinRecording { name: name.clone(), started_at: Local::now(), commands: Vec::new() },
+        started: Instant::now(),
+    });
+    info!("Recording stdin as '{}'", name);
+    Ok(name)
+}
+
+// Stop recording and save the recording to recordings/<name>.json.
+#[tauri::command]
+pub async fn stop_recording_stdin(state: tauri::State<'_, RunnerState>) -> Result<SavedRecording, String> {
+    let recorder = state.stdin_recording.lock().unwrap().take().ok_or("Not recording stdin")?;
+    let duration_ms = recorder.started.elapsed().as_millis() as u64;
+    let recording = recorder.recording;
+    let path = recording_path(&recording.name)?;
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(&recording).map_err(|e| format!("Failed to serialize recording: {}", e))?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+
+    info!("Saved stdin recording '{}' ({} commands) to {}", recording.name, recording.commands.len(), path.display());
+    Ok(SavedRecording {
+        name: recording.name,
+        path: path.to_string_lossy().to_string(),
+        commands: recording.commands.len(),
+        duration_ms,
+    })
+}
+
+// Send a saved recording's commands to the running process with their
+// original timing. Stops early if the process exits or is replaced.
+#[tauri::command]
+pub async fn replay_stdin(recording: String, state: tauri::State<'_, RunnerState>) -> Result<String, String> {
+    let path = recording_path(recording.trim())?;
+    let contents = fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let recording: StdinRecording =
+        serde_json::from_str(&contents).map_err(|e| format!("Invalid recording {}: {}", path.display(), e))?;
+    let run_id = state
+        .python_process
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|process| process.run_id)
+        .ok_or("Python runner is not running")?;
+
+    info!("Replaying stdin recording '{}' ({} commands)", recording.name, recording.commands.len());
+    let started = Instant::now();
+    let total = recording.commands.len();
+    for (index, command) in recording.commands.into_iter().enumerate() {
+        let due = started + Duration::from_millis(command.offset_ms);
+        tokio::time::sleep(due.saturating_duration_since(Instant::now())).await;
+
+        let same_process = state.python_process.lock().unwrap().as_ref().is_some_and(|process| process.run_id == run_id);
+        if !same_process {
+            warn!("Stopped replaying '{}': the Python runner exited after {} of {} commands", recording.name, index, total);
+            return Err(format!("Python runner exited after {} of {} commands", index, total));
+        }
+        crate::write_stdin(&state, command.data.into_bytes()).await?;
+        debug!("Replayed command {}/{} at {}ms", index + 1, total, command.offset_ms);
+    }
+    info!("Replayed stdin recording '{}'", recording.name);
+    Ok(format!("Replayed {} commands", total))
+}
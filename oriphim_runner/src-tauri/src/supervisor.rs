@@ -0,0 +1,352 @@
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::process::ExitStatus;
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, System};
+use tauri::{AppHandle, Manager};
+use tokio::sync::oneshot;
+use log::{debug, error, info, warn};
+
+use crate::circuit;
+use crate::crash_debug;
+use crate::crash_dump::{self, LastCrash};
+use crate::elevation;
+use crate::lifecycle::{self, LifecycleKind};
+use crate::notify;
+use crate::process::{ManagedProcess, READER_JOIN_TIMEOUT};
+use crate::readiness::Health;
+use crate::stats::SessionStats;
+use crate::traceback::{self, CrashReport};
+use crate::RunnerState;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+// Sample memory every 4th poll (~2s); refreshing a single process is cheap but not free.
+const MEMORY_SAMPLE_POLLS: u32 = 4;
+// A process that stays up this long is no longer part of a crash loop
+const STABLE_RUNTIME: Duration = Duration::from_secs(60);
+const MAX_RESTART_DELAY: Duration = Duration::from_secs(60);
+
+// Consecutive crashes and the restart scheduled after the latest one.
+#[derive(Default)]
+pub struct RestartState {
+    pub attempts: u32,
+    // Token of the pending restart; clearing it cancels the restart
+    pending: Option<u64>,
+    next_token: u64,
+    // Wakes the pending restart's timer to abort it, as does dropping it
+    cancel: Option<oneshot::Sender<()>>,
+    next_attempt_at: Option<DateTime<Local>>,
+    max_attempts: u32,
+}
+
+// What the supervisor is doing about the last crash, for the UI.
+#[derive(Debug, Clone, Serialize)]
+pub struct RestartStatus {
+    // Waiting to restart
+    pub pending: bool,
+    // Consecutive crashes so far, i.e. the number of the pending attempt
+    pub attempt: u32,
+    pub max_attempts: u32,
+    pub next_attempt_at: Option<DateTime<Local>>,
+}
+
+impl RestartState {
+    pub fn status(&self) -> RestartStatus {
+        RestartStatus {
+            pending: self.pending.is_some(),
+            attempt: self.attempts,
+            max_attempts: self.max_attempts,
+            next_attempt_at: self.next_attempt_at.filter(|_| self.pending.is_some()),
+        }
+    }
+}
+
+// Forget the crash history and drop any pending restart, e.g. when the user
+// starts or stops the runner themselves.
+pub fn reset_restarts(state: &RunnerState) {
+    let mut restart = state.restart.lock().unwrap();
+    restart.attempts = 0;
+    restart.pending = None;
+    restart.next_attempt_at = None;
+    restart.cancel = None;
+}
+
+#[derive(Clone, Serialize)]
+struct RestartCancelledEvent {
+    attempt: u32,
+}
+
+// Abort a scheduled auto-restart and leave the runner stopped.
+#[tauri::command]
+pub async fn cancel_pending_restart(app: AppHandle, state: tauri::State<'_, RunnerState>) -> Result<String, String> {
+    let attempt = {
+        let mut restart = state.restart.lock().unwrap();
+        if restart.pending.is_none() {
+            return Err("No restart is pending".to_string());
+        }
+        if let Some(cancel) = restart.cancel.take() {
+            let _ = cancel.send(());
+        }
+        let attempt = restart.attempts;
+        restart.attempts = 0;
+        restart.pending = None;
+        restart.next_attempt_at = None;
+        attempt
+    };
+
+    info!("Cancelled pending auto-restart (attempt {})", attempt);
+    lifecycle::record(LifecycleKind::Restart, None, None, format!("Auto-restart attempt {} cancelled", attempt));
+    // Failed from the crash until now; the runner is simply idle
+    *state.health.lock().unwrap() = Health::Stopped;
+    let _ = app.emit_all("restart-cancelled", RestartCancelledEvent { attempt });
+    let _ = app.emit_all("runner-status", crate::runner_status(&state));
+    Ok("Pending restart cancelled".to_string())
+}
+
+#[tauri::command]
+pub async fn get_restart_state(state: tauri::State<'_, RunnerState>) -> Result<RestartStatus, String> {
+    Ok(state.restart.lock().unwrap().status())
+}
+
+#[derive(Clone, Serialize)]
+struct CrashEvent {
+    exit_code: Option<i32>,
+    status: String,
+    report: Option<CrashReport>,
+}
+
+// stderr lines searched for the final traceback
+const TRACEBACK_SEARCH_LINES: usize = 500;
+
+#[derive(Clone, Serialize)]
+struct RestartExhaustedEvent {
+    attempts: u32,
+}
+
+enum Poll {
+    Running(u32),
+    Exited(Box<ManagedProcess>, ExitStatus),
+}
+
+// Watch the process started as `run_id` until it exits on its own. Returns
+// quietly once the process is stopped or replaced, since whoever did that
+// already updated the state.
+pub fn watch(app: AppHandle, run_id: u64) {
+    tauri::async_runtime::spawn(async move {
+        let mut system = System::new();
+        let mut polls = 0u32;
+
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            let state = app.state::<RunnerState>();
+
+            let poll = {
+                let mut process_guard = state.python_process.lock().unwrap();
+                let process = match process_guard.as_mut() {
+                    Some(process) if process.run_id == run_id => process,
+                    _ => return,
+                };
+                match process.child.try_wait() {
+                    Ok(Some(status)) => {
+                        let process = process_guard.take().unwrap();
+                        *state.is_running.lock().unwrap() = false;
+                        *state.metrics.lock().unwrap() = None;
+                        *state.health.lock().unwrap() =
+                            if status.success() { Health::Stopped } else { Health::Failed };
+                        Poll::Exited(Box::new(process), status)
+                    }
+                    Ok(None) => Poll::Running(process.child.id()),
+                    Err(e) => {
+                        warn!("Failed to poll Python runner status: {}", e);
+                        continue;
+                    }
+                }
+            };
+
+            match poll {
+                Poll::Running(pid) => {
+                    polls += 1;
+                    if polls % MEMORY_SAMPLE_POLLS == 0 {
+                        let pid = Pid::from_u32(pid);
+                        if system.refresh_process(pid) {
+                            if let Some(process) = system.process(pid) {
+                                state.stats.record_memory(process.memory());
+                            }
+                        }
+                    }
+                }
+                Poll::Exited(mut process, status) => {
+                    // Let the last output lines reach the UI and log before reporting the exit
+                    process.join_readers(READER_JOIN_TIMEOUT).await;
+                    handle_exit(&app, &state, &process, status);
+                    return;
+                }
+            }
+        }
+    });
+}
+
+fn handle_exit(app: &AppHandle, state: &RunnerState, process: &ManagedProcess, status: ExitStatus) {
+    let pid = Some(process.child.id());
+    let runtime = process.started_at.elapsed();
+    let elevated = process.started_config.as_ref().is_some_and(|config| config.elevated);
+    if elevated && elevation::was_cancelled(status) {
+        // Not a crash: nothing ran, and restarting would just prompt again
+        let message = "Elevation was cancelled; the Python runner was not started";
+        warn!("{}", message);
+        lifecycle::record(LifecycleKind::StartFailed, pid, status.code(), message);
+        crate::record_last_error(state, message, &process.command_line, None);
+        let _ = app.emit_all("runner-elevation-cancelled", ());
+    } else if status.success() {
+        info!("Python runner exited cleanly");
+        lifecycle::record(LifecycleKind::Exit, pid, status.code(), "Exited on its own");
+    } else {
+        warn!("Python runner crashed: {}", status);
+        SessionStats::increment(&state.stats.crashes);
+        lifecycle::record(LifecycleKind::Crash, pid, status.code(), status.to_string());
+        let report = {
+            let stderr_buffer = state.stderr_buffer.lock().unwrap();
+            traceback::parse(&stderr_buffer.stderr_since(process.stderr_start, TRACEBACK_SEARCH_LINES))
+        };
+        if let Some(report) = &report {
+            warn!("Python runner raised {}: {}", report.exception.exception_type, report.exception.message);
+        }
+        record_crash(app, state, process.child.id(), status, report.clone());
+        crate::record_last_error(
+            state,
+            &format!("Python runner crashed: {}", status),
+            &process.command_line,
+            Some(process.stderr_start),
+        );
+        if state.config.lock().unwrap().notify_on_crash {
+            if let Err(e) = notify::alert(app, "Oriphim Runner crashed", &format!("The Python runner exited: {}", status)) {
+                warn!("{}", e);
+            }
+        }
+        let _ = app.emit_all(
+            "runner-crashed",
+            CrashEvent {
+                exit_code: status.code(),
+                status: status.to_string(),
+                report,
+            },
+        );
+        schedule_restart(app, state, status, runtime);
+    }
+    if process.crash_debug {
+        crash_debug::finished(app, status);
+    }
+    let _ = app.emit_all("runner-status", crate::runner_status(state));
+}
+
+fn record_crash(app: &AppHandle, state: &RunnerState, pid: u32, status: ExitStatus, report: Option<CrashReport>) {
+    *state.last_crash.lock().unwrap() = Some(LastCrash {
+        timestamp: Local::now(),
+        pid,
+        exit_code: status.code(),
+        status: status.to_string(),
+        dump_dir: None,
+        report,
+    });
+    let (command, max_dumps) = {
+        let config = state.config.lock().unwrap();
+        (config.crash_dump_command.clone(), config.crash_dump_max_count)
+    };
+    if let Some(command) = command {
+        crash_dump::collect(app, command, pid, status.code(), max_dumps);
+    }
+}
+
+fn schedule_restart(app: &AppHandle, state: &RunnerState, status: ExitStatus, runtime: Duration) {
+    let (auto_restart, filter, base_delay, max_attempts) = {
+        let config = state.config.lock().unwrap();
+        (
+            config.auto_restart,
+            config.restart_on_exit_codes.clone(),
+            Duration::from_secs(config.restart_delay_secs),
+            config.restart_max_attempts,
+        )
+    };
+    if !auto_restart {
+        return;
+    }
+    if !filter.should_restart(status.code()) {
+        info!("Not restarting Python runner: {} is excluded by restart_on_exit_codes", status);
+        return;
+    }
+    if !circuit::allow_restart(app, state, "crash") {
+        return;
+    }
+
+    // 2s, 4s, 8s, ... for the default delay
+    let delay_for = |attempt: u32| {
+        base_delay
+            .saturating_mul(1 << (attempt - 1).min(16))
+            .min(MAX_RESTART_DELAY)
+    };
+
+    let (attempt, token, delay, backoff, cancelled) = {
+        let mut restart = state.restart.lock().unwrap();
+        restart.max_attempts = max_attempts;
+        if runtime >= STABLE_RUNTIME {
+            restart.attempts = 0;
+        }
+        if restart.attempts >= max_attempts {
+            error!("Python runner crashed {} times in a row, giving up on auto-restart", restart.attempts);
+            lifecycle::record(
+                LifecycleKind::Restart,
+                None,
+                status.code(),
+                format!("Gave up after {} consecutive crashes", restart.attempts),
+            );
+            let _ = app.emit_all("runner-restart-exhausted", RestartExhaustedEvent { attempts: restart.attempts });
+            return;
+        }
+        restart.attempts += 1;
+        restart.next_token += 1;
+        restart.pending = Some(restart.next_token);
+        let delay = delay_for(restart.attempts);
+        restart.next_attempt_at = chrono::Duration::from_std(delay).ok().map(|delay| Local::now() + delay);
+        let (cancel, cancelled) = oneshot::channel();
+        restart.cancel = Some(cancel);
+        (restart.attempts, restart.next_token, delay, restart.status(), cancelled)
+    };
+
+    let _ = app.emit_all("runner-backoff", backoff);
+    warn!("Restarting Python runner in {}s (attempt {}/{})", delay.as_secs(), attempt, max_attempts);
+    lifecycle::record(
+        LifecycleKind::Restart,
+        None,
+        status.code(),
+        format!("Auto-restart in {}s (attempt {}/{})", delay.as_secs(), attempt, max_attempts),
+    );
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let scheduled = Instant::now();
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = cancelled => {
+                debug!("Pending restart (attempt {}) cancelled", attempt);
+                return;
+            }
+        }
+        let state = app.state::<RunnerState>();
+        {
+            let mut restart = state.restart.lock().unwrap();
+            // Cancelled just as the timer fired
+            if restart.pending != Some(token) {
+                return;
+            }
+            restart.pending = None;
+            restart.next_attempt_at = None;
+            restart.cancel = None;
+        }
+
+        info!("Auto-restarting Python runner after {}s", scheduled.elapsed().as_secs());
+        SessionStats::increment(&state.stats.restarts);
+        if let Err(e) = crate::start_runner(&app, &state, true).await {
+            error!("Auto-restart failed: {}", e);
+        }
+    });
+}
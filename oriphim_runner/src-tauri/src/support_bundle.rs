@@ -0,0 +1,239 @@
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use log::{info, warn};
+use tauri::{AppHandle, Manager};
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::config::{self, REDACTED};
+use crate::crash_dump::LastCrash;
+use crate::diagnose::{self, Diagnosis};
+use crate::lock_stats::{self, LockStats};
+use crate::logs;
+use crate::paths;
+use crate::stats::SessionStatsSnapshot;
+use crate::RunnerState;
+
+// Shorter values (`1`, `yes`) would blank out unrelated text
+const MIN_SECRET_LEN: usize = 4;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BundleFile {
+    pub name: String,
+    pub size_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SupportBundle {
+    pub path: String,
+    pub files: Vec<BundleFile>,
+    // Distinct secret values replaced across all files
+    pub redacted_values: usize,
+}
+
+#[derive(Serialize)]
+struct Manifest<'a> {
+    created_at: DateTime<Local>,
+    app_version: String,
+    files: &'a [BundleFile],
+    redacted_values: usize,
+}
+
+#[derive(Serialize)]
+struct AppInfo {
+    app_version: String,
+    os: &'static str,
+    arch: &'static str,
+    packaged: bool,
+    workspace: Option<PathBuf>,
+    status: serde_json::Value,
+    session: SessionStatsSnapshot,
+    // Debug builds only
+    lock_stats: Option<Vec<LockStats>>,
+}
+
+#[derive(Serialize)]
+struct Diagnostics {
+    last_error: Option<String>,
+    diagnosis: Option<Diagnosis>,
+    last_crash: Option<LastCrash>,
+}
+
+// Values of secret-looking variables in the app's environment and the running
+// process's, longest first so a secret containing another is replaced whole.
+fn secret_values(state: &RunnerState) -> Vec<String> {
+    let mut values: BTreeSet<String> = std::env::vars().filter(|(name, _)| config::is_secret_name(name)).map(|(_, value)| value).collect();
+    if let Some(process) = state.python_process.lock().unwrap().as_ref() {
+        values.extend(
+            process.environment.iter().filter(|(name, _)| config::is_secret_name(name)).map(|(_, value)| value.clone()),
+        );
+    }
+    let mut values: Vec<String> = values.into_iter().filter(|value| value.len() >= MIN_SECRET_LEN).collect();
+    values.sort_by_key(|value| std::cmp::Reverse(value.len()));
+    values
+}
+
+struct Redactor {
+    secrets: Vec<String>,
+    found: BTreeSet<usize>,
+}
+
+impl Redactor {
+    fn redact(&mut self, text: &str) -> String {
+        let mut text = text.to_string();
+        for (index, secret) in self.secrets.iter().enumerate() {
+            if text.contains(secret.as_str()) {
+                text = text.replace(secret.as_str(), REDACTED);
+                self.found.insert(index);
+            }
+        }
+        text
+    }
+}
+
+fn to_json(value: &impl Serialize) -> Result<String, String> {
+    serde_json::to_string_pretty(value).map_err(|e| format!("Failed to serialize support bundle: {}", e))
+}
+
+// Files copied from disk as `(name in the bundle, path)`; missing ones are skipped.
+fn disk_files() -> Vec<(String, PathBuf)> {
+    let mut files = Vec::new();
+    if let Some(path) = paths::lifecycle_file() {
+        files.push(("lifecycle.jsonl".to_string(), path));
+    }
+    if let Some(path) = paths::logs_dir().map(|dir| dir.join("runner.log")) {
+        files.push(("logs/runner.1.log".to_string(), logs::rotated_path(&path, 1)));
+        files.push(("logs/runner.log".to_string(), path));
+    }
+    if let Some(path) = paths::metrics_history_file() {
+        files.push(("metrics/metrics.1.jsonl".to_string(), logs::rotated_path(&path, 1)));
+        files.push(("metrics/metrics.jsonl".to_string(), path));
+    }
+    files
+}
+
+// Everything a maintainer needs to look into an issue, as one zip at `dest`
+// (or in it, if it's a directory): app info and runner status, diagnostics,
+// the lifecycle record, the runner log, metrics history and the config.
+// Secret-looking environment values are redacted from every file.
+#[tauri::command]
+pub async fn create_support_bundle(
+    dest: String,
+    app: AppHandle,
+    state: tauri::State<'_, RunnerState>,
+) -> Result<SupportBundle, String> {
+    let mut dest = PathBuf::from(dest.trim());
+    if dest.is_dir() {
+        dest.push(default_file_name());
+    }
+
+    let app_version = app.package_info().version.to_string();
+    let app_info = AppInfo {
+        app_version: app_version.clone(),
+        os: std::env::consts::OS,
+        arch: std::env::consts::ARCH,
+        packaged: paths::is_packaged(),
+        workspace: paths::workspace(),
+        status: serde_json::to_value(crate::runner_status(&state)).map_err(|e| format!("Failed to serialize runner status: {}", e))?,
+        session: state.stats.snapshot(),
+        lock_stats: cfg!(debug_assertions).then(|| lock_stats::snapshot(&state)),
+    };
+    let diagnostics = Diagnostics {
+        last_error: state.last_error.lock().unwrap().clone(),
+        diagnosis: diagnose::diagnose(&state).ok(),
+        last_crash: state.last_crash.lock().unwrap().clone(),
+    };
+    let config = state.config.lock().unwrap().redacted();
+    let config = toml::to_string_pretty(&config).map_err(|e| format!("Failed to serialize config: {}", e))?;
+    let generated = vec![
+        ("app-info.json".to_string(), to_json(&app_info)?),
+        ("diagnostics.json".to_string(), to_json(&diagnostics)?),
+        ("config.toml".to_string(), config),
+    ];
+    let redactor = Redactor { secrets: secret_values(&state), found: BTreeSet::new() };
+
+    let bundle = tauri::async_runtime::spawn_blocking(move || write_bundle(&dest, &app_version, generated, redactor))
+        .await
+        .map_err(|e| format!("Failed to create support bundle: {}", e))??;
+    info!(
+        "Created support bundle {} ({} files, {} secret values redacted)",
+        bundle.path,
+        bundle.files.len(),
+        bundle.redacted_values
+    );
+    Ok(bundle)
+}
+
+fn default_file_name() -> String {
+    Local::now().format("oriphim-support-%Y%m%d-%H%M%S.zip").to_string()
+}
+
+fn write_bundle(dest: &Path, app_version: &str, generated: Vec<(String, String)>, mut redactor: Redactor) -> Result<SupportBundle, String> {
+    let mut contents = generated;
+    for (name, path) in disk_files() {
+        match fs::read(&path) {
+            Ok(bytes) => contents.push((name, String::from_utf8_lossy(&bytes).into_owned())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => warn!("Leaving {} out of the support bundle: {}", path.display(), e),
+        }
+    }
+    let contents: Vec<(String, String)> = contents.into_iter().map(|(name, text)| (name, redactor.redact(&text))).collect();
+    let files: Vec<BundleFile> =
+        contents.iter().map(|(name, text)| BundleFile { name: name.clone(), size_bytes: text.len() as u64 }).collect();
+    let manifest = Manifest {
+        created_at: Local::now(),
+        app_version: app_version.to_string(),
+        files: &files,
+        redacted_values: redactor.found.len(),
+    };
+    let manifest = to_json(&manifest)?;
+
+    if let Some(dir) = dest.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let write_error = |e: &dyn std::fmt::Display| format!("Failed to write {}: {}", dest.display(), e);
+    let file = File::create(dest).map_err(|e| write_error(&e))?;
+    let mut zip = ZipWriter::new(file);
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (name, text) in std::iter::once(("manifest.json".to_string(), manifest)).chain(contents) {
+        zip.start_file(name, options).map_err(|e| write_error(&e))?;
+        zip.write_all(text.as_bytes()).map_err(|e| write_error(&e))?;
+    }
+    zip.finish().map_err(|e| write_error(&e))?;
+
+    Ok(SupportBundle {
+        path: dest.display().to_string(),
+        files,
+        redacted_values: redactor.found.len(),
+    })
+}
+
+// The tray's "Create Support Bundle…": ask where to save it, then write it there.
+pub fn save_from_tray(app: &AppHandle) {
+    let app = app.clone();
+    tauri::api::dialog::FileDialogBuilder::new()
+        .set_title("Create Support Bundle")
+        .set_file_name(&default_file_name())
+        .add_filter("Zip archive", &["zip"])
+        .save_file(move |path| {
+            // None when the dialog was cancelled
+            if let Some(path) = path {
+                tauri::async_runtime::spawn(async move {
+                    let state = app.state::<RunnerState>();
+                    match create_support_bundle(path.to_string_lossy().to_string(), app.clone(), state).await {
+                        Ok(bundle) => {
+                            let _ = app.emit_all("support-bundle-created", bundle);
+                        }
+                        Err(e) => {
+                            warn!("Failed to create support bundle from tray: {}", e);
+                            let _ = app.emit_all("support-bundle-failed", e);
+                        }
+                    }
+                });
+            }
+        });
+}
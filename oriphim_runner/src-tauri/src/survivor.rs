@@ -0,0 +1,161 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use sysinfo::{Pid, System};
+use tauri::{AppHandle, Manager};
+use log::{info, warn};
+
+use crate::lifecycle::{self, LifecycleKind};
+use crate::process::{ManagedProcess, ProcessIdentity};
+use crate::saved_state::SavedState;
+use crate::RunnerState;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+// The runner last started, saved in state.json. Nothing clears it: once the
+// process is gone its identity no longer matches, which also guards against a
+// reused PID.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunnerRecord {
+    pub pid: u32,
+    pub command_line: String,
+    pub started_at: DateTime<Local>,
+    identity: ProcessIdentity,
+}
+
+// A runner from an earlier session of the app that was still alive when this
+// one started, e.g. after the app crashed. It isn't our child, so its output
+// can't be read and its exit code is never known; it can only be watched and
+// stopped. A script that keeps printing to the old pipes will usually fail on
+// its next write unless its output was redirected.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReconnectedProcess {
+    pub pid: u32,
+    pub command_line: String,
+    pub started_at: DateTime<Local>,
+    pub reconnected_at: DateTime<Local>,
+    // Set once the process is seen to be gone, or was stopped from here
+    pub exited_at: Option<DateTime<Local>>,
+    #[serde(skip)]
+    identity: ProcessIdentity,
+}
+
+// Save `process` as the runner to look for at the next launch.
+pub fn remember(process: &ManagedProcess) {
+    let record = process.identity().map(|identity| RunnerRecord {
+        pid: process.child.id(),
+        command_line: process.command_line.clone(),
+        started_at: identity.start_time().unwrap_or(process.spawned_at),
+        identity: identity.clone(),
+    });
+    if let Err(e) = SavedState::update(|saved| saved.last_runner = record) {
+        warn!("Failed to save the runner's PID: {}", e);
+    }
+}
+
+// Look for the runner saved by the last session and take note of it if it is
+// still running. Called once at startup.
+pub fn detect(state: &RunnerState) -> Option<ReconnectedProcess> {
+    let record = SavedState::load().last_runner?;
+    if !record.identity.is_alive(record.pid) {
+        return None;
+    }
+    info!(
+        "Python runner from the previous session is still running (PID {}, started {}); reconnected to it",
+        record.pid,
+        record.started_at.format("%Y-%m-%d %H:%M:%S")
+    );
+    lifecycle::record(LifecycleKind::Start, Some(record.pid), None, "Reconnected to a runner left by the previous session");
+    let process = ReconnectedProcess {
+        pid: record.pid,
+        command_line: record.command_line,
+        started_at: record.started_at,
+        reconnected_at: Local::now(),
+        exited_at: None,
+        identity: record.identity,
+    };
+    *state.reconnected.lock().unwrap() = Some(process.clone());
+    Some(process)
+}
+
+// PID of the reconnected runner while it is still alive.
+pub fn alive_pid(state: &RunnerState) -> Option<u32> {
+    state
+        .reconnected
+        .lock()
+        .unwrap()
+        .as_ref()
+        .filter(|process| process.exited_at.is_none())
+        .map(|process| process.pid)
+}
+
+// Poll the reconnected runner until it exits.
+pub fn watch(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            let state = app.state::<RunnerState>();
+            let pid = {
+                let mut reconnected = state.reconnected.lock().unwrap();
+                let process = match reconnected.as_mut() {
+                    Some(process) if process.exited_at.is_none() => process,
+                    _ => return,
+                };
+                if process.identity.is_alive(process.pid) {
+                    continue;
+                }
+                process.exited_at = Some(Local::now());
+                process.pid
+            };
+            info!("Reconnected Python runner (PID {}) has exited", pid);
+            lifecycle::record(LifecycleKind::Exit, Some(pid), None, "Reconnected runner exited");
+            let _ = app.emit_all("reconnected-runner-exited", pid);
+            let _ = app.emit_all("runner-status", crate::runner_status(&state));
+            return;
+        }
+    });
+}
+
+// Kill the reconnected runner, if it is alive, and return its PID. With `tree`
+// its process group goes too on Unix; Windows can only end the process itself.
+pub fn stop(state: &RunnerState, tree: bool) -> Option<u32> {
+    let pid = {
+        let mut reconnected = state.reconnected.lock().unwrap();
+        let process = reconnected.as_mut().filter(|process| process.exited_at.is_none())?;
+        let alive = process.identity.is_alive(process.pid);
+        process.exited_at = Some(Local::now());
+        if !alive {
+            return None;
+        }
+        process.pid
+    };
+    if kill(pid, tree) {
+        info!("Killed the reconnected Python runner (PID {})", pid);
+    } else {
+        warn!("Failed to kill the reconnected Python runner (PID {})", pid);
+    }
+    lifecycle::record(LifecycleKind::Stop, Some(pid), None, "Stopped the reconnected runner");
+    Some(pid)
+}
+
+fn kill(pid: u32, tree: bool) -> bool {
+    // spawn() made the runner lead its own process group
+    #[cfg(unix)]
+    {
+        if tree && unsafe { libc::killpg(pid as libc::pid_t, libc::SIGKILL) } == 0 {
+            return true;
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = tree;
+    let mut system = System::new();
+    let pid = Pid::from_u32(pid);
+    system.refresh_process(pid) && system.process(pid).is_some_and(|process| process.kill())
+}
+
+// Whether this session reconnected to a runner the previous one left running.
+// None when there was nothing to reconnect to.
+#[tauri::command]
+pub async fn get_reconnected_process(state: tauri::State<'_, RunnerState>) -> Result<Option<ReconnectedProcess>, String> {
+    Ok(state.reconnected.lock().unwrap().clone())
+}
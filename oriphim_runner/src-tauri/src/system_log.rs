@@ -0,0 +1,104 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::config::RunnerConfig;
+
+// Mirrors `system_log` / `system_log_stderr` so callers without access to the
+// config, like the lifecycle log and output readers, can check them cheaply.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static FORWARD_STDERR: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Copy)]
+pub enum Level {
+    Info,
+    Warning,
+    Error,
+}
+
+// Apply the system log settings from a newly loaded config.
+pub fn configure(config: &RunnerConfig) {
+    ENABLED.store(config.system_log, Ordering::Relaxed);
+    FORWARD_STDERR.store(config.system_log && config.system_log_stderr, Ordering::Relaxed);
+}
+
+pub fn forward_stderr() -> bool {
+    FORWARD_STDERR.load(Ordering::Relaxed)
+}
+
+// Send a message to syslog on Unix or the Application event log on Windows,
+// if `system_log` is enabled. The file logs are written either way.
+pub fn write(level: Level, message: &str) {
+    if ENABLED.load(Ordering::Relaxed) {
+        platform::write(level, message);
+    }
+}
+
+#[cfg(unix)]
+mod platform {
+    use std::ffi::CString;
+    use std::sync::Once;
+
+    use super::Level;
+
+    // openlog keeps the pointer, so the ident must live forever
+    const IDENT: &[u8] = b"oriphim-runner\0";
+    static OPEN: Once = Once::new();
+
+    pub fn write(level: Level, message: &str) {
+        OPEN.call_once(|| unsafe { libc::openlog(IDENT.as_ptr().cast(), libc::LOG_PID, libc::LOG_USER) });
+        let priority = match level {
+            Level::Info => libc::LOG_INFO,
+            Level::Warning => libc::LOG_WARNING,
+            Level::Error => libc::LOG_ERR,
+        };
+        // Interior NULs can't be passed through; drop them
+        let message = CString::new(message.replace('\0', "")).unwrap_or_default();
+        unsafe { libc::syslog(priority, b"%s\0".as_ptr().cast(), message.as_ptr()) };
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::sync::OnceLock;
+    use windows_sys::Win32::System::EventLog::{
+        RegisterEventSourceW, ReportEventW, EVENTLOG_ERROR_TYPE, EVENTLOG_INFORMATION_TYPE, EVENTLOG_WARNING_TYPE,
+    };
+
+    use super::Level;
+
+    // Without a registered message file Event Viewer prefixes entries with a
+    // "description cannot be found" note, but the text is still recorded.
+    static SOURCE: OnceLock<isize> = OnceLock::new();
+
+    fn wide(text: &str) -> Vec<u16> {
+        text.encode_utf16().chain(std::iter::once(0)).collect()
+    }
+
+    pub fn write(level: Level, message: &str) {
+        let source = *SOURCE.get_or_init(|| unsafe {
+            RegisterEventSourceW(std::ptr::null(), wide("Oriphim Runner").as_ptr())
+        });
+        if source == 0 {
+            return;
+        }
+        let event_type = match level {
+            Level::Info => EVENTLOG_INFORMATION_TYPE,
+            Level::Warning => EVENTLOG_WARNING_TYPE,
+            Level::Error => EVENTLOG_ERROR_TYPE,
+        };
+        let message = wide(message);
+        let strings = [message.as_ptr()];
+        unsafe {
+            ReportEventW(
+                source,
+                event_type,
+                0,
+                0,
+                std::ptr::null_mut(),
+                1,
+                0,
+                strings.as_ptr(),
+                std::ptr::null(),
+            );
+        }
+    }
+}
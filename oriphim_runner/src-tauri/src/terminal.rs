@@ -0,0 +1,78 @@
+use std::path::Path;
+use std::process::Command;
+
+#[cfg(all(unix, not(target_os = "macos")))]
+use crate::launcher;
+
+// Window title for the runner's terminal
+#[cfg(windows)]
+const TITLE: &str = "Oriphim Runner";
+
+// Build a command that runs `program args...` in `working_dir` inside a new
+// terminal window, for interactive debugging. The returned process lasts as
+// long as the program does where the platform allows it, so status and exit
+// codes keep working; the program's output goes to the terminal, not the app.
+//
+// On Windows `start /wait` opens a console window (Windows Terminal when that's
+// the default) and waits for it. On macOS Terminal.app runs the command and an
+// AppleScript loop waits until its tab is no longer busy; stopping the runner
+// only ends that wait, not the program in Terminal. On Linux the first of
+// x-terminal-emulator, gnome-terminal, konsole and xterm found is used.
+#[cfg(windows)]
+pub fn command(program: &str, args: &[String], working_dir: &Path) -> Result<Command, String> {
+    let mut command = Command::new("cmd");
+    command
+        .args(["/c", "start", TITLE, "/wait", "/d"])
+        .arg(working_dir)
+        .arg(program)
+        .args(args)
+        .current_dir(working_dir);
+    Ok(command)
+}
+
+#[cfg(target_os = "macos")]
+pub fn command(program: &str, args: &[String], working_dir: &Path) -> Result<Command, String> {
+    let shell = format!(
+        "cd {} && exec {}",
+        shell_quote(&working_dir.to_string_lossy()),
+        std::iter::once(program).chain(args.iter().map(String::as_str)).map(shell_quote).collect::<Vec<_>>().join(" ")
+    );
+    // AppleScript string literals escape \ and "
+    let literal = shell.replace('\\', "\\\\").replace('"', "\\\"");
+    let script = format!(
+        "tell application \"Terminal\"\nactivate\nset runnerTab to do script \"{}\"\nrepeat while busy of runnerTab\ndelay 1\nend repeat\nend tell",
+        literal
+    );
+    let mut command = Command::new("osascript");
+    command.args(["-e", &script]).current_dir(working_dir);
+    Ok(command)
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+pub fn command(program: &str, args: &[String], working_dir: &Path) -> Result<Command, String> {
+    // gnome-terminal hands the window to a server and returns unless told to wait
+    const TERMINALS: &[(&str, &[&str])] = &[
+        ("x-terminal-emulator", &["-e"]),
+        ("gnome-terminal", &["--wait", "--"]),
+        ("konsole", &["-e"]),
+        ("xterm", &["-e"]),
+    ];
+    let (terminal, terminal_args) = TERMINALS
+        .iter()
+        .find_map(|(name, terminal_args)| launcher::find(name).map(|path| (path, *terminal_args)))
+        .ok_or("No terminal emulator found (tried x-terminal-emulator, gnome-terminal, konsole and xterm)")?;
+    let mut command = Command::new(terminal);
+    command
+        .args(terminal_args)
+        .args(["sh", "-c", "cd \"$0\" && exec \"$@\""])
+        .arg(working_dir)
+        .arg(program)
+        .args(args)
+        .current_dir(working_dir);
+    Ok(command)
+}
+
+#[cfg(target_os = "macos")]
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
@@ -0,0 +1,111 @@
+use regex::Regex;
+use serde::Serialize;
+use std::sync::OnceLock;
+
+// Innermost frames kept per exception
+const MAX_FRAMES: usize = 10;
+
+const CHAIN_SEPARATORS: &[&str] = &[
+    "During handling of the above exception, another exception occurred:",
+    "The above exception was the direct cause of the following exception:",
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Frame {
+    pub file: String,
+    pub line: u32,
+    pub function: String,
+    pub code: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PythonException {
+    pub exception_type: String,
+    pub message: String,
+    // Innermost last, as Python prints them
+    pub frames: Vec<Frame>,
+}
+
+// The exception that ended the process, plus any it was raised while handling
+// or from, oldest first.
+#[derive(Debug, Clone, Serialize)]
+pub struct CrashReport {
+    #[serde(flatten)]
+    pub exception: PythonException,
+    pub chain: Vec<PythonException>,
+}
+
+fn frame_regex() -> &'static Regex {
+    static FRAME: OnceLock<Regex> = OnceLock::new();
+    FRAME.get_or_init(|| Regex::new(r#"^\s*File "(.+)", line (\d+), in (.+)$"#).unwrap())
+}
+
+fn exception_regex() -> &'static Regex {
+    static EXCEPTION: OnceLock<Regex> = OnceLock::new();
+    EXCEPTION.get_or_init(|| Regex::new(r"^([A-Za-z_][\w.]*)(?::\s?(.*))?$").unwrap())
+}
+
+// Find the last traceback in `lines` (stderr, oldest first) and its chain.
+// Returns None when no complete traceback is present.
+pub fn parse(lines: &[&str]) -> Option<CrashReport> {
+    let mut finished: Vec<PythonException> = Vec::new();
+    let mut current: Option<PythonException> = None;
+    // Whether a chain separator came after the last finished exception
+    let mut chained = false;
+
+    for line in lines {
+        let line = line.trim_end();
+
+        if line.trim_start() == "Traceback (most recent call last):" {
+            if !chained {
+                // An unrelated earlier traceback
+                finished.clear();
+            }
+            chained = false;
+            current = Some(PythonException {
+                exception_type: String::new(),
+                message: String::new(),
+                frames: Vec::new(),
+            });
+            continue;
+        }
+        if CHAIN_SEPARATORS.contains(&line.trim()) {
+            chained = !finished.is_empty();
+            continue;
+        }
+
+        let exception = match current.as_mut() {
+            Some(exception) => exception,
+            None => continue,
+        };
+        if let Some(captures) = frame_regex().captures(line) {
+            exception.frames.push(Frame {
+                file: captures[1].to_string(),
+                line: captures[2].parse().unwrap_or(0),
+                function: captures[3].to_string(),
+                code: None,
+            });
+        } else if line.starts_with(char::is_whitespace) {
+            // Source line under a frame; skip the ^^^ markers newer Pythons add
+            let code = line.trim();
+            if let Some(frame) = exception.frames.last_mut() {
+                if frame.code.is_none() && !code.chars().all(|c| matches!(c, '^' | '~')) {
+                    frame.code = Some(code.to_string());
+                }
+            }
+        } else if let Some(captures) = exception_regex().captures(line) {
+            let mut exception = current.take().unwrap();
+            exception.exception_type = captures[1].to_string();
+            exception.message = captures.get(2).map_or("", |m| m.as_str()).to_string();
+            let skip = exception.frames.len().saturating_sub(MAX_FRAMES);
+            exception.frames.drain(..skip);
+            finished.push(exception);
+        } else {
+            // Not part of a traceback after all
+            current = None;
+        }
+    }
+
+    let exception = finished.pop()?;
+    Some(CrashReport { exception, chain: finished })
+}
@@ -0,0 +1,109 @@
+use std::fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+use log::{info, warn};
+
+use crate::config::RunnerConfig;
+use crate::logs::LogFormat;
+use crate::output::WatchMatcher;
+use crate::paths;
+use crate::saved_state::SavedState;
+use crate::{schedule, system_log, RunnerState};
+
+// Re-activate the workspace chosen in a previous session, if it still exists.
+// Must run before the config is first loaded.
+pub fn restore() {
+    let file = match paths::workspace_file() {
+        Some(file) => file,
+        None => return,
+    };
+    let dir = match fs::read_to_string(&file) {
+        Ok(contents) => PathBuf::from(contents.trim()),
+        Err(_) => return,
+    };
+    if dir.is_dir() {
+        info!("Using workspace {}", dir.display());
+        paths::set_workspace(Some(dir));
+    } else {
+        warn!("Workspace {} no longer exists, using global settings", dir.display());
+    }
+}
+
+fn persist(dir: Option<&PathBuf>) -> Result<(), String> {
+    let file = paths::workspace_file().ok_or("Could not find home directory")?;
+    match dir {
+        Some(dir) => {
+            if let Some(parent) = file.parent() {
+                fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+            }
+            fs::write(&file, dir.display().to_string()).map_err(|e| format!("Failed to write {}: {}", file.display(), e))
+        }
+        None => match fs::remove_file(&file) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(format!("Failed to remove {}: {}", file.display(), e))
+            }
+            _ => Ok(()),
+        },
+    }
+}
+
+// Switch to the project at `path`, or back to the global settings with None.
+// The workspace's `.oriphim/config.toml` is used if it has one, and logs and
+// saved state move to `<path>/.oriphim`. The runner must be stopped first.
+#[tauri::command]
+pub async fn set_workspace(
+    path: Option<String>,
+    app: AppHandle,
+    state: tauri::State<'_, RunnerState>,
+) -> Result<String, String> {
+    if *state.is_running.lock().unwrap() {
+        return Err("Stop the runner before switching workspaces".to_string());
+    }
+
+    let dir = match path.map(|path| path.trim().to_string()).filter(|path| !path.is_empty()) {
+        Some(path) => {
+            let dir = PathBuf::from(&path)
+                .canonicalize()
+                .map_err(|e| format!("Invalid workspace {}: {}", path, e))?;
+            if !dir.is_dir() {
+                return Err(format!("Workspace {} is not a directory", dir.display()));
+            }
+            Some(dir)
+        }
+        None => None,
+    };
+    persist(dir.as_ref())?;
+    paths::set_workspace(dir.clone());
+
+    let config = RunnerConfig::load().unwrap_or_else(|error| {
+        let _ = app.emit_all("config-error", error);
+        RunnerConfig::default()
+    });
+    *state.watch_patterns.lock().unwrap() = WatchMatcher::compile_all(&config.watch_patterns);
+    *state.log_format.lock().unwrap() = LogFormat::compile(&config.log_format);
+    *state.log_file.lock().unwrap() = crate::open_log_file(&config);
+    state.log_buffer.lock().unwrap().set_cap(config.log_buffer_lines);
+    system_log::configure(&config);
+    *state.config.lock().unwrap() = config;
+
+    let saved = SavedState::load();
+    let mut schedule = state.schedule.lock().unwrap();
+    *schedule = saved.schedule;
+    schedule::update_tooltip(&app, &schedule);
+
+    Ok(match dir {
+        Some(dir) => {
+            info!("Switched to workspace {}", dir.display());
+            format!("Workspace set to {}", dir.display())
+        }
+        None => {
+            info!("Switched to global settings");
+            "Using global settings".to_string()
+        }
+    })
+}
+
+#[tauri::command]
+pub async fn get_workspace() -> Result<Option<String>, String> {
+    Ok(paths::workspace().map(|dir| dir.display().to_string()))
+}
@@ -17,6 +17,8 @@ class OriphimRunnerUI {
         this.connectionStatus = 'disconnected';
         this.brokerStatus = 'disconnected';
         this.updateInterval = null;
+        // Log entry per stream showing a progress bar that's still redrawing
+        this.liveLines = {};
         
         // Initialize UI
         this.initializeUI();
@@ -51,6 +53,7 @@ class OriphimRunnerUI {
         
         // Clear logs
         document.getElementById('clear-logs-btn').addEventListener('click', () => this.clearLogs());
+        document.getElementById('log-window-btn').addEventListener('click', () => this.handleOpenLogWindow());
         
         // Setup modal
         document.getElementById('setup-save-btn').addEventListener('click', () => this.handleSetupSave());
@@ -81,7 +84,260 @@ class OriphimRunnerUI {
             await listen('log-update', (event) => {
                 this.handleLogUpdate(event.payload);
             });
-            
+
+            // Listen for raw Python runner output
+            await listen('runner-stdout', (event) => {
+                this.clearLiveLine('stdout');
+                this.addLogEntry(event.payload.line, 'info');
+            });
+            await listen('runner-stderr', (event) => {
+                this.clearLiveLine('stderr');
+                this.addLogEntry(event.payload.line, 'error');
+            });
+            // Both streams in arrival order when `merge_output` is enabled
+            await listen('runner-output', (event) => {
+                this.clearLiveLine(event.payload.stream);
+                this.addLogEntry(event.payload.line, event.payload.stream === 'stderr' ? 'error' : 'info');
+            });
+            // Progress bar redraws (carriage_return = "overwrite") update one line in place
+            await listen('runner-overwrite', (event) => {
+                const { stream, line } = event.payload;
+                const entry = this.liveLines[stream];
+                if (entry && entry.isConnected) {
+                    entry.querySelector('.log-message').textContent = line;
+                } else {
+                    this.liveLines[stream] = this.addLogEntry(line, stream === 'stderr' ? 'error' : 'info');
+                }
+            });
+
+            // Listen for watch pattern matches
+            await listen('pattern-matched', (event) => {
+                this.addLogEntry(`Pattern "${event.payload.pattern}" matched: ${event.payload.line}`, 'warning');
+            });
+
+            // Listen for an invalid config.toml (the app falls back to defaults)
+            await listen('config-error', (event) => {
+                const { message, line, column, backup } = event.payload;
+                const where = line ? ` (line ${line}, column ${column})` : '';
+                this.addLogEntry(`Invalid config.toml${where}: ${message}`, 'error');
+                this.showToast(backup ? `Config file is invalid, backed up to ${backup}` : 'Config file is invalid', 'error', 8000);
+            });
+
+            // Listen for stops that had to force-kill the runner
+            await listen('runner-force-killed', (event) => {
+                const { grace_secs, message } = event.payload;
+                this.addLogEntry(`Runner force-killed after ${grace_secs}s grace period`, 'warning');
+                if (message) {
+                    this.showToast(message, 'warning', 8000);
+                }
+            });
+
+            // Listen for elevated launches (config `elevated`)
+            await listen('runner-elevation-prompt', () => {
+                this.addLogEntry('Starting runner elevated - approve the elevation prompt', 'warning');
+                this.showToast('An elevation prompt will appear to start the runner with elevated privileges', 'warning', 8000);
+            });
+
+            await listen('runner-elevation-cancelled', () => {
+                this.addLogEntry('Elevation was cancelled; runner not started', 'error');
+                this.showToast('Elevation was cancelled, so the runner was not started', 'error');
+                this.checkRunnerStatus();
+            });
+
+            // Listen for starts slower than startup_latency_warn_ms
+            await listen('runner-slow-start', (event) => {
+                const { latency_ms, threshold_ms } = event.payload;
+                this.addLogEntry(`Runner took ${latency_ms}ms to become ready (threshold ${threshold_ms}ms)`, 'warning');
+            });
+
+            // Listen for stops by idle_shutdown_secs
+            await listen('runner-idle-stopped', (event) => {
+                this.addLogEntry(`Runner stopped after ${event.payload.idle_secs}s idle`, 'info');
+                this.checkRunnerStatus();
+            });
+
+            // Listen for reset_to_defaults
+            await listen('config-reset', (event) => {
+                const { backup_dir } = event.payload;
+                this.addLogEntry(backup_dir ? `Settings reset, backup saved to ${backup_dir}` : 'Settings reset', 'warning');
+                this.showToast('Settings were reset to defaults', 'info');
+                this.checkRunnerStatus();
+            });
+
+            // Listen for a newer bundled main.py than the one in use
+            await listen('script-update-available', (event) => {
+                const { installed, installed_version, bundled_version } = event.payload;
+                const versions = installed_version && bundled_version ? ` (${installed_version} -> ${bundled_version})` : '';
+                this.addLogEntry(`A newer runner script is available for ${installed}${versions}`, 'info');
+                this.showToast('Runner script update available', 'info');
+            });
+
+            // Listen for stop progress, to show a long graceful shutdown is still going
+            await listen('runner-stopping-progress', (event) => {
+                const { stage, elapsed_ms, grace_secs } = event.payload;
+                const text = document.getElementById('status-text');
+                switch (stage) {
+                    case 'signal_sent':
+                        this.addLogEntry(`Stop signal sent, waiting up to ${grace_secs}s`, 'info');
+                        break;
+                    case 'waiting':
+                        this.updateStatusIndicator('stopping');
+                        text.textContent = `Stopping... ${Math.floor(elapsed_ms / 1000)}s / ${grace_secs}s`;
+                        break;
+                    case 'escalating':
+                        this.addLogEntry('Runner did not stop in time, force killing', 'warning');
+                        break;
+                    case 'stopped':
+                        this.checkRunnerStatus();
+                        break;
+                }
+            });
+
+            // Listen for stops by max_runtime_secs
+            await listen('runner-max-runtime-reached', (event) => {
+                this.addLogEntry(`Runner stopped after reaching its ${event.payload.max_runtime_secs}s max runtime`, 'info');
+                this.checkRunnerStatus();
+            });
+
+            // Listen for the restart circuit breaker halting automatic restarts
+            await listen('circuit-breaker-tripped', (event) => {
+                const { reason, restarts, window_secs } = event.payload;
+                this.addLogEntry(`${restarts} automatic restarts in ${window_secs}s (last: ${reason}); automatic restarts halted until reset`, 'error');
+                this.showToast('Automatic restarts halted', 'error');
+            });
+
+            // Listen for output dropped by output_max_lines_per_sec
+            await listen('runner-output-throttled', (event) => {
+                const { dropped, limit_per_sec } = event.payload;
+                this.addLogEntry(`Output is flooding: dropped ${dropped} lines over the ${limit_per_sec}/s limit`, 'warning');
+            });
+
+            // Listen for each new process, logged as a session header
+            await listen('runner-started', (event) => {
+                const { pid, command_line, working_dir, interpreter_version, session_label } = event.payload;
+                const python = interpreter_version ? `Python ${interpreter_version}, ` : '';
+                const label = session_label ? ` [${session_label}]` : '';
+                this.addLogEntry(`=== Runner started${label}: pid ${pid}, ${python}in ${working_dir} ===`, 'info');
+                this.addLogEntry(`$ ${command_line}`, 'info');
+            });
+
+            // Listen for drift in the script, interpreter or packages between starts
+            await listen('environment-changed', (event) => {
+                const { changed, packages_added, packages_removed } = event.payload;
+                const packages = changed.includes('packages') ? ` (${packages_added.length} packages added, ${packages_removed.length} removed)` : '';
+                this.addLogEntry(`Environment changed since the last start: ${changed.join(', ')}${packages}`, 'warning');
+            });
+
+            // Listen for thread counts over thread_count_warn
+            await listen('runner-thread-warning', (event) => {
+                const { thread_count, limit } = event.payload;
+                this.addLogEntry(`Runner has ${thread_count} threads, over the ${limit} thread limit (possible thread leak)`, 'warning');
+            });
+
+            // Listen for snooze_notifications ending
+            await listen('notifications-resumed', () => {
+                this.addLogEntry('Notifications resumed', 'info');
+            });
+
+            // Listen for a runner that outlived the previous session of the app
+            await listen('reconnected-to-runner', (event) => {
+                const { pid, started_at } = event.payload;
+                this.addLogEntry(`Reconnected to the runner left running by the last session (pid ${pid}, started ${new Date(started_at).toLocaleString()}); its output isn't shown`, 'info');
+                this.showToast('Runner is still running from the last session', 'info');
+                this.checkRunnerStatus();
+            });
+
+            await listen('reconnected-runner-exited', (event) => {
+                this.addLogEntry(`Reconnected runner (pid ${event.payload}) has exited`, 'warning');
+                this.checkRunnerStatus();
+            });
+
+            // Listen for the end of a run armed by arm_crash_debug
+            await listen('crash-debug-finished', (event) => {
+                const { crashed, exit_code } = event.payload;
+                if (crashed) {
+                    this.addLogEntry(`Crash debugging captured a crash (exit code ${exit_code}); see the stderr log for the fault handler dump`, 'warning');
+                } else {
+                    this.addLogEntry('Crash debug run exited cleanly; arm it again to keep looking', 'info');
+                }
+                this.checkRunnerStatus();
+            });
+
+            // Listen for the runner log becoming unwritable, and recovering
+            await listen('logging-disabled', (event) => {
+                const { path, reason, retry_secs } = event.payload;
+                this.addLogEntry(`Can't write ${path} (${reason}); file logging is paused and retried every ${retry_secs}s`, 'warning');
+            });
+
+            await listen('logging-enabled', (event) => {
+                this.addLogEntry(`Writing ${event.payload} again`, 'info');
+            });
+
+            // Listen for switch_script changing what runs
+            await listen('script-switched', (event) => {
+                const { previous, script } = event.payload;
+                this.addLogEntry(`=== Switched script: ${previous} -> ${script} ===`, 'info');
+            });
+
+            // Listen for support bundles created from the tray
+            await listen('support-bundle-created', (event) => {
+                const { path, files, redacted_values } = event.payload;
+                this.addLogEntry(`Support bundle saved to ${path} (${files.length} files, ${redacted_values} secrets redacted)`, 'info');
+            });
+
+            await listen('support-bundle-failed', (event) => {
+                this.addLogEntry(`Failed to create support bundle: ${event.payload}`, 'error');
+            });
+
+            // Listen for restarts starting a new console (console_per_restart) or continuing this one
+            await listen('console-new-session', (event) => {
+                this.startNewConsole(event.payload.pid);
+            });
+
+            await listen('console-continued', (event) => {
+                this.addLogEntry(event.payload.separator, 'info');
+            });
+
+            // Listen for restarts triggered by memory_limit_mb
+            await listen('runner-memory-restart', (event) => {
+                const mb = (bytes) => Math.round(bytes / (1024 * 1024));
+                const { memory_bytes, limit_bytes, over_secs } = event.payload;
+                this.addLogEntry(`Restarting runner: ${mb(memory_bytes)} MB over the ${mb(limit_bytes)} MB limit for ${over_secs}s`, 'warning');
+            });
+
+            // Listen for a heartbeat_file that stopped being touched
+            await listen('runner-stalled', (event) => {
+                this.addLogEntry(`No heartbeat from the runner for ${event.payload.stale_secs}s`, 'warning');
+            });
+
+            // Listen for saved settings that only apply after a restart
+            await listen('config-requires-restart', (event) => {
+                const fields = event.payload.fields.join(', ');
+                this.addLogEntry(`Restart the runner to apply: ${fields}`, 'warning');
+                this.showToast('Some settings apply after a restart', 'warning', 6000);
+            });
+
+            // Listen for the supervisor waiting to restart a crashed runner
+            await listen('runner-backoff', (event) => {
+                const { attempt, max_attempts, next_attempt_at } = event.payload;
+                const seconds = Math.max(0, Math.round((new Date(next_attempt_at) - Date.now()) / 1000));
+                this.addLogEntry(`Restarting in ${seconds}s (attempt ${attempt}/${max_attempts})`, 'warning');
+            });
+
+            await listen('restart-cancelled', () => {
+                this.addLogEntry('Auto-restart cancelled', 'info');
+            });
+
+            // Starting returns right away; these report how it went
+            await listen('runner-ready', () => {
+                this.addLogEntry('Runner is ready', 'success');
+                this.checkRunnerStatus();
+            });
+            await listen('runner-start-failed', (event) => {
+                this.addLogEntry(event.payload.error, 'error');
+                this.checkRunnerStatus();
+            });
+
         } catch (error) {
             console.error('Error setting up event listeners:', error);
         }
@@ -92,6 +348,7 @@ class OriphimRunnerUI {
         this.updateInterval = setInterval(() => {
             this.updateTimestamp();
             this.checkRunnerStatus();
+            this.updateSessionStats();
         }, 5000);
     }
     
@@ -112,11 +369,15 @@ class OriphimRunnerUI {
     
     async checkRunnerStatus() {
         try {
-            const isRunning = await invoke('get_runner_status');
-            this.isRunning = isRunning;
+            const status = await invoke('get_runner_status');
+            this.isRunning = status.running;
             
             // Update status indicator based on runner state
-            if (isRunning) {
+            if (status.stopping) {
+                this.updateStatusIndicator(status.queued === 'start' ? 'restarting' : 'stopping');
+            } else if (status.running && status.health === 'starting') {
+                this.updateStatusIndicator('starting');
+            } else if (status.running) {
                 if (this.currentJob) {
                     this.updateStatusIndicator('running');
                 } else {
@@ -132,6 +393,30 @@ class OriphimRunnerUI {
         }
     }
     
+    async updateSessionStats() {
+        try {
+            const stats = await invoke('get_session_stats');
+            const formatBytes = (bytes) => {
+                const units = ['B', 'KB', 'MB', 'GB'];
+                let i = 0;
+                while (bytes >= 1024 && i < units.length - 1) {
+                    bytes /= 1024;
+                    i++;
+                }
+                return `${bytes.toFixed(i === 0 ? 0 : 1)} ${units[i]}`;
+            };
+            
+            document.getElementById('stats-starts').textContent = `${stats.starts} / ${stats.stops}`;
+            document.getElementById('stats-restarts').textContent = `${stats.restarts} / ${stats.crashes}`;
+            document.getElementById('stats-lines').textContent = `${stats.stdout_lines + stats.stderr_lines}`;
+            document.getElementById('stats-bytes').textContent = formatBytes(stats.stdout_bytes + stats.stderr_bytes);
+            document.getElementById('stats-memory').textContent =
+                stats.peak_memory_bytes > 0 ? formatBytes(stats.peak_memory_bytes) : '-';
+        } catch (error) {
+            console.error('Error fetching session stats:', error);
+        }
+    }
+    
     // UI Update Methods
     updateStatusIndicator(status) {
         const dot = document.getElementById('status-dot');
@@ -148,6 +433,19 @@ class OriphimRunnerUI {
                 dot.classList.add('running');
                 text.textContent = 'Running';
                 break;
+            case 'starting':
+                dot.classList.add('starting');
+                text.textContent = 'Starting...';
+                break;
+            case 'stopping':
+                dot.classList.add('starting');
+                text.textContent = 'Stopping...';
+                break;
+            // A start queued behind a stop that is still finishing
+            case 'restarting':
+                dot.classList.add('starting');
+                text.textContent = 'Stopping, then starting...';
+                break;
             case 'disconnected':
             default:
                 text.textContent = 'Disconnected';
@@ -196,10 +494,17 @@ class OriphimRunnerUI {
         const timeStr = timestamp.toLocaleTimeString();
         
         logEntry.className = `log-entry ${level}`;
-        logEntry.innerHTML = `
-            <span class="log-time">[${timeStr}]</span>
-            <span class="log-message">${message}</span>
-        `;
+
+        // Runner output is untrusted, so it only ever goes in as text
+        const time = document.createElement('span');
+        time.className = 'log-time';
+        time.textContent = `[${timeStr}]`;
+
+        const text = document.createElement('span');
+        text.className = 'log-message';
+        text.textContent = message;
+
+        logEntry.append(time, text);
         
         logsContent.appendChild(logEntry);
         
@@ -211,6 +516,16 @@ class OriphimRunnerUI {
         if (entries.length > 100) {
             logsContent.removeChild(entries[0]);
         }
+        return logEntry;
+    }
+
+    // The finished line replaces the redraws that led up to it
+    clearLiveLine(stream) {
+        const entry = this.liveLines[stream];
+        if (entry) {
+            entry.remove();
+            delete this.liveLines[stream];
+        }
     }
     
     clearLogs() {
@@ -218,6 +533,14 @@ class OriphimRunnerUI {
         logsContent.innerHTML = '';
         this.addLogEntry('Logs cleared');
     }
+
+    // Earlier sessions stay in runner.log
+    startNewConsole(pid) {
+        const logsContent = document.getElementById('logs-content');
+        logsContent.innerHTML = '';
+        this.liveLines = {};
+        this.addLogEntry(`New session (PID ${pid})`, 'info');
+    }
     
     updateTimestamp() {
         const timestamp = document.getElementById('last-update');
@@ -303,14 +626,8 @@ class OriphimRunnerUI {
             this.addLogEntry('Restarting runner...', 'warning');
             this.showToast('Restarting runner...', 'info');
             
-            // Stop current runner
-            await invoke('stop_python_runner');
-            
-            // Wait a moment
-            await new Promise(resolve => setTimeout(resolve, 1000));
-            
-            // Start new runner
-            await invoke('start_python_runner');
+            // Stop, start and wait until the new runner reports ready
+            await invoke('safe_restart');
             
             this.addLogEntry('Runner restarted successfully', 'success');
             this.showToast('Runner restarted', 'success');
@@ -329,6 +646,15 @@ class OriphimRunnerUI {
         }
     }
     
+    async handleOpenLogWindow() {
+        try {
+            await invoke('open_log_window');
+        } catch (error) {
+            console.error('Error opening log window:', error);
+            this.showToast('Failed to open log window', 'error');
+        }
+    }
+    
     async handleOpenLogs() {
         try {
             await invoke('open_logs_folder');
@@ -377,6 +703,11 @@ class OriphimRunnerUI {
         
         this.addLogEntry(`Runner status: ${payload.running ? 'Running' : 'Stopped'}`, 
                         payload.running ? 'success' : 'warning');
+        if (payload.stopping) {
+            this.updateStatusIndicator(payload.queued === 'start' ? 'restarting' : 'stopping');
+        } else if (payload.running && payload.health === 'starting') {
+            this.updateStatusIndicator('starting');
+        }
         
         // Update pause button
         const pauseBtn = document.getElementById('pause-btn');
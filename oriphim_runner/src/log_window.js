@@ -0,0 +1,50 @@
+/**
+ * Oriphim Runner - Detached log window
+ *
+ * Streams the Python runner's output into its own window
+ */
+
+const { listen } = window.__TAURI__.event;
+
+// The console keeps more history than the main window's activity feed
+const MAX_LOG_ENTRIES = 1000;
+
+function addLogEntry(message, level = 'info') {
+    const logsContent = document.getElementById('logs-content');
+    const logEntry = document.createElement('div');
+    logEntry.className = `log-entry ${level}`;
+
+    const time = document.createElement('span');
+    time.className = 'log-time';
+    time.textContent = `[${new Date().toLocaleTimeString()}]`;
+
+    const text = document.createElement('span');
+    text.className = 'log-message';
+    text.textContent = message;
+
+    logEntry.append(time, text);
+
+    // Only follow the output if the user hasn't scrolled up to read something
+    const atBottom = logsContent.scrollTop + logsContent.clientHeight >= logsContent.scrollHeight - 4;
+    logsContent.appendChild(logEntry);
+    if (atBottom) {
+        logsContent.scrollTop = logsContent.scrollHeight;
+    }
+
+    while (logsContent.children.length > MAX_LOG_ENTRIES) {
+        logsContent.removeChild(logsContent.firstChild);
+    }
+}
+
+document.getElementById('clear-logs-btn').addEventListener('click', () => {
+    document.getElementById('logs-content').innerHTML = '';
+});
+
+listen('runner-stdout', (event) => addLogEntry(event.payload.line, 'info'));
+listen('runner-stderr', (event) => addLogEntry(event.payload.line, 'error'));
+listen('runner-output', (event) => {
+    addLogEntry(event.payload.line, event.payload.stream === 'stderr' ? 'error' : 'info');
+});
+listen('pattern-matched', (event) => {
+    addLogEntry(`Pattern "${event.payload.pattern}" matched: ${event.payload.line}`, 'warning');
+});
@@ -13,6 +13,8 @@ Architecture:
 - UI: HTML/CSS/JS frontend via Tauri or tkinter
 """
 
+__version__ = "1.0.0"
+
 import asyncio
 import sys
 import os
@@ -34,6 +36,17 @@ from engine import TradingEngine
 from storage import LocalDataManager
 from ui_manager import UIManager
 
+def expand_args_file():
+    """Replace an `@<file>` argument with the arguments listed in the file, one per line"""
+    args = []
+    for arg in sys.argv[1:]:
+        if arg.startswith("@") and Path(arg[1:]).is_file():
+            args.extend(Path(arg[1:]).read_text(encoding="utf-8").splitlines())
+        else:
+            args.append(arg)
+    sys.argv[1:] = args
+
+
 # Configure logging
 def setup_logging():
     """Set up comprehensive logging for the Runner"""
@@ -235,7 +248,7 @@ class OriphimRunner:
         """Send comprehensive status update to cloud"""
         try:
             status = {
-                'runner_version': '1.0.0',
+                'runner_version': __version__,
                 'connection_status': self.connection_status,
                 'broker_status': self.broker_status,
                 'is_paused': self.is_paused,
@@ -359,6 +372,7 @@ async def main():
 
 
 if __name__ == "__main__":
+    expand_args_file()
     # Run the async main function
     if sys.platform == "win32":
         # Windows-specific event loop policy